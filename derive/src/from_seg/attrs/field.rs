@@ -1,5 +1,9 @@
-use super::toplevel::AlsoNeeds;
-use crate::util::get_attr_value;
+use super::{
+    magic::Magic,
+    padding::{Align, Pad, Padding},
+    toplevel::AlsoNeeds,
+};
+use crate::util::{find_idents, get_attr_value, get_raw_values};
 use alloc::rc::Rc;
 use pmhelp::{
     exts::{GetBaseTypes as _, OptionTypeExt as _, ParseBufferExt as _},
@@ -17,8 +21,8 @@ use syn::{
     parse::{Parse, ParseStream, Result},
     punctuated::Punctuated,
     token::Paren,
-    Expr, ExprCall, ExprField, ExprLit, ExprPath, Field, Ident, Lit, LitInt, Path, Token, Type,
-    TypePath,
+    Expr, ExprCall, ExprField, ExprLit, ExprPath, Field, Ident, Lit, LitInt, LitStr, Path, Token,
+    Type, TypePath,
 };
 
 mod kw {
@@ -41,6 +45,30 @@ mod kw {
     syn::custom_keyword!(parse_each);
     syn::custom_keyword!(remaining);
     syn::custom_keyword!(subseg);
+    syn::custom_keyword!(uleb128);
+    syn::custom_keyword!(sleb128);
+    syn::custom_keyword!(varint);
+    syn::custom_keyword!(zigzag);
+    syn::custom_keyword!(vlq);
+    syn::custom_keyword!(cstr);
+    syn::custom_keyword!(str);
+    syn::custom_keyword!(utf16);
+    syn::custom_keyword!(len);
+    syn::custom_keyword!(len_prefix);
+    syn::custom_keyword!(padded);
+    syn::custom_keyword!(pad);
+    syn::custom_keyword!(verify_checksum);
+    syn::custom_keyword!(verify_crc32);
+    syn::custom_keyword!(verify_adler32);
+    syn::custom_keyword!(range);
+    syn::custom_keyword!(decompress);
+    syn::custom_keyword!(max_decompressed_size);
+    syn::custom_keyword!(magic);
+    syn::custom_keyword!(align);
+    syn::custom_keyword!(pad_to);
+    syn::custom_keyword!(pad_after);
+    syn::custom_keyword!(calc);
+    syn::custom_keyword!(ctx);
 }
 
 pub struct AlsoPassEntry {
@@ -197,6 +225,36 @@ impl ToTokens for ErrorIf {
     }
 }
 
+/// Verifies that the field (a stored checksum) matches the checksum of a range of the segment.
+struct Verify {
+    checksum: TokenStream,
+    range: TokenStream,
+}
+
+impl Verify {
+    /// Parses the `range = start..end` part. Since ranges can't be parsed as expressions without
+    /// syn's full feature, everything after the equals sign is used as is.
+    fn parse_range(stream: ParseStream, checksum: TokenStream) -> Result<Self> {
+        stream.parse::<kw::range>()?;
+        stream.parse::<Token![=]>()?;
+        Ok(Self {
+            checksum,
+            range: stream.parse()?,
+        })
+    }
+
+    fn get_verify_tokens(&self, value: &Ident, offset: &Ident, suffix: TokenStream) -> TokenStream {
+        let checksum = &self.checksum;
+        let range = &self.range;
+        quote! {
+            {
+                let range: ::core::ops::Range<usize> = #range;
+                segment.verify_checksum::<#checksum>(range.start, range.end, #value, #offset)#suffix;
+            }
+        }
+    }
+}
+
 enum FromOption {
     Default,
     Type(Box<Type>),
@@ -259,6 +317,141 @@ impl Parse for SizeOption {
     }
 }
 
+#[derive(Clone, Copy)]
+enum VarIntKind {
+    Uleb128,
+    Sleb128,
+    VarInt,
+    ZigZag,
+    Vlq,
+}
+
+impl VarIntKind {
+    fn get_read_tokens(&self, ty: &Type) -> TokenStream {
+        let method = match self {
+            Self::Uleb128 => quote! {next_uleb128},
+            Self::Sleb128 => quote! {next_sleb128},
+            Self::VarInt => quote! {next_varint},
+            Self::ZigZag => quote! {next_zigzag},
+            Self::Vlq => quote! {next_vlq},
+        };
+        quote! {{
+            let offset = segment.current_offset();
+            segment.#method().and_then(|value| {
+                ::segsource::derive_extras::convert_var_int::<#ty, _>(value, offset)
+            })
+        }}
+    }
+
+    fn get_write_tokens(&self, value: TokenStream) -> TokenStream {
+        let (method, conv) = match self {
+            Self::Uleb128 => (quote! {put_uleb128}, quote! {to_var_u64}),
+            Self::Sleb128 => (quote! {put_sleb128}, quote! {to_var_i64}),
+            Self::VarInt => (quote! {put_varint}, quote! {to_var_u64}),
+            Self::ZigZag => (quote! {put_zigzag}, quote! {to_var_i64}),
+            Self::Vlq => (quote! {put_vlq}, quote! {to_var_u64}),
+        };
+        quote! {{
+            use ::segsource::derive_extras::VarIntValue as _;
+            let offset = segment.current_offset();
+            segment.#method(#value.#conv(offset)?)?;
+        }}
+    }
+}
+
+enum StrKind {
+    CStr,
+    Len(Box<Expr>),
+    LenPrefix(Box<Type>),
+    Padded(Box<Expr>, Option<Box<Expr>>),
+    Utf16CStr,
+    Utf16Len(Box<Expr>),
+}
+
+impl StrKind {
+    fn exprs(&self) -> Vec<&Expr> {
+        match self {
+            Self::Len(len) | Self::Utf16Len(len) => vec![len],
+            Self::Padded(width, Some(pad)) => vec![width, pad],
+            Self::Padded(width, None) => vec![width],
+            _ => Vec::new(),
+        }
+    }
+
+    fn get_pad(pad: &Option<Box<Expr>>) -> TokenStream {
+        match pad {
+            Some(pad) => pad.to_token_stream(),
+            None => quote! {0},
+        }
+    }
+
+    fn get_read_tokens(&self, ty: &Type) -> TokenStream {
+        // UTF-16 has to be decoded into a new string, so it can't be borrowed from the segment.
+        let owned = match self {
+            Self::Utf16CStr => Some(quote! {segment.next_utf16_cstr()}),
+            Self::Utf16Len(len) => Some(quote! {segment.next_utf16((#len) as usize)}),
+            _ => None,
+        };
+        if let Some(read) = owned {
+            return quote! { #read.map(::core::convert::Into::<#ty>::into) };
+        }
+        let read = match self {
+            Self::CStr => quote! {segment.next_cstr()},
+            Self::Len(len) => quote! {segment.next_str((#len) as usize)},
+            Self::LenPrefix(prefix) => quote! {segment.next_prefixed_str::<#prefix>()},
+            Self::Padded(width, pad) => {
+                let pad = Self::get_pad(pad);
+                quote! {segment.next_padded_str((#width) as usize, #pad)}
+            }
+            Self::Utf16CStr | Self::Utf16Len(_) => unreachable!(),
+        };
+        quote! { #read.map(<#ty as ::core::convert::From<&str>>::from) }
+    }
+
+    fn get_write_tokens(&self, value: TokenStream) -> TokenStream {
+        let write = match self {
+            Self::CStr => quote! {segment.put_cstr(value)?;},
+            Self::Len(_) => quote! {segment.put_str(value)?;},
+            Self::LenPrefix(prefix) => quote! {segment.put_prefixed_str::<#prefix>(value)?;},
+            Self::Padded(width, pad) => {
+                let pad = Self::get_pad(pad);
+                quote! {segment.put_padded_str(value, (#width) as usize, #pad)?;}
+            }
+            Self::Utf16CStr => quote! {segment.put_utf16_cstr(value)?;},
+            Self::Utf16Len(_) => quote! {segment.put_utf16(value)?;},
+        };
+        quote! {{
+            let value: &str = ::core::convert::AsRef::<str>::as_ref(#value);
+            #write
+        }}
+    }
+}
+
+impl Parse for StrKind {
+    fn parse(stream: ParseStream) -> Result<Self> {
+        if stream.peek_and_consume(kw::len_prefix) {
+            stream.parse::<Token![=]>()?;
+            Ok(Self::LenPrefix(stream.parse()?))
+        } else if stream.peek_and_consume(kw::len) {
+            stream.parse::<Token![=]>()?;
+            Ok(Self::Len(stream.parse()?))
+        } else if stream.peek_and_consume(kw::padded) {
+            stream.parse::<Token![=]>()?;
+            let width = stream.parse()?;
+            let pad = if stream.peek_and_consume(Token![,]) {
+                stream.parse::<kw::pad>()?;
+                stream.parse::<Token![=]>()?;
+                Some(stream.parse()?)
+            } else {
+                None
+            };
+            Ok(Self::Padded(width, pad))
+        } else {
+            Err(stream.error("Expected one of len, len_prefix, or padded"))
+        }
+    }
+}
+
 enum FromSegEntry {
     Skip,
     FromIter,
@@ -279,6 +472,17 @@ enum FromSegEntry {
     ParseEach,
     Subseg(Box<Expr>),
     While(Box<Expr>),
+    VarInt(VarIntKind),
+    Str(StrKind),
+    Verify(Box<Verify>),
+    Decompress(Ident),
+    MaxDecompressedSize(Box<Expr>),
+    Magic(Box<Magic>),
+    Align(Box<Align>),
+    PadTo(Box<Pad>),
+    PadAfter(Box<Pad>),
+    Calc(Box<Expr>),
+    Ctx(Vec<TokenStream>),
 }
 
 impl FromSegEntry {
@@ -297,6 +501,17 @@ impl FromSegEntry {
             Self::MoveBy(value) => from_seg.move_by = Some(value),
             Self::Subseg(value) => from_seg.subseg = Some(value),
             Self::While(value) => from_seg.take_while = Some(value),
+            Self::VarInt(value) => from_seg.var_int = Some(value),
+            Self::Str(value) => from_seg.string = Some(value),
+            Self::Verify(value) => from_seg.verify = Some(value),
+            Self::Decompress(value) => from_seg.decompress = Some(value),
+            Self::MaxDecompressedSize(value) => from_seg.max_decompressed_size = Some(value),
+            Self::Magic(value) => from_seg.magic = Some(value),
+            Self::Align(value) => from_seg.padding.align = Some(value),
+            Self::PadTo(value) => from_seg.padding.pad_to = Some(value),
+            Self::PadAfter(value) => from_seg.padding.pad_after = Some(value),
+            Self::Calc(value) => from_seg.calc = Some(value),
+            Self::Ctx(value) => from_seg.ctx = Some(value),
             Self::MapEach(value) => {
                 from_seg.from_iter = true;
                 from_seg.map_each = Some(value);
@@ -324,6 +539,75 @@ impl Parse for FromSegEntry {
             Ok(Self::While(from_parens!(stream).parse()?))
         // } else if stream.peek_and_consume(kw::subseg) {
         //     Ok(Self::Subseg(from_parens!(stream).parse()?))
+        } else if stream.peek_and_consume(kw::uleb128) {
+            Ok(Self::VarInt(VarIntKind::Uleb128))
+        } else if stream.peek_and_consume(kw::sleb128) {
+            Ok(Self::VarInt(VarIntKind::Sleb128))
+        } else if stream.peek_and_consume(kw::varint) {
+            Ok(Self::VarInt(VarIntKind::VarInt))
+        } else if stream.peek_and_consume(kw::zigzag) {
+            Ok(Self::VarInt(VarIntKind::ZigZag))
+        } else if stream.peek_and_consume(kw::vlq) {
+            Ok(Self::VarInt(VarIntKind::Vlq))
+        } else if stream.peek_and_consume(kw::cstr) {
+            Ok(Self::Str(StrKind::CStr))
+        } else if stream.peek_and_consume(kw::str) {
+            Ok(Self::Str(from_parens!(stream).parse()?))
+        } else if stream.peek_and_consume(kw::utf16) {
+            if stream.peek(Paren) {
+                let stream = from_parens!(stream);
+                stream.parse::<kw::len>()?;
+                stream.parse::<Token![=]>()?;
+                Ok(Self::Str(StrKind::Utf16Len(stream.parse()?)))
+            } else {
+                Ok(Self::Str(StrKind::Utf16CStr))
+            }
+        } else if stream.peek_and_consume(kw::verify_crc32) {
+            let stream = from_parens!(stream);
+            let checksum = quote! {::segsource::checksum::Crc32};
+            Ok(Self::Verify(Box::new(Verify::parse_range(
+                &stream, checksum,
+            )?)))
+        } else if stream.peek_and_consume(kw::verify_adler32) {
+            let stream = from_parens!(stream);
+            let checksum = quote! {::segsource::checksum::Adler32};
+            Ok(Self::Verify(Box::new(Verify::parse_range(
+                &stream, checksum,
+            )?)))
+        } else if stream.peek_and_consume(kw::verify_checksum) {
+            let stream = from_parens!(stream);
+            let checksum = stream.parse::<Type>()?.to_token_stream();
+            stream.parse::<Token![,]>()?;
+            Ok(Self::Verify(Box::new(Verify::parse_range(
+                &stream, checksum,
+            )?)))
+        } else if stream.peek_and_consume(kw::decompress) {
+            stream.parse::<Token![=]>()?;
+            let format = stream.parse::<LitStr>()?;
+            let method = match format.value().as_str() {
+                "deflate" => "decompress_deflate",
+                "zlib" => "decompress_zlib",
+                "gzip" => "decompress_gzip",
+                "lz4" => "decompress_lz4",
+                "zstd" => "decompress_zstd",
+                _ => {
+                    return Err(syn::Error::new(
+                        format.span(),
+                        "Expected one of deflate, zlib, gzip, lz4, or zstd",
+                    ))
+                }
+            };
+            Ok(Self::Decompress(Ident::new(method, format.span())))
+        } else if stream.peek_and_consume(kw::max_decompressed_size) {
+            Ok(Self::MaxDecompressedSize(from_parens!(stream).parse()?))
+        } else if stream.peek_and_consume(kw::magic) {
+            Ok(Self::Magic(stream.parse()?))
+        } else if stream.peek_and_consume(kw::align) {
+            Ok(Self::Align(stream.parse()?))
+        } else if stream.peek_and_consume(kw::pad_to) {
+            Ok(Self::PadTo(stream.parse()?))
+        } else if stream.peek_and_consume(kw::pad_after) {
+            Ok(Self::PadAfter(stream.parse()?))
         } else if stream.peek_and_consume(kw::parser) {
             Ok(Self::Parser(from_parens!(stream).parse()?))
         } else if stream.peek_and_consume(kw::also_pass) {
@@ -332,6 +616,10 @@ impl Parse for FromSegEntry {
             Ok(Self::ErrorIf(stream.parse()?))
         } else if stream.peek_and_consume(Token![if]) {
             Ok(Self::If(from_parens!(stream).parse()?))
+        } else if stream.peek_and_consume(kw::calc) {
+            Ok(Self::Calc(get_attr_value(stream)?))
+        } else if stream.peek_and_consume(kw::ctx) {
+            Ok(Self::Ctx(get_raw_values(&from_parens!(stream))?))
         } else if stream.peek_and_consume(kw::skip) {
             Ok(Self::Skip)
         } else if stream.peek_and_consume(kw::from_iter) {
@@ -370,6 +658,7 @@ impl Parse for FromSegEntry {
 
 pub struct FromSegField {
     tmp_var: Ident,
+    name: Option<Ident>,
     ty: Type,
     base_type: Option<Type>,
     generating_try_from: bool,
@@ -393,6 +682,17 @@ pub struct FromSegField {
     take_while: Option<Box<Expr>>,
     make_mut: bool,
     parse_each: bool,
+    var_int: Option<VarIntKind>,
+    string: Option<StrKind>,
+    verify: Option<Box<Verify>>,
+    decompress: Option<Ident>,
+    max_decompressed_size: Option<Box<Expr>>,
+    magic: Option<Box<Magic>>,
+    padding: Padding,
+    calc: Option<Box<Expr>>,
+    ctx: Option<Vec<TokenStream>>,
+    field_name: String,
+    type_name: Option<String>,
 }
 
 impl FromSegField {
@@ -418,6 +718,7 @@ impl FromSegField {
         };
         Self {
             tmp_var,
+            name: None,
             ty,
             base_type,
             generating_try_from,
@@ -441,6 +742,17 @@ impl FromSegField {
             take_while: None,
             make_mut: false,
             parse_each: false,
+            var_int: None,
+            string: None,
+            verify: None,
+            decompress: None,
+            max_decompressed_size: None,
+            magic: None,
+            padding: Default::default(),
+            calc: None,
+            ctx: None,
+            field_name: String::new(),
+            type_name: None,
         }
     }
 
@@ -454,6 +766,11 @@ impl FromSegField {
     fn get_assign_value(&self) -> TokenStream {
         let mut base = if self.skip {
             self.get_default_value()
+        } else if let Some(calc) = &self.calc {
+            // Calculated values are used as is.
+            return calc.to_token_stream();
+        } else if let Some(method) = &self.decompress {
+            self.get_decompressed_value(method)
         } else if self.parse_each {
             self.get_parse_each()
         } else if self.from_iter || self.map_each.is_some() {
@@ -487,6 +804,51 @@ impl FromSegField {
         }
     }
 
+    /// Decompresses the field's data (either its size or, if it doesn't have one, everything
+    /// that's left) and then parses the field from the decompressed data. Errors from parsing
+    /// the decompressed data record where the compressed data came from.
+    fn get_decompressed_value(&self, method: &Ident) -> TokenStream {
+        let statement_suffix = self.get_statement_suffix();
+        let size = match &self.size {
+            Some(SizeOption::Expr(size)) => quote! {#size as usize},
+            _ => quote! {segment.remaining()},
+        };
+        let ty = self.base_type.as_ref().unwrap_or(&self.ty);
+        let parse = if self.generating_try_from {
+            quote! {<#ty as ::core::convert::TryFrom<_>>::try_from(decompressed)}
+        } else {
+            quote! {<#ty as ::core::convert::From<_>>::from(decompressed)}
+        };
+        let decompress = match &self.max_decompressed_size {
+            Some(max_size) => {
+                let method = quote::format_ident!("{}_with_max_size", method);
+                quote! {compressed.#method((#max_size) as usize)}
+            }
+            None => quote! {compressed.#method()},
+        };
+        let try_suffix = if self.generating_try_from {
+            Some(quote! {
+                .map_err(|error| {
+                    use ::segsource::derive_extras::{AddErrorContext as _, KeepErrorContext as _};
+                    (&mut ::segsource::derive_extras::ErrorContext(Some(error)))
+                        .in_compressed(
+                            compressed.lower_offset_limit(),
+                            compressed.upper_offset_limit(),
+                        )
+                })
+                #statement_suffix
+            })
+        } else {
+            None
+        };
+        quote! {{
+            let compressed = segment.next_n(#size)#statement_suffix;
+            let source = #decompress#statement_suffix;
+            let decompressed = &::segsource::Source::all(&source)#statement_suffix;
+            #parse#try_suffix
+        }}
+    }
+
     fn get_parse_each(&self) -> TokenStream {
         let take_while = self
             .take_while
@@ -503,10 +865,12 @@ impl FromSegField {
         let gen_val = self.get_simple_assign_val_no_suffix(self.also_pass.get_args());
         if self.generating_try_from && matches!(self.try_, TryOption::Default | TryOption::Try) {
             let suffix = self.get_try_suffix_ignore_none();
+            let index_context = self.get_error_context(quote! {at_index}, quote! {index});
             quote! {
                 ::segsource::derive_extras::iter_to_result(::core::iter::repeat(true)
                     #iter_def
-                    .map(|_| #gen_val)
+                    .enumerate()
+                    .map(|(index, _)| (#gen_val)#index_context)
                     #take_while
                 )#suffix
                 #map_each
@@ -535,6 +899,17 @@ impl FromSegField {
         let conv_type = &self.also_pass.get_conv_type();
         if let Some(parser) = &self.parser {
             quote! {#parser}
+        } else if let Some(var_int) = &self.var_int {
+            var_int.get_read_tokens(self.base_type.as_ref().unwrap_or(&self.ty))
+        } else if let Some(string) = &self.string {
+            string.get_read_tokens(self.base_type.as_ref().unwrap_or(&self.ty))
+        } else if let Some(ctx) = &self.ctx {
+            let ty = self.base_type.as_ref().unwrap_or(&self.ty);
+            let ctx = match ctx.as_slice() {
+                [value] => value.clone(),
+                values => quote! {(#(#values),*)},
+            };
+            quote! {<#ty as ::segsource::FromSegment<'_, _, _>>::parse(#value, #ctx)}
         } else if let Some(FromOption::Default) = &self.from {
             let ty = self.base_type.as_ref().unwrap_or(&self.ty);
             quote! {<#ty as ::core::convert::From<#conv_type>>::from(#value)}
@@ -566,10 +941,42 @@ impl FromSegField {
         }
     }
 
+    /// Records which field was being parsed (and the segment it was being parsed from) on any
+    /// errors. Errors that aren't segsource's [`Error`] are passed through unchanged.
+    fn get_error_context(&self, method: TokenStream, args: TokenStream) -> Option<TokenStream> {
+        self.type_name.as_ref().map(|_| {
+            quote! {
+                .map_err(|error| {
+                    use ::segsource::derive_extras::{AddErrorContext as _, KeepErrorContext as _};
+                    (&mut ::segsource::derive_extras::ErrorContext(Some(error))).#method(
+                        #args,
+                        segment.lower_offset_limit(),
+                        segment.upper_offset_limit(),
+                    )
+                })
+            }
+        })
+    }
+
+    fn get_field_context(&self) -> Option<TokenStream> {
+        let type_name = self.type_name.as_ref()?;
+        let field_name = &self.field_name;
+        self.get_error_context(quote! {in_field}, quote! {#type_name, #field_name})
+    }
+
+    /// Sets the name of the type (or enum variant) the field belongs to. Until this is set, no
+    /// context is added to errors.
+    pub fn set_type_name(&mut self, type_name: String) {
+        self.type_name = Some(type_name);
+    }
+
     fn get_try_suffix_ignore_none(&self) -> TokenStream {
+        let context = self.get_field_context();
         match self.try_ {
-            TryOption::Try => quote! {?},
-            TryOption::Default | TryOption::AsIs if self.generating_try_from => quote! {?},
+            TryOption::Try => quote! {#context?},
+            TryOption::Default | TryOption::AsIs if self.generating_try_from => {
+                quote! {#context?}
+            }
             TryOption::Unwrap => quote! {.unwrap()},
             TryOption::Default | TryOption::AsIs if !self.generating_try_from => {
                 quote! {.unwrap()}
@@ -592,12 +999,19 @@ impl FromSegField {
         }
     }
 
-    fn get_pre_assign_statements(&self) -> Option<TokenStream> {
-        let suffix = if self.generating_try_from {
-            quote! {?}
+    /// The suffix used for statements that return segsource's [`Error`] (rather than the
+    /// field's).
+    fn get_statement_suffix(&self) -> TokenStream {
+        if self.generating_try_from {
+            let context = self.get_field_context();
+            quote! {#context?}
         } else {
             quote! {.unwrap()}
-        };
+        }
+    }
+
+    fn get_pre_assign_statements(&self) -> Option<TokenStream> {
+        let suffix = self.get_statement_suffix();
         if self.move_to.is_some() {
             self.move_to
                 .as_ref()
@@ -611,21 +1025,206 @@ impl FromSegField {
         }
     }
 
+    /// Makes statements that go along with reading the field (e.g. checking its magic) only run
+    /// if the field is actually read.
+    fn guard(&self, statements: Option<TokenStream>) -> Option<TokenStream> {
+        let statements = statements?;
+        Some(match &self.if_ {
+            Some(predicate) => quote! {
+                if #predicate {
+                    #statements
+                }
+            },
+            None => statements,
+        })
+    }
+
     pub fn tmp_var(&self) -> Ident {
         self.tmp_var.clone()
     }
+
+    /// Pairs the field's name (if it has one) with its temporary, using the shorthand if they're
+    /// the same.
+    fn with_name(&self) -> TokenStream {
+        let tmp_var = &self.tmp_var;
+        match &self.name {
+            Some(name) if name != tmp_var => quote! {#name: #tmp_var},
+            _ => quote! {#tmp_var},
+        }
+    }
+
+    /// The field's part of the expression that creates the struct or variant.
+    pub fn constructor_entry(&self) -> TokenStream {
+        self.with_name()
+    }
+
+    /// The field's part of the pattern that destructures the struct or variant.
+    pub fn pattern_entry(&self) -> TokenStream {
+        self.with_name()
+    }
+
+    /// The type annotation for the field's temporary. Without it, later fields couldn't call
+    /// methods on values whose types are inferred (e.g. collected ones).
+    fn get_type_annotation(&self) -> TokenStream {
+        let ty = &self.ty;
+        quote! {: #ty}
+    }
+
+    /// Every identifier used in the field's expressions (e.g. its size or if condition). When
+    /// deriving `ToSegment`, this is used to figure out which fields need to be in scope as values.
+    pub fn referenced_idents(&self) -> Vec<Ident> {
+        let mut found = Vec::new();
+        for expr in [&self.move_to, &self.move_by, &self.if_]
+            .iter()
+            .copied()
+            .flatten()
+        {
+            find_idents(expr.to_token_stream(), &mut found);
+        }
+        if let Some(magic) = &self.magic {
+            find_idents(magic.value().to_token_stream(), &mut found);
+        }
+        for expr in self.padding.exprs() {
+            find_idents(expr.to_token_stream(), &mut found);
+        }
+        if let Some(SizeOption::Expr(size)) = &self.size {
+            find_idents(size.to_token_stream(), &mut found);
+        }
+        if let Some(string) = &self.string {
+            for expr in string.exprs() {
+                find_idents(expr.to_token_stream(), &mut found);
+            }
+        }
+        if let Some(calc) = &self.calc {
+            find_idents(calc.to_token_stream(), &mut found);
+        }
+        found
+    }
+
+    /// Generates the statements that write the field to a `U8Sink` named `segment`. This is the
+    /// inverse of the field's assignment statement.
+    pub fn get_write_statements(&self) -> TokenStream {
+        if self.parser.is_some() {
+            panic!(
+                "Field {} uses a parser, which can't be used when deriving ToSegment!",
+                self.tmp_var
+            );
+        } else if self.map_each.is_some() {
+            panic!(
+                "Field {} uses map_each, which can't be used when deriving ToSegment!",
+                self.tmp_var
+            );
+        } else if self.take_while.is_some() {
+            panic!(
+                "Field {} uses while, which can't be used when deriving ToSegment!",
+                self.tmp_var
+            );
+        } else if self.decompress.is_some() {
+            panic!(
+                "Field {} uses decompress, which can't be used when deriving ToSegment!",
+                self.tmp_var
+            );
+        } else if matches!(self.from, Some(FromOption::Type(_)))
+            || matches!(self.try_from, Some(FromOption::Type(_)))
+        {
+            // The field is created by another type's conversion, which can't be reversed.
+            panic!(
+                "Field {} uses from or try_from with a type, which can't be used when deriving \
+                ToSegment!",
+                self.tmp_var
+            );
+        }
+        let pre_assign = self.get_pre_assign_statements();
+        let magic = self
+            .magic
+            .as_ref()
+            .and_then(|magic| magic.get_write_statements());
+        let tmp_var = &self.tmp_var;
+        let write_value = if let Some(var_int) = &self.var_int {
+            Some(var_int.get_write_tokens(quote! {value}))
+        } else {
+            self.string
+                .as_ref()
+                .map(|string| string.get_write_tokens(quote! {value}))
+        };
+        // Calculated values aren't written.
+        let write = if self.calc.is_some() || self.skip {
+            None
+        } else if let Some(write_value) = write_value {
+            Some(if self.parse_each {
+                quote! {
+                    for value in #tmp_var.iter() {
+                        #write_value
+                    }
+                }
+            } else if self.ty.is_option() {
+                quote! {
+                    if let Some(value) = &#tmp_var {
+                        #write_value
+                    }
+                }
+            } else {
+                quote! {
+                    let value = &#tmp_var;
+                    #write_value
+                }
+            })
+        } else {
+            Some(quote! { ::segsource::ToSegment::write_to(&#tmp_var, segment)?; })
+        };
+        let align = self.padding.get_align_write_statements();
+        let start = self.padding.get_start_statement();
+        let after = self.padding.get_after_write_statements();
+        let body = quote! {
+            #align
+            #start
+            #magic
+            #write
+            #after
+        };
+        match &self.if_ {
+            // Options already write nothing if they're None, but their magic and padding still
+            // need to be skipped.
+            Some(_) if self.ty.is_option() => quote! {
+                #pre_assign
+                if #tmp_var.is_some() {
+                    #body
+                }
+            },
+            Some(predicate) => quote! {
+                #pre_assign
+                if #predicate {
+                    #body
+                }
+            },
+            None => quote! {
+                #pre_assign
+                #body
+            },
+        }
+    }
 }
 
 impl From<(usize, (Field, bool, Rc<AlsoNeeds>))> for FromSegField {
     fn from(
         (idx, (field, generating_try_from, also_needs)): (usize, (Field, bool, Rc<AlsoNeeds>)),
     ) -> Self {
-        let tmp_var = field
-            .ident
-            .clone()
-            .unwrap_or(quote::format_ident!("tmp_{}", idx));
+        // Once a field has been parsed, it's in scope for the fields after it under its name (or,
+        // for tuple fields, an underscore followed by its index). The only exception is a field
+        // named segment, since that would hide the segment being parsed.
+        let tmp_var = match &field.ident {
+            Some(ident) if ident == "segment" => quote::format_ident!("__field_{}", ident),
+            Some(ident) => ident.clone(),
+            None => quote::format_ident!("_{}", idx),
+        };
         let mut me =
             FromSegField::with_defaults(tmp_var, field.ty, generating_try_from, also_needs);
+        me.name = field.ident.clone();
+        me.field_name = field
+            .ident
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_else(|| idx.to_string());
         for attr in field.attrs {
             if attr.path.is_ident("from_seg") {
                 comma_separated::<FromSegEntry>(parenthesized::<TokenStream>(attr.tokens).unwrap())
@@ -635,7 +1234,10 @@ impl From<(usize, (Field, bool, Rc<AlsoNeeds>))> for FromSegField {
                 break;
             }
         }
-        if me.default_value.is_some() && (me.if_.is_none() || !me.skip) {
+        // A default is only ever used when the field isn't parsed, so it needs either an if
+        // condition or skip.
+        let default_is_used = me.if_.is_some() || me.skip;
+        if me.default_value.is_some() && !default_is_used {
             panic!("Cannot specify default_value without an if condition or skip!")
         }
         if (me.map_each.is_some() || me.from_iter || me.parse_each)
@@ -644,6 +1246,22 @@ impl From<(usize, (Field, bool, Rc<AlsoNeeds>))> for FromSegField {
         {
             panic!("A size or take_while is needed for repeated values!")
         }
+        if me.decompress.is_some() && (me.parse_each || me.from_iter || me.map_each.is_some()) {
+            panic!("decompress can't be used with repeated values!")
+        }
+        if me.max_decompressed_size.is_some() && me.decompress.is_none() {
+            panic!("max_decompressed_size can only be used with decompress!")
+        }
+        if me.calc.is_some() && (me.skip || me.parser.is_some() || me.if_.is_some()) {
+            panic!("calc can't be used with skip, parser, or if!")
+        }
+        if me.ctx.is_some()
+            && (me.from.is_some()
+                || me.try_from.is_some()
+                || me.also_pass.additional_types.is_some())
+        {
+            panic!("ctx can't be used with from, try_from, or also_pass!")
+        }
         if me.move_to.is_some() && me.move_by.is_some() {
             panic!("Either move_to or move_by can be specified, not both.")
         }
@@ -677,6 +1295,15 @@ impl ToTokens for FromSegField {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let tmp_var = &self.tmp_var;
         let pre_assign = self.get_pre_assign_statements();
+        let suffix = self.get_statement_suffix();
+        let align = self.guard(self.padding.get_align_statements(&suffix));
+        let start = self.padding.get_start_statement();
+        let magic = self.guard(
+            self.magic
+                .as_ref()
+                .map(|magic| magic.get_check_statements(&suffix)),
+        );
+        let after = self.guard(self.padding.get_after_statements(&suffix));
         let lhs = self.get_assign_value();
         let post_statements = self.get_post_statements();
         let make_mut = if self.make_mut {
@@ -684,10 +1311,31 @@ impl ToTokens for FromSegField {
         } else {
             None
         };
-        let result = quote! {
-            #pre_assign
-            let #make_mut #tmp_var = #lhs;
-            #post_statements
+        let annotation = self.get_type_annotation();
+        let result = if let Some(verify) = &self.verify {
+            let offset = quote::format_ident!("__checksum_offset");
+            let verify = verify.get_verify_tokens(tmp_var, &offset, suffix.clone());
+            quote! {
+                #pre_assign
+                #align
+                #start
+                #magic
+                let #offset = segment.current_offset();
+                let #make_mut #tmp_var #annotation = #lhs;
+                #verify
+                #after
+                #post_statements
+            }
+        } else {
+            quote! {
+                #pre_assign
+                #align
+                #start
+                #magic
+                let #make_mut #tmp_var #annotation = #lhs;
+                #after
+                #post_statements
+            }
         };
         tokens.extend(result);
     }
@@ -0,0 +1,105 @@
+use pmhelp::{exts::ParseBufferExt as _, from_parens};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream, Result},
+    Expr, ExprLit, Lit, Token,
+};
+
+mod kw {
+    syn::custom_keyword!(peek);
+}
+
+/// A magic number (or any other constant) that has to be found before a field or type. It's either
+/// written as `magic = value` or, to check it without consuming it, `magic(value, peek)`.
+pub struct Magic {
+    value: Expr,
+    peek: bool,
+}
+
+impl Magic {
+    /// Parses the value (and whether to peek) from a stream starting just after the `magic`
+    /// keyword.
+    fn parse_value(stream: ParseStream) -> Result<Expr> {
+        let value = stream.parse::<Expr>()?;
+        if let Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) = &value
+        {
+            // Otherwise, there's no way to know how many bytes it should be.
+            if int.suffix().is_empty() {
+                return Err(syn::Error::new(
+                    int.span(),
+                    "Integer magic values need a type suffix (e.g. 0xcafe_u16)",
+                ));
+            }
+        }
+        Ok(value)
+    }
+
+    /// The expected bytes as a `&[u8]` (or something that derefs to one).
+    fn get_bytes(&self, suffix: &TokenStream) -> TokenStream {
+        let value = &self.value;
+        match value {
+            Expr::Lit(ExprLit {
+                lit: Lit::ByteStr(_),
+                ..
+            }) => quote! {#value},
+            _ => quote! {
+                &::segsource::ToSegment::to_bytes_with_endidness(&(#value), segment.endidness())
+                    #suffix
+            },
+        }
+    }
+
+    /// Generates the statement that checks the magic against a `Segment` named `segment`.
+    pub fn get_check_statements(&self, suffix: &TokenStream) -> TokenStream {
+        let bytes = self.get_bytes(suffix);
+        if self.peek {
+            quote! { segment.peek_magic(#bytes)#suffix; }
+        } else {
+            quote! { segment.next_magic(#bytes)#suffix; }
+        }
+    }
+
+    /// Generates the statements that write the magic to a `U8Sink` named `segment`. Magic that's
+    /// only peeked at is part of whatever comes next, so nothing is written for it.
+    pub fn get_write_statements(&self) -> Option<TokenStream> {
+        if self.peek {
+            return None;
+        }
+        let value = &self.value;
+        Some(match value {
+            Expr::Lit(ExprLit {
+                lit: Lit::ByteStr(_),
+                ..
+            }) => quote! { segment.put_bytes(#value)?; },
+            _ => quote! { ::segsource::ToSegment::write_to(&(#value), segment)?; },
+        })
+    }
+
+    pub fn value(&self) -> &Expr {
+        &self.value
+    }
+}
+
+impl Parse for Magic {
+    fn parse(stream: ParseStream) -> Result<Self> {
+        if stream.peek_and_consume(Token![=]) {
+            Ok(Self {
+                value: Self::parse_value(stream)?,
+                peek: false,
+            })
+        } else {
+            let stream = from_parens!(stream);
+            let value = Self::parse_value(&stream)?;
+            let peek = if stream.peek_and_consume(Token![,]) {
+                stream.parse::<kw::peek>()?;
+                true
+            } else {
+                false
+            };
+            Ok(Self { value, peek })
+        }
+    }
+}
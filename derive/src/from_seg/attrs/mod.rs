@@ -2,4 +2,9 @@ mod toplevel;
 pub use toplevel::*;
 mod field;
 pub use field::*;
-//mod variant;
+mod magic;
+pub use magic::*;
+mod padding;
+pub use padding::*;
+mod variant;
+pub use variant::*;
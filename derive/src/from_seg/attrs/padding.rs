@@ -0,0 +1,185 @@
+use pmhelp::{exts::ParseBufferExt as _, from_parens};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream, Result},
+    Expr, Ident, Token,
+};
+
+mod kw {
+    syn::custom_keyword!(base);
+    syn::custom_keyword!(zeroed);
+}
+
+/// Skips to the next aligned offset: `align(n)`, optionally followed by `base = offset` (the offset
+/// the alignment is relative to, which defaults to the segment's initial offset) and/or `zeroed`
+/// (which checks that the padding is all zeros).
+pub struct Align {
+    alignment: Expr,
+    base: Option<Expr>,
+    zeroed: bool,
+}
+
+impl Parse for Align {
+    fn parse(stream: ParseStream) -> Result<Self> {
+        let stream = from_parens!(stream);
+        let mut me = Self {
+            alignment: stream.parse()?,
+            base: None,
+            zeroed: false,
+        };
+        while stream.peek_and_consume(Token![,]) {
+            if stream.peek_and_consume(kw::zeroed) {
+                me.zeroed = true;
+            } else {
+                stream.parse::<kw::base>()?;
+                stream.parse::<Token![=]>()?;
+                me.base = Some(stream.parse()?);
+            }
+        }
+        Ok(me)
+    }
+}
+
+/// A fixed amount of padding (`pad_after(n)`) or the size something is padded to (`pad_to(n)`),
+/// optionally followed by `zeroed`.
+pub struct Pad {
+    size: Expr,
+    zeroed: bool,
+}
+
+impl Parse for Pad {
+    fn parse(stream: ParseStream) -> Result<Self> {
+        let stream = from_parens!(stream);
+        let size = stream.parse()?;
+        let zeroed = if stream.peek_and_consume(Token![,]) {
+            stream.parse::<kw::zeroed>()?;
+            true
+        } else {
+            false
+        };
+        Ok(Self { size, zeroed })
+    }
+}
+
+/// Generates the statement that skips `padding` bytes of a `Segment` named `segment`.
+fn skip_padding(padding: TokenStream, zeroed: bool, suffix: &TokenStream) -> TokenStream {
+    if zeroed {
+        quote! { segment.next_zero_padding(#padding)#suffix; }
+    } else {
+        quote! { segment.move_by((#padding) as i128)#suffix; }
+    }
+}
+
+/// The alignment and padding of a field or type. When parsing, padding is skipped (and checked if
+/// it's zeroed), and when writing, zeros are always written.
+#[derive(Default)]
+pub struct Padding {
+    pub align: Option<Box<Align>>,
+    pub pad_to: Option<Box<Pad>>,
+    pub pad_after: Option<Box<Pad>>,
+}
+
+impl Padding {
+    fn start_var() -> Ident {
+        format_ident!("__pad_start")
+    }
+
+    fn get_base(align: &Align) -> TokenStream {
+        align
+            .base
+            .as_ref()
+            .map(|base| quote! {(#base) as usize})
+            .unwrap_or_else(|| quote! {segment.initial_offset()})
+    }
+
+    /// Skips to the next aligned offset.
+    pub fn get_align_statements(&self, suffix: &TokenStream) -> Option<TokenStream> {
+        let align = self.align.as_ref()?;
+        let alignment = &align.alignment;
+        let base = Self::get_base(align);
+        let skip = skip_padding(quote! {padding}, align.zeroed, suffix);
+        Some(quote! {{
+            let padding = segment.padding_to_align((#alignment) as usize, #base);
+            #skip
+        }})
+    }
+
+    /// Records where the padded value started (after it's been aligned), if it's needed.
+    pub fn get_start_statement(&self) -> Option<TokenStream> {
+        let start = Self::start_var();
+        self.pad_to
+            .as_ref()
+            .map(|_| quote! { let #start = segment.current_offset(); })
+    }
+
+    /// Skips the padding after the value.
+    pub fn get_after_statements(&self, suffix: &TokenStream) -> Option<TokenStream> {
+        let pad_to = self.pad_to.as_ref().map(|pad_to| {
+            let start = Self::start_var();
+            let size = &pad_to.size;
+            let skip = skip_padding(quote! {padding}, pad_to.zeroed, suffix);
+            quote! {{
+                let padding = segment.padding_to(#start, (#size) as usize)#suffix;
+                #skip
+            }}
+        });
+        let pad_after = self.pad_after.as_ref().map(|pad_after| {
+            let size = &pad_after.size;
+            skip_padding(quote! {(#size) as usize}, pad_after.zeroed, suffix)
+        });
+        if pad_to.is_none() && pad_after.is_none() {
+            None
+        } else {
+            Some(quote! {
+                #pad_to
+                #pad_after
+            })
+        }
+    }
+
+    /// Writes zeros up to the next aligned offset of a `U8Sink` named `segment`.
+    pub fn get_align_write_statements(&self) -> Option<TokenStream> {
+        let align = self.align.as_ref()?;
+        let alignment = &align.alignment;
+        let base = Self::get_base(align);
+        Some(quote! {
+            segment.put_zeros(segment.padding_to_align((#alignment) as usize, #base))?;
+        })
+    }
+
+    /// Writes the zeros after the value to a `U8Sink` named `segment`.
+    pub fn get_after_write_statements(&self) -> Option<TokenStream> {
+        let pad_to = self.pad_to.as_ref().map(|pad_to| {
+            let start = Self::start_var();
+            let size = &pad_to.size;
+            quote! {
+                segment.put_zeros(segment.padding_to(#start, (#size) as usize)?)?;
+            }
+        });
+        let pad_after = self.pad_after.as_ref().map(|pad_after| {
+            let size = &pad_after.size;
+            quote! { segment.put_zeros((#size) as usize)?; }
+        });
+        if pad_to.is_none() && pad_after.is_none() {
+            None
+        } else {
+            Some(quote! {
+                #pad_to
+                #pad_after
+            })
+        }
+    }
+
+    /// Every expression used, so that the identifiers in them can be found.
+    pub fn exprs(&self) -> Vec<&Expr> {
+        let mut exprs = Vec::new();
+        if let Some(align) = &self.align {
+            exprs.push(&align.alignment);
+            exprs.extend(align.base.as_ref());
+        }
+        exprs.extend(self.pad_to.as_ref().map(|pad| &pad.size));
+        exprs.extend(self.pad_after.as_ref().map(|pad| &pad.size));
+        exprs
+    }
+}
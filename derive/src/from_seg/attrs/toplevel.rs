@@ -1,21 +1,29 @@
+use super::{
+    magic::Magic,
+    padding::{Align, Pad, Padding},
+};
 use pmhelp::{exts::ParseBufferExt as _, from_parens, parse::parse_stream::comma_separated};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote, ToTokens};
 use syn::{
     parse::{Parse, ParseStream, Result},
     punctuated::Punctuated,
-    Data, DataEnum, DataStruct, DeriveInput, Fields, FieldsNamed, FieldsUnnamed, Ident, Path,
-    Token, Type, TypePath,
+    Data, DataEnum, DataStruct, DeriveInput, Field, Fields, FieldsNamed, FieldsUnnamed, Generics,
+    Ident, Path, Token, Type, TypePath,
 };
 
 mod kw {
     syn::custom_keyword!(error);
-    //TODO add support for them to have generics
-    //TODO support naming/unpacking
     syn::custom_keyword!(also_needs);
+    syn::custom_keyword!(ctx);
     syn::custom_keyword!(item);
+    syn::custom_keyword!(magic);
+    syn::custom_keyword!(align);
+    syn::custom_keyword!(pad_to);
+    syn::custom_keyword!(pad_after);
     syn::custom_keyword!(preparse);
     syn::custom_keyword!(postparse);
+    syn::custom_keyword!(temp);
 }
 
 pub struct AlsoNeedsEntry {
@@ -35,13 +43,35 @@ impl Parse for AlsoNeedsEntry {
     }
 }
 
+/// The context needed to parse a type, declared via either `ctx(name: Type, ...)` or
+/// `also_needs(name: Type, ...)`. Either can be preceded by generics that the types can use (e.g.
+/// `ctx<'c, T: Copy>(names: &'c [T])`).
+///
+/// Types with a context implement `FromSegment` with the context's type, which is the type of its
+/// only value or, if it has more than one, a tuple of them. Types that use `also_needs` can also
+/// be created from a tuple of the values followed by the segment via `TryFrom` (or `From`).
 #[derive(Default)]
 pub struct AlsoNeeds {
     segment_generics: Option<TokenStream>,
     additional_types: Option<Punctuated<AlsoNeedsEntry, Token![,]>>,
+    generics: Generics,
+    tuple_bridge: bool,
 }
 
 impl AlsoNeeds {
+    /// Parses the generics and values from a stream starting just after the `ctx` or
+    /// `also_needs` keyword.
+    fn parse_declaration(stream: ParseStream, tuple_bridge: bool) -> Result<Self> {
+        let generics = stream.parse()?;
+        let stream = from_parens!(stream);
+        Ok(Self {
+            segment_generics: None,
+            additional_types: Some(comma_separated(&stream)?),
+            generics,
+            tuple_bridge,
+        })
+    }
+
     pub fn get_default_args() -> TokenStream {
         quote! {segment}
     }
@@ -88,15 +118,52 @@ impl AlsoNeeds {
             default
         }
     }
-}
 
-impl Parse for AlsoNeeds {
-    #[inline]
-    fn parse(stream: ParseStream) -> Result<Self> {
-        Ok(Self {
-            segment_generics: None,
-            additional_types: Some(comma_separated(stream)?),
-        })
+    fn entries(&self) -> Vec<&AlsoNeedsEntry> {
+        self.additional_types
+            .as_ref()
+            .map(|entries| entries.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether the type needs any context (if not, it's parsed from just a segment).
+    pub fn has_ctx(&self) -> bool {
+        !self.entries().is_empty()
+    }
+
+    /// Whether the type can also be created from a tuple of the context's values followed by the
+    /// segment.
+    pub fn has_tuple_bridge(&self) -> bool {
+        self.tuple_bridge
+    }
+
+    /// The generics declared along with the context.
+    pub fn generics(&self) -> &Generics {
+        &self.generics
+    }
+
+    pub fn get_ctx_type(&self) -> TokenStream {
+        match self.entries().as_slice() {
+            [] => quote! {()},
+            [entry] => entry.ty.to_token_stream(),
+            entries => {
+                let types = entries.iter().map(|entry| &entry.ty);
+                quote! {(#(#types),*)}
+            }
+        }
+    }
+
+    /// The context's values, which is both the pattern they're unpacked with and the expression
+    /// that packs them back up.
+    pub fn get_ctx_value(&self) -> TokenStream {
+        match self.entries().as_slice() {
+            [] => quote! {()},
+            [entry] => entry.ident.to_token_stream(),
+            entries => {
+                let idents = entries.iter().map(|entry| &entry.ident);
+                quote! {(#(#idents),*)}
+            }
+        }
     }
 }
 
@@ -106,6 +173,11 @@ enum FromSegEntry {
     AlsoNeeds(AlsoNeeds),
     Preparse(Path),
     Postparse(Path),
+    Magic(Magic),
+    Align(Box<Align>),
+    PadTo(Box<Pad>),
+    PadAfter(Box<Pad>),
+    Temps(Vec<Field>),
 }
 
 impl FromSegEntry {
@@ -116,6 +188,11 @@ impl FromSegEntry {
             Self::AlsoNeeds(also_needs) => info.also_needs = also_needs,
             Self::Preparse(path) => info.preparse = Some(path),
             Self::Postparse(path) => info.postparse = Some(path),
+            Self::Magic(magic) => info.magic = Some(magic),
+            Self::Align(align) => info.padding.align = Some(align),
+            Self::PadTo(pad) => info.padding.pad_to = Some(pad),
+            Self::PadAfter(pad) => info.padding.pad_after = Some(pad),
+            Self::Temps(temps) => info.temps = temps,
         }
     }
 }
@@ -130,8 +207,27 @@ impl Parse for FromSegEntry {
             Ok(Self::Postparse(from_parens!(stream).parse()?))
         } else if stream.peek_and_consume(kw::item) {
             Ok(Self::Item(from_parens!(stream).parse()?))
+        } else if stream.peek_and_consume(kw::magic) {
+            Ok(Self::Magic(stream.parse()?))
+        } else if stream.peek_and_consume(kw::align) {
+            Ok(Self::Align(stream.parse()?))
+        } else if stream.peek_and_consume(kw::pad_to) {
+            Ok(Self::PadTo(stream.parse()?))
+        } else if stream.peek_and_consume(kw::pad_after) {
+            Ok(Self::PadAfter(stream.parse()?))
+        } else if stream.peek_and_consume(kw::temp) {
+            let stream = from_parens!(stream);
+            Ok(Self::Temps(
+                Punctuated::<Field, Token![,]>::parse_terminated_with(&stream, Field::parse_named)?
+                    .into_iter()
+                    .collect(),
+            ))
         } else if stream.peek_and_consume(kw::also_needs) {
-            Ok(Self::AlsoNeeds(from_parens!(stream).parse()?))
+            Ok(Self::AlsoNeeds(AlsoNeeds::parse_declaration(stream, true)?))
+        } else if stream.peek_and_consume(kw::ctx) {
+            Ok(Self::AlsoNeeds(AlsoNeeds::parse_declaration(
+                stream, false,
+            )?))
         } else {
             Err(stream.error("Invalid option to top level from_seg attribute"))
         }
@@ -144,6 +240,12 @@ pub struct FromSegInfo {
     pub also_needs: AlsoNeeds,
     pub preparse: Option<Path>,
     pub postparse: Option<Path>,
+    pub magic: Option<Magic>,
+    pub padding: Padding,
+    /// Values that are parsed before the fields so that the fields can use them, but that aren't
+    /// stored. They're declared via `temp(name: Type, ...)`, and each one can have its own
+    /// `from_seg` attribute (e.g. `temp(#[from_seg(uleb128)] len: u32)`).
+    pub temps: Vec<Field>,
 }
 
 impl Default for FromSegInfo {
@@ -157,6 +259,9 @@ impl Default for FromSegInfo {
             also_needs: Default::default(),
             preparse: None,
             postparse: None,
+            magic: None,
+            padding: Default::default(),
+            temps: Vec::new(),
         }
     }
 }
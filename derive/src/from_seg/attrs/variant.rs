@@ -1,13 +1,17 @@
-#![allow(dead_code, unused_variables, unused_imports, unused_macros)]
-use crate::util::{get_attr_value, parse_parenthesized2};
-use core::iter::{FromIterator, IntoIterator};
+use pmhelp::{
+    exts::ParseBufferExt as _,
+    from_parens,
+    parse::{
+        parse_stream::comma_separated as comma_separated_ps,
+        token_stream::{comma_separated, parenthesized},
+    },
+};
 use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
+use quote::quote;
 use syn::{
-    parenthesized,
-    parse::{Parse, ParseStream, Result as ParseResult},
+    parse::{Parse, ParseStream, Result},
     punctuated::Punctuated,
-    Expr, ExprCall, ExprField, ExprLit, ExprPath, Field, Ident, Lit, LitInt, Path, Token, Type,
+    Expr, ExprLit, ExprPath, Lit, LitInt, Path, Token, Type, Variant,
 };
 
 mod kw {
@@ -16,6 +20,7 @@ mod kw {
     syn::custom_keyword!(value_is);
     syn::custom_keyword!(predicate);
     syn::custom_keyword!(default);
+    syn::custom_keyword!(use_if);
 }
 
 enum AtOption {
@@ -29,8 +34,9 @@ impl Default for AtOption {
         Self::Next
     }
 }
+
 impl Parse for AtOption {
-    fn parse(stream: ParseStream) -> ParseResult<Self> {
+    fn parse(stream: ParseStream) -> Result<Self> {
         match stream.parse::<Expr>()? {
             Expr::Lit(ExprLit {
                 lit: Lit::Int(val), ..
@@ -41,6 +47,19 @@ impl Parse for AtOption {
     }
 }
 
+impl AtOption {
+    /// Generates a `Result` containing the segment the value is read from.
+    fn get_segment(&self) -> TokenStream {
+        match self {
+            Self::Next => {
+                quote! { ::core::result::Result::<_, ::segsource::Error>::Ok(segment.clone()) }
+            }
+            Self::Offset(offset) => quote! { segment.all_after(#offset) },
+            Self::Expr(offset) => quote! { segment.all_after(#offset) },
+        }
+    }
+}
+
 struct ValueIs {
     type_: Type,
     value: Expr,
@@ -49,7 +68,7 @@ struct ValueIs {
 }
 
 impl Parse for ValueIs {
-    fn parse(stream: ParseStream) -> ParseResult<Self> {
+    fn parse(stream: ParseStream) -> Result<Self> {
         let type_ = stream.parse::<Type>()?;
         stream.parse::<Token![,]>()?;
         let value = stream.parse::<Expr>()?;
@@ -57,16 +76,14 @@ impl Parse for ValueIs {
         let mut at = AtOption::default();
         if stream.peek(Token![,]) {
             stream.parse::<Token![,]>()?;
-            if stream.peek(kw::peek) {
-                stream.parse::<kw::peek>()?;
+            if stream.peek_and_consume(kw::peek) {
                 peek = true;
             } else {
                 at = stream.parse::<AtOption>()?;
             }
             if stream.peek(Token![,]) {
                 stream.parse::<Token![,]>()?;
-                if stream.peek(kw::peek) {
-                    stream.parse::<kw::peek>()?;
+                if stream.peek_and_consume(kw::peek) {
                     peek = true;
                 } else {
                     at = stream.parse::<AtOption>()?;
@@ -82,6 +99,59 @@ impl Parse for ValueIs {
     }
 }
 
+impl ValueIs {
+    /// Whether or not the value is consumed (values read from somewhere other than the current
+    /// offset never move the cursor).
+    fn consumes(&self) -> bool {
+        self.consume && matches!(self.at, AtOption::Next)
+    }
+
+    fn get_condition(&self, suffix: &TokenStream) -> TokenStream {
+        let type_ = &self.type_;
+        let value = &self.value;
+        let value_segment = self.at.get_segment();
+        let consume = if self.consumes() {
+            Some(quote! { segment.move_to(value_segment.current_offset())#suffix; })
+        } else {
+            None
+        };
+        // If the value can't be read (e.g. there's not enough data left or its offset is out of
+        // range), then the variant just doesn't match.
+        quote! {
+            match #value_segment {
+                Ok(value_segment) => {
+                    let found: ::core::result::Result<#type_, _> =
+                        ::core::convert::TryFrom::try_from(&value_segment);
+                    match found {
+                        Ok(found) if found == #value => {
+                            #consume
+                            true
+                        }
+                        _ => false,
+                    }
+                }
+                Err(_) => false,
+            }
+        }
+    }
+}
+
+impl ValueIs {
+    fn get_write_statements(&self) -> Option<TokenStream> {
+        // Only values that were consumed are written; anything else is either part of a field or
+        // somewhere else entirely.
+        if self.consumes() {
+            let type_ = &self.type_;
+            let value = &self.value;
+            Some(quote! {
+                ::segsource::ToSegment::write_to(&{ let value: #type_ = #value; value }, segment)?;
+            })
+        } else {
+            None
+        }
+    }
+}
+
 enum UseIfEntry {
     HasMore,
     Default,
@@ -90,19 +160,141 @@ enum UseIfEntry {
 }
 
 impl Parse for UseIfEntry {
-    fn parse(stream: ParseStream) -> ParseResult<Self> {
-        if stream.peek(kw::has_more) {
+    fn parse(stream: ParseStream) -> Result<Self> {
+        if stream.peek_and_consume(kw::has_more) {
             Ok(Self::HasMore)
-        } else if stream.peek(kw::default) {
+        } else if stream.peek_and_consume(kw::default) {
             Ok(Self::Default)
-        } else if stream.peek(kw::value_is) {
-            stream.parse::<kw::value_is>()?;
-            Ok(Self::ValueIs(Box::new(parse_parenthesized2(stream)?)))
-        } else if stream.peek(kw::predicate) {
-            stream.parse::<kw::predicate>()?;
-            Ok(Self::Predicate(stream.parse()?))
+        } else if stream.peek_and_consume(kw::value_is) {
+            Ok(Self::ValueIs(from_parens!(stream).parse()?))
+        } else if stream.peek_and_consume(kw::predicate) {
+            Ok(Self::Predicate(from_parens!(stream).parse()?))
         } else {
             Err(stream.error(format!("Invalid input to use_if: {}", stream)))
         }
     }
 }
+
+impl UseIfEntry {
+    fn get_condition(&self, suffix: &TokenStream) -> TokenStream {
+        match self {
+            Self::HasMore => quote! { segment.has_more() },
+            Self::Default => quote! { true },
+            Self::ValueIs(value_is) => value_is.get_condition(suffix),
+            Self::Predicate(path) => quote! { #path(segment) },
+        }
+    }
+
+    fn get_write_statements(&self) -> Option<TokenStream> {
+        match self {
+            Self::ValueIs(value_is) => value_is.get_write_statements(),
+            _ => None,
+        }
+    }
+
+    fn consumes(&self) -> bool {
+        matches!(self, Self::ValueIs(value_is) if value_is.consumes())
+    }
+}
+
+struct UseIf(Punctuated<UseIfEntry, Token![,]>);
+
+impl Parse for UseIf {
+    #[inline]
+    fn parse(stream: ParseStream) -> Result<Self> {
+        Ok(Self(comma_separated_ps(stream)?))
+    }
+}
+
+enum FromSegEntry {
+    UseIf(UseIf),
+}
+
+impl FromSegEntry {
+    fn apply(self, from_seg: &mut FromSegVariant) {
+        match self {
+            Self::UseIf(value) => from_seg.use_if.extend(value.0),
+        }
+    }
+}
+
+impl Parse for FromSegEntry {
+    fn parse(stream: ParseStream) -> Result<Self> {
+        if stream.peek_and_consume(kw::use_if) {
+            Ok(Self::UseIf(from_parens!(stream).parse()?))
+        } else {
+            Err(stream.error(format!("Invalid from_seg option for a variant: {}", stream)))
+        }
+    }
+}
+
+/// The `from_seg` options for a single variant of an enum.
+#[derive(Default)]
+pub struct FromSegVariant {
+    use_if: Vec<UseIfEntry>,
+}
+
+impl FromSegVariant {
+    /// Whether or not this variant should be used if no other variant matches.
+    pub fn is_default(&self) -> bool {
+        self.use_if
+            .iter()
+            .any(|entry| matches!(entry, UseIfEntry::Default))
+    }
+
+    /// Generates an expression that evaluates to `true` if this variant should be used. All of
+    /// the variant's `use_if` entries must match.
+    pub fn get_condition(&self, generating_try_from: bool) -> TokenStream {
+        let suffix = if generating_try_from {
+            quote! {?}
+        } else {
+            quote! {.unwrap()}
+        };
+        if self.use_if.is_empty() {
+            panic!("Every variant needs either a use_if condition or to be the default!");
+        }
+        let conditions = self.use_if.iter().map(|entry| entry.get_condition(&suffix));
+        if !self.use_if.iter().any(UseIfEntry::consumes) {
+            return quote! { #(#conditions)&&* };
+        }
+        // A value that's consumed moves the cursor, so if a later condition doesn't match, it has
+        // to be moved back before the next variant is tried.
+        quote! {{
+            let variant_start = segment.current_offset();
+            let matched = #(#conditions)&&*;
+            if !matched {
+                // The start was valid when we got it, so moving back to it can't fail.
+                let _ = segment.move_to(variant_start);
+            }
+            matched
+        }}
+    }
+
+    /// Generates the statements that write any values the variant's conditions consumed (e.g. a
+    /// tag read via `value_is`) to a `U8Sink` named `segment`.
+    pub fn get_write_statements(&self) -> TokenStream {
+        let writes = self
+            .use_if
+            .iter()
+            .filter_map(UseIfEntry::get_write_statements);
+        quote! { #(#writes)* }
+    }
+}
+
+impl From<&Variant> for FromSegVariant {
+    fn from(variant: &Variant) -> Self {
+        let mut me = Self::default();
+        for attr in variant.attrs.iter() {
+            if attr.path.is_ident("from_seg") {
+                comma_separated::<FromSegEntry>(
+                    parenthesized::<TokenStream>(attr.tokens.clone()).unwrap(),
+                )
+                .unwrap()
+                .into_iter()
+                .for_each(|entry| entry.apply(&mut me));
+                break;
+            }
+        }
+        me
+    }
+}
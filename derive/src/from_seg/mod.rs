@@ -5,23 +5,17 @@ use quote::quote;
 use syn::{
     parse::{Parser as _, Result},
     punctuated::Punctuated,
-    Data, DataEnum, DataStruct, DeriveInput, Fields, FieldsNamed, FieldsUnnamed, Ident, Path,
-    Token, Type,
+    Data, DataEnum, DataStruct, DeriveInput, Field, Fields, FieldsNamed, FieldsUnnamed,
+    GenericParam, Generics, Ident, Path, Token, Type, Variant,
 };
-mod attrs;
-use attrs::{AlsoNeeds, FromSegField, FromSegInfo};
+pub(crate) mod attrs;
+use attrs::{AlsoNeeds, FromSegField, FromSegInfo, FromSegVariant};
 
 fn generate_create_self_stmt(
-    field_names: Punctuated<Ident, Token![,]>,
-    postparse: Option<Path>,
-    tuple_like: bool,
+    base: TokenStream,
+    postparse: Option<&Path>,
     generating_try_from: bool,
 ) -> TokenStream {
-    let base = if tuple_like {
-        quote! {Self(#field_names)}
-    } else {
-        quote! {Self{#field_names}}
-    };
     if let Some(postparse) = postparse {
         if generating_try_from {
             quote! { Ok(#postparse(#base)?) }
@@ -37,47 +31,179 @@ fn generate_create_self_stmt(
 
 fn generate_fields_body(
     ident: &Ident,
+    type_name: String,
+    constructor: TokenStream,
     fields: Fields,
     also_needs: Rc<AlsoNeeds>,
-    postparse: Option<Path>,
+    postparse: Option<&Path>,
     generating_try_from: bool,
+    allow_unit: bool,
 ) -> TokenStream {
     let (tuple_like, fields_iter) = match fields {
         Fields::Named(FieldsNamed { named, .. }) => (false, named.into_iter()),
         Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => (true, unnamed.into_iter()),
+        Fields::Unit if allow_unit => {
+            return generate_create_self_stmt(constructor, postparse, generating_try_from)
+        }
         Fields::Unit => panic!("Struct or variant {} has no fields!", ident),
     };
     let fields: Punctuated<FromSegField, Token![;]> = fields_iter
         .map(|f| (f, generating_try_from, Rc::clone(&also_needs)))
         .enumerate()
         .map(FromSegField::from)
+        .map(|mut field| {
+            field.set_type_name(type_name.clone());
+            field
+        })
         .collect();
-    let field_names: Punctuated<Ident, Token![,]> =
-        fields.iter().map(FromSegField::tmp_var).collect();
-    let create_self_stmt =
-        generate_create_self_stmt(field_names, postparse, tuple_like, generating_try_from);
+    let entries: Punctuated<TokenStream, Token![,]> =
+        fields.iter().map(FromSegField::constructor_entry).collect();
+    let base = if tuple_like {
+        quote! {#constructor(#entries)}
+    } else {
+        quote! {#constructor{#entries}}
+    };
+    let create_self_stmt = generate_create_self_stmt(base, postparse, generating_try_from);
     quote! {
         #fields
         #create_self_stmt
     }
 }
 
+fn generate_variants_body(
+    ident: &Ident,
+    variants: Punctuated<Variant, Token![,]>,
+    also_needs: Rc<AlsoNeeds>,
+    postparse: Option<&Path>,
+    generating_try_from: bool,
+) -> TokenStream {
+    let mut default_body = None;
+    let mut branches = Vec::new();
+    for variant in variants {
+        let info = FromSegVariant::from(&variant);
+        let variant_ident = &variant.ident;
+        let body = generate_fields_body(
+            variant_ident,
+            format!("{}::{}", ident, variant_ident),
+            quote! {Self::#variant_ident},
+            variant.fields,
+            Rc::clone(&also_needs),
+            postparse,
+            generating_try_from,
+            true,
+        );
+        if info.is_default() {
+            if default_body.is_some() {
+                panic!("Enum {} has more than one default variant!", ident);
+            }
+            default_body = Some(body);
+        } else {
+            let condition = info.get_condition(generating_try_from);
+            branches.push(quote! { if #condition { #body } });
+        }
+    }
+    let mut start_offset = None;
+    let fallback = if let Some(body) = default_body {
+        body
+    } else if generating_try_from {
+        start_offset = Some(quote! { let start_offset = segment.current_offset(); });
+        let name = ident.to_string();
+        quote! {
+            return Err(::core::convert::From::from(::segsource::Error::NoMatchingVariant {
+                name: #name,
+                offset: start_offset,
+            }));
+        }
+    } else {
+        panic!(
+            "Enum {} needs a variant with use_if(default) in order to derive FromSegment!",
+            ident
+        );
+    };
+    quote! {
+        #start_offset
+        #(#branches else)* {
+            #fallback
+        }
+    }
+}
+
+/// Generates the statements that parse a type's temporaries. They're parsed just like fields, but
+/// they're never stored.
+fn generate_temps(
+    type_name: String,
+    temps: Vec<Field>,
+    also_needs: &Rc<AlsoNeeds>,
+    generating_try_from: bool,
+) -> TokenStream {
+    let temps = temps.into_iter().map(|temp| {
+        let mut temp = FromSegField::from((0, (temp, generating_try_from, Rc::clone(also_needs))));
+        temp.set_type_name(type_name.clone());
+        temp
+    });
+    quote! { #(#temps)* }
+}
+
 fn generate_body(
     ident: &Ident,
     data: Data,
     also_needs: Rc<AlsoNeeds>,
     postparse: Option<Path>,
+    temps: Vec<Field>,
     generating_try_from: bool,
 ) -> TokenStream {
     match data {
         Data::Struct(DataStruct { fields, .. }) => {
-            generate_fields_body(ident, fields, also_needs, postparse, generating_try_from)
+            let temps = generate_temps(ident.to_string(), temps, &also_needs, generating_try_from);
+            let body = generate_fields_body(
+                ident,
+                ident.to_string(),
+                quote! {Self},
+                fields,
+                also_needs,
+                postparse.as_ref(),
+                generating_try_from,
+                false,
+            );
+            quote! {
+                #temps
+                #body
+            }
         }
-        Data::Enum(DataEnum { variants, .. }) => todo!(),
+        Data::Enum(_) if !temps.is_empty() => {
+            panic!(
+                "Enum {} can't have temps, since its variants have different fields!",
+                ident
+            )
+        }
+        Data::Enum(DataEnum { variants, .. }) => generate_variants_body(
+            ident,
+            variants,
+            also_needs,
+            postparse.as_ref(),
+            generating_try_from,
+        ),
         _ => unimplemented!(),
     }
 }
 
+/// Adds the generics declared along with a type's context to the generics of its impl. Lifetimes
+/// have to come before any other generics, so they're added after the existing ones.
+fn add_ctx_generics(generics: &mut Generics, ctx_generics: &Generics) {
+    for param in ctx_generics.params.iter().cloned() {
+        if let GenericParam::Lifetime(_) = param {
+            let idx = generics
+                .params
+                .iter()
+                .take_while(|param| matches!(param, GenericParam::Lifetime(_)))
+                .count();
+            generics.params.insert(idx, param);
+        } else {
+            generics.params.push(param);
+        }
+    }
+}
+
 pub fn base_from_segment(input: DeriveInput, generating_try_from: bool) -> Result<TokenStream> {
     let mut maybe_info = None;
     for attr in input.attrs {
@@ -86,30 +212,52 @@ pub fn base_from_segment(input: DeriveInput, generating_try_from: bool) -> Resul
             break;
         }
     }
-    let ([lifetime], generics) = create_new_lifetimes(&input.generics);
-    let (impl_g, _, maybe_where) = generics.split_for_impl();
+    let ([lifetime], mut generics) = create_new_lifetimes(&input.generics);
     let FromSegInfo {
         item_type,
         error_type,
         mut also_needs,
         preparse,
         postparse,
+        magic,
+        padding,
+        temps,
     } = maybe_info.unwrap_or_default();
     if generating_try_from && error_type.is_none() {
         panic!("No error type specified!");
     }
     also_needs.set_segment_generics(quote! {#lifetime, #item_type});
+    add_ctx_generics(&mut generics, also_needs.generics());
+    let (impl_g, _, maybe_where) = generics.split_for_impl();
     let also_needs = Rc::new(also_needs);
-    let error_stmt = error_type
-        .map(|etype| quote! {type Error = #etype;})
-        .unwrap_or_default();
+    let has_ctx = also_needs.has_ctx();
+    let has_tuple_bridge = also_needs.has_tuple_bridge();
+    // Types with a context implement FromSegment, which is always fallible.
+    let error_stmt = match &error_type {
+        Some(etype) if generating_try_from => quote! {type Error = #etype;},
+        _ if has_ctx => quote! {type Error = ::core::convert::Infallible;},
+        _ => TokenStream::new(),
+    };
     let name = input.ident;
     let (_, type_g, _) = input.generics.split_for_impl();
     let segment_type = also_needs.get_type();
     let method_args = also_needs.get_args();
-    let (trait_name, method_sig) = if generating_try_from {
+    let ctx_type = also_needs.get_ctx_type();
+    let ctx_value = also_needs.get_ctx_value();
+    let from_segment_trait = quote! {::segsource::FromSegment<#lifetime, #ctx_type, #item_type>};
+    let (trait_name, method_sig) = if has_ctx {
+        (
+            from_segment_trait.clone(),
+            quote! {
+                parse(
+                    segment: &::segsource::Segment<#lifetime, #item_type>,
+                    __ctx: #ctx_type,
+                ) -> ::core::result::Result<Self, Self::Error>
+            },
+        )
+    } else if generating_try_from {
         (
-            quote! {::core::convert::TryFrom},
+            quote! {::core::convert::TryFrom<#segment_type>},
             quote! {
                 try_from(#method_args: #segment_type)
                     -> ::core::result::Result<Self, Self::Error>
@@ -117,10 +265,15 @@ pub fn base_from_segment(input: DeriveInput, generating_try_from: bool) -> Resul
         )
     } else {
         (
-            quote! {::core::convert::From},
+            quote! {::core::convert::From<#segment_type>},
             quote! {from(#method_args: #segment_type) -> Self},
         )
     };
+    let unpack_ctx = if has_ctx {
+        Some(quote! { let #ctx_value = __ctx; })
+    } else {
+        None
+    };
     let preparse = preparse.map(|preparse| {
         if generating_try_from {
             quote! { let #method_args = #preparse(#method_args)?; }
@@ -128,22 +281,86 @@ pub fn base_from_segment(input: DeriveInput, generating_try_from: bool) -> Resul
             quote! { let #method_args = #preparse(#method_args); }
         }
     });
+    let suffix = if generating_try_from {
+        quote! {?}
+    } else {
+        quote! {.unwrap()}
+    };
+    let magic = magic.map(|magic| magic.get_check_statements(&suffix));
+    let align = padding.get_align_statements(&suffix);
+    let start = padding.get_start_statement();
     let body = generate_body(
         &name,
         input.data,
         also_needs,
         postparse,
+        temps,
         generating_try_from,
     );
+    // The padding after the type has to be skipped after it's been parsed, but before it's
+    // returned.
+    let body = match padding.get_after_statements(&suffix) {
+        Some(after) if generating_try_from => quote! {
+            let __padded: ::core::result::Result<Self, Self::Error> = { #body };
+            let __padded = __padded?;
+            #after
+            Ok(__padded)
+        },
+        Some(after) => quote! {
+            let __padded = { #body };
+            #after
+            __padded
+        },
+        None => body,
+    };
+    let body = if has_ctx && !generating_try_from {
+        quote! { Ok({ #body }) }
+    } else {
+        body
+    };
+    // Types that use also_needs can still be created from a tuple of their context and the
+    // segment.
+    let tuple_bridge = if !has_ctx || !has_tuple_bridge {
+        None
+    } else if generating_try_from {
+        Some(quote! {
+            impl #impl_g ::core::convert::TryFrom<#segment_type> for #name #type_g #maybe_where {
+                #error_stmt
+                #[inline]
+                fn try_from(
+                    #method_args: #segment_type,
+                ) -> ::core::result::Result<Self, Self::Error> {
+                    <Self as #from_segment_trait>::parse(segment, #ctx_value)
+                }
+            }
+        })
+    } else {
+        Some(quote! {
+            impl #impl_g ::core::convert::From<#segment_type> for #name #type_g #maybe_where {
+                #[inline]
+                fn from(#method_args: #segment_type) -> Self {
+                    match <Self as #from_segment_trait>::parse(segment, #ctx_value) {
+                        Ok(value) => value,
+                        Err(never) => match never {},
+                    }
+                }
+            }
+        })
+    };
     Ok(quote! {
-        impl #impl_g #trait_name<#segment_type> for #name #type_g #maybe_where {
+        impl #impl_g #trait_name for #name #type_g #maybe_where {
             #error_stmt
             #[allow(unused_parens)]
             fn #method_sig {
+                #unpack_ctx
                 #preparse
+                #align
+                #start
+                #magic
                 #body
             }
         }
+        #tuple_bridge
     })
 }
 
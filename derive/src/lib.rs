@@ -6,7 +6,9 @@ use syn::parse_macro_input;
 extern crate alloc;
 
 mod from_seg;
+mod to_seg;
 pub(crate) mod util;
+mod view;
 
 #[macro_use]
 extern crate here;
@@ -22,3 +24,13 @@ pub fn try_from_segment(stream: TokenStream) -> TokenStream {
         stream
     )))
 }
+
+#[proc_macro_derive(ToSegment, attributes(from_seg))]
+pub fn to_segment(stream: TokenStream) -> TokenStream {
+    TokenStream::from(to_seg::derive_to_segment(parse_macro_input!(stream)))
+}
+
+#[proc_macro_derive(SegmentView)]
+pub fn segment_view(stream: TokenStream) -> TokenStream {
+    TokenStream::from(view::derive_segment_view(parse_macro_input!(stream)))
+}
@@ -0,0 +1,152 @@
+use crate::from_seg::attrs::{AlsoNeeds, FromSegField, FromSegInfo, FromSegVariant};
+use alloc::rc::Rc;
+use pmhelp::parse::token_stream::parenthesized;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse::Result, punctuated::Punctuated, Data, DataEnum, DataStruct, DeriveInput, Fields,
+    FieldsNamed, FieldsUnnamed, Ident, Token,
+};
+
+/// Generates a pattern that destructures the struct or variant, along with the statements that
+/// write its fields.
+fn generate_fields_body(
+    constructor: TokenStream,
+    fields: Fields,
+    also_needs: &Rc<AlsoNeeds>,
+) -> (TokenStream, TokenStream) {
+    let (tuple_like, fields_iter) = match fields {
+        Fields::Named(FieldsNamed { named, .. }) => (false, named.into_iter()),
+        Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => (true, unnamed.into_iter()),
+        Fields::Unit => return (constructor, TokenStream::new()),
+    };
+    // The field attributes are shared with TryFromSegment, so any statements that would use the
+    // try suffix will use `?`.
+    let fields: Vec<FromSegField> = fields_iter
+        .map(|f| (f, true, Rc::clone(also_needs)))
+        .enumerate()
+        .map(FromSegField::from)
+        .collect();
+    let entries: Punctuated<TokenStream, Token![,]> =
+        fields.iter().map(FromSegField::pattern_entry).collect();
+    let pattern = if tuple_like {
+        quote! {#constructor(#entries)}
+    } else {
+        quote! {#constructor{#entries}}
+    };
+    // When parsing, fields used in other fields' expressions (e.g. `size(count)`) are values, not
+    // references, so the same needs to be true here.
+    let referenced: Vec<Ident> = fields
+        .iter()
+        .flat_map(FromSegField::referenced_idents)
+        .collect();
+    let clones = fields
+        .iter()
+        .map(FromSegField::tmp_var)
+        .filter(|name| referenced.contains(name))
+        .map(|name| quote! { let #name = ::core::clone::Clone::clone(#name); });
+    let writes = fields.iter().map(FromSegField::get_write_statements);
+    (
+        pattern,
+        quote! {
+            #(#clones)*
+            #(#writes)*
+        },
+    )
+}
+
+fn generate_body(data: Data, also_needs: Rc<AlsoNeeds>) -> TokenStream {
+    match data {
+        Data::Struct(DataStruct { fields, .. }) => {
+            let (pattern, body) = generate_fields_body(quote! {Self}, fields, &also_needs);
+            quote! {
+                let #pattern = self;
+                #body
+            }
+        }
+        Data::Enum(DataEnum { variants, .. }) => {
+            let arms = variants.into_iter().map(|variant| {
+                let info = FromSegVariant::from(&variant);
+                let variant_ident = &variant.ident;
+                let (pattern, body) = generate_fields_body(
+                    quote! {Self::#variant_ident},
+                    variant.fields,
+                    &also_needs,
+                );
+                let tag = info.get_write_statements();
+                quote! {
+                    #pattern => {
+                        #tag
+                        #body
+                    }
+                }
+            });
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        _ => unimplemented!(),
+    }
+}
+
+pub fn base_to_segment(input: DeriveInput) -> Result<TokenStream> {
+    let mut maybe_info = None;
+    for attr in input.attrs {
+        if attr.path.is_ident("from_seg") {
+            maybe_info = Some(syn::parse2(parenthesized::<TokenStream>(attr.tokens)?)?);
+            break;
+        }
+    }
+    let FromSegInfo {
+        item_type,
+        mut also_needs,
+        preparse,
+        postparse,
+        magic,
+        padding,
+        temps,
+        ..
+    } = maybe_info.unwrap_or_default();
+    if quote! {#item_type}.to_string() != "u8" {
+        panic!("ToSegment can only be derived for items that are read from u8 segments!");
+    }
+    if preparse.is_some() || postparse.is_some() {
+        panic!("preparse and postparse can't be used when deriving ToSegment!");
+    }
+    if !temps.is_empty() {
+        panic!("temp can't be used when deriving ToSegment, since there'd be nothing to write!");
+    }
+    // Never actually used, but the field attributes expect it to be set.
+    also_needs.set_segment_generics(quote! {'static, u8});
+    let name = input.ident;
+    let (impl_g, type_g, maybe_where) = input.generics.split_for_impl();
+    let magic = magic.and_then(|magic| magic.get_write_statements());
+    let align = padding.get_align_write_statements();
+    let start = padding.get_start_statement();
+    let after = padding.get_after_write_statements();
+    let body = generate_body(input.data, Rc::new(also_needs));
+    // The fields are written in their own block so that their padding doesn't shadow the type's.
+    let body = quote! {{ #body }};
+    Ok(quote! {
+        impl #impl_g ::segsource::ToSegment for #name #type_g #maybe_where {
+            #[allow(unused_parens, unused_variables)]
+            fn write_to<S: ::segsource::U8Sink>(
+                &self,
+                segment: &mut S,
+            ) -> ::segsource::Result<()> {
+                #align
+                #start
+                #magic
+                #body
+                #after
+                Ok(())
+            }
+        }
+    })
+}
+
+pub(crate) fn derive_to_segment(input: DeriveInput) -> TokenStream {
+    base_to_segment(input).unwrap()
+}
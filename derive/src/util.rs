@@ -1,3 +1,4 @@
+use proc_macro2::{Ident, TokenStream, TokenTree};
 use syn::{
     parse::{Parse, ParseStream, Result},
     Token,
@@ -7,3 +8,42 @@ pub fn get_attr_value<P: Parse>(stream: ParseStream) -> Result<P> {
     stream.parse::<Token![=]>()?;
     stream.parse::<P>()
 }
+
+/// Takes every token up to the next comma (or the end of the stream) as is.
+fn get_raw_value(stream: ParseStream) -> Result<TokenStream> {
+    let mut tokens = TokenStream::new();
+    while !stream.is_empty() && !stream.peek(Token![,]) {
+        tokens.extend(Some(stream.parse::<TokenTree>()?));
+    }
+    if tokens.is_empty() {
+        Err(stream.error("Expected a value"))
+    } else {
+        Ok(tokens)
+    }
+}
+
+/// Takes each of a list of comma separated values (e.g. the contents of parentheses) as is. This is
+/// used for values that syn can't parse as expressions without its full feature (e.g. method
+/// calls).
+pub fn get_raw_values(stream: ParseStream) -> Result<Vec<TokenStream>> {
+    let mut values = Vec::new();
+    while !stream.is_empty() {
+        values.push(get_raw_value(stream)?);
+        if !stream.is_empty() {
+            stream.parse::<Token![,]>()?;
+        }
+    }
+    Ok(values)
+}
+
+/// Adds every identifier found in the provided tokens (including those in nested groups) to
+/// `found`.
+pub fn find_idents(tokens: TokenStream, found: &mut Vec<Ident>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => found.push(ident),
+            TokenTree::Group(group) => find_idents(group.stream(), found),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DataStruct, DeriveInput, Fields, FieldsNamed};
+
+pub fn derive_segment_view(input: DeriveInput) -> TokenStream {
+    let DeriveInput {
+        ident,
+        vis,
+        data,
+        generics,
+        ..
+    } = input;
+    if !generics.params.is_empty() {
+        panic!("SegmentView can't be derived for generic types!");
+    }
+    let fields = match data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(FieldsNamed { named, .. }),
+            ..
+        }) => named,
+        _ => panic!("SegmentView can only be derived for structs with named fields!"),
+    };
+    let view_ident = format_ident!("{}View", ident);
+    // The offset of each field is the sum of the sizes of the fields before it.
+    let mut offset = quote! {0};
+    let mut accessors = Vec::new();
+    for field in fields {
+        let name = field.ident.unwrap();
+        let field_vis = field.vis;
+        let ty = field.ty;
+        let doc = format!("Reads the `{}` field.", name);
+        accessors.push(quote! {
+            #[doc = #doc]
+            #[inline]
+            #field_vis fn #name(&self) -> <#ty as ::segsource::ViewField<'s>>::View {
+                ::segsource::derive_extras::view_field::<#ty>(&self.segment, #offset)
+            }
+        });
+        offset = quote! {#offset + <#ty as ::segsource::FixedLayout>::LAYOUT_SIZE};
+    }
+    let view_doc = format!(
+        "A zero-copy view of a [`{}`] that reads its fields from a segment on demand.",
+        ident
+    );
+    quote! {
+        #[doc = #view_doc]
+        #[derive(Clone)]
+        #vis struct #view_ident<'s> {
+            segment: ::segsource::Segment<'s, u8>,
+        }
+
+        impl ::segsource::FixedLayout for #ident {
+            const LAYOUT_SIZE: usize = #offset;
+        }
+
+        impl<'s> ::segsource::ViewField<'s> for #ident {
+            type View = #view_ident<'s>;
+
+            #[inline]
+            fn view(segment: ::segsource::Segment<'s, u8>) -> Self::View {
+                #view_ident { segment }
+            }
+        }
+
+        impl<'s> #view_ident<'s> {
+            /// The number of bytes the record takes up.
+            pub const LAYOUT_SIZE: usize = <#ident as ::segsource::FixedLayout>::LAYOUT_SIZE;
+
+            /// Creates a new view whose fields start at the segment's initial offset. Returns an
+            /// error if the segment is too small to hold the record.
+            pub fn new(segment: ::segsource::Segment<'s, u8>) -> ::segsource::Result<Self> {
+                ::segsource::derive_extras::check_view_size(&segment, Self::LAYOUT_SIZE)?;
+                Ok(Self { segment })
+            }
+
+            /// The segment the fields are read from.
+            #[inline]
+            pub fn segment(&self) -> &::segsource::Segment<'s, u8> {
+                &self.segment
+            }
+
+            #(#accessors)*
+        }
+
+        impl<'s> ::core::convert::TryFrom<&::segsource::Segment<'s, u8>> for #view_ident<'s> {
+            type Error = ::segsource::Error;
+
+            #[inline]
+            fn try_from(segment: &::segsource::Segment<'s, u8>) -> ::segsource::Result<Self> {
+                segment.next_view::<#ident>()
+            }
+        }
+    }
+}
@@ -0,0 +1,465 @@
+//! Checksums and hashes that can be calculated directly over a [`crate::Segment`] (see
+//! [`crate::Segment::checksum`] and [`crate::Segment::checksum_range`]) without copying any data.
+//!
+//! Every algorithm implements [`Checksum`], so they can also be fed data a piece at a time:
+//!
+//! ```
+//! # use segsource::checksum::{Checksum, Crc32};
+//! let mut crc = Crc32::default();
+//! crc.update(b"1234");
+//! crc.update(b"56789");
+//! assert_eq!(crc.finish(), 0xcbf43926);
+//! assert_eq!(Crc32::checksum(b"123456789"), 0xcbf43926);
+//! ```
+use core::{convert::TryInto, fmt::Debug};
+
+/// A checksum (or non-cryptographic hash) algorithm.
+pub trait Checksum: Default {
+    /// The type of the finished checksum.
+    type Output: Copy + Debug + PartialEq + Into<u64>;
+
+    /// Adds the provided data to the checksum.
+    fn update(&mut self, data: &[u8]);
+
+    /// The checksum of all of the data added so far. This doesn't reset the checksum, so more
+    /// data can still be added afterwards.
+    fn finish(&self) -> Self::Output;
+
+    /// Calculates the checksum of the provided data in one go.
+    #[inline]
+    fn checksum(data: &[u8]) -> Self::Output {
+        let mut checksum = Self::default();
+        checksum.update(data);
+        checksum.finish()
+    }
+}
+
+const fn reflected_table32(poly: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut value = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 == 1 {
+                (value >> 1) ^ poly
+            } else {
+                value >> 1
+            };
+            bit += 1;
+        }
+        table[i] = value;
+        i += 1;
+    }
+    table
+}
+
+const fn reflected_table16(poly: u16) -> [u16; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut value = i as u16;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 == 1 {
+                (value >> 1) ^ poly
+            } else {
+                value >> 1
+            };
+            bit += 1;
+        }
+        table[i] = value;
+        i += 1;
+    }
+    table
+}
+
+const fn table16(poly: u16) -> [u16; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut value = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 0x8000 == 0x8000 {
+                (value << 1) ^ poly
+            } else {
+                value << 1
+            };
+            bit += 1;
+        }
+        table[i] = value;
+        i += 1;
+    }
+    table
+}
+
+const CRC32_TABLE: [u32; 256] = reflected_table32(0xedb88320);
+
+/// CRC-32 (also known as CRC-32/ISO-HDLC), as used by zlib, gzip, PNG, and ZIP files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crc32(u32);
+
+impl Default for Crc32 {
+    #[inline]
+    fn default() -> Self {
+        Self(0xffffffff)
+    }
+}
+
+impl Checksum for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.0 = CRC32_TABLE[((self.0 ^ *byte as u32) & 0xff) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    #[inline]
+    fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+macro_rules! make_crc16 {
+    ($(
+        $(#[$attr:meta])*
+        $name:ident, $table:ident = $make_table:ident($poly:expr), init = $init:expr, $reflected:expr
+    );+ $(;)?) => {
+        $(
+            const $table: [u16; 256] = $make_table($poly);
+
+            $(#[$attr])*
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            pub struct $name(u16);
+
+            impl Default for $name {
+                #[inline]
+                fn default() -> Self {
+                    Self($init)
+                }
+            }
+
+            impl Checksum for $name {
+                type Output = u16;
+
+                fn update(&mut self, data: &[u8]) {
+                    for byte in data {
+                        self.0 = if $reflected {
+                            $table[((self.0 ^ *byte as u16) & 0xff) as usize] ^ (self.0 >> 8)
+                        } else {
+                            $table[((self.0 >> 8) ^ *byte as u16) as usize] ^ (self.0 << 8)
+                        };
+                    }
+                }
+
+                #[inline]
+                fn finish(&self) -> u16 {
+                    self.0
+                }
+            }
+        )+
+    };
+}
+
+make_crc16! {
+    /// CRC-16/ARC (also known as CRC-16/IBM and CRC-16/LHA), as used by ARC and LHA archives.
+    Crc16Arc, CRC16_ARC_TABLE = reflected_table16(0xa001), init = 0, true;
+    /// CRC-16/MODBUS, as used by Modbus RTU.
+    Crc16Modbus, CRC16_MODBUS_TABLE = reflected_table16(0xa001), init = 0xffff, true;
+    /// CRC-16/CCITT-FALSE (also known as CRC-16/IBM-3740).
+    Crc16CcittFalse, CRC16_CCITT_FALSE_TABLE = table16(0x1021), init = 0xffff, false;
+    /// CRC-16/XMODEM (also known as CRC-16/ACORN), as used by XMODEM and Bluetooth LE.
+    Crc16Xmodem, CRC16_XMODEM_TABLE = table16(0x1021), init = 0, false;
+}
+
+const ADLER32_MOD: u32 = 65521;
+// The most bytes that can be added before the sums have to be reduced to avoid overflowing.
+const ADLER32_MAX_RUN: usize = 5552;
+
+/// Adler-32, as used by zlib streams.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Default for Adler32 {
+    #[inline]
+    fn default() -> Self {
+        Self { a: 1, b: 0 }
+    }
+}
+
+impl Checksum for Adler32 {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        for run in data.chunks(ADLER32_MAX_RUN) {
+            for byte in run {
+                self.a += *byte as u32;
+                self.b += self.a;
+            }
+            self.a %= ADLER32_MOD;
+            self.b %= ADLER32_MOD;
+        }
+    }
+
+    #[inline]
+    fn finish(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+const XXH32_PRIME_1: u32 = 0x9e3779b1;
+const XXH32_PRIME_2: u32 = 0x85ebca77;
+const XXH32_PRIME_3: u32 = 0xc2b2ae3d;
+const XXH32_PRIME_4: u32 = 0x27d4eb2f;
+const XXH32_PRIME_5: u32 = 0x165667b1;
+
+#[inline]
+fn u32_le(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+#[inline]
+fn u64_le(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
+
+#[inline]
+fn xxh32_round(acc: u32, input: u32) -> u32 {
+    acc.wrapping_add(input.wrapping_mul(XXH32_PRIME_2))
+        .rotate_left(13)
+        .wrapping_mul(XXH32_PRIME_1)
+}
+
+/// The 32-bit version of xxHash. By default, the seed is zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Xxh32 {
+    seed: u32,
+    acc: [u32; 4],
+    total_len: u64,
+    buffer: [u8; 16],
+    buffered: usize,
+}
+
+impl Xxh32 {
+    /// Creates a new hasher using the provided seed.
+    pub fn with_seed(seed: u32) -> Self {
+        Self {
+            seed,
+            acc: [
+                seed.wrapping_add(XXH32_PRIME_1).wrapping_add(XXH32_PRIME_2),
+                seed.wrapping_add(XXH32_PRIME_2),
+                seed,
+                seed.wrapping_sub(XXH32_PRIME_1),
+            ],
+            total_len: 0,
+            buffer: [0; 16],
+            buffered: 0,
+        }
+    }
+
+    fn stripe(&mut self, stripe: &[u8]) {
+        for (i, acc) in self.acc.iter_mut().enumerate() {
+            *acc = xxh32_round(*acc, u32_le(&stripe[i * 4..]));
+        }
+    }
+}
+
+impl Default for Xxh32 {
+    #[inline]
+    fn default() -> Self {
+        Self::with_seed(0)
+    }
+}
+
+impl Checksum for Xxh32 {
+    type Output = u32;
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+        if self.buffered > 0 {
+            let take = data.len().min(16 - self.buffered);
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&data[..take]);
+            self.buffered += take;
+            data = &data[take..];
+            if self.buffered < 16 {
+                return;
+            }
+            let buffer = self.buffer;
+            self.stripe(&buffer);
+            self.buffered = 0;
+        }
+        let mut stripes = data.chunks_exact(16);
+        for stripe in &mut stripes {
+            self.stripe(stripe);
+        }
+        let rest = stripes.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    fn finish(&self) -> u32 {
+        let mut hash = if self.total_len >= 16 {
+            let [a, b, c, d] = self.acc;
+            a.rotate_left(1)
+                .wrapping_add(b.rotate_left(7))
+                .wrapping_add(c.rotate_left(12))
+                .wrapping_add(d.rotate_left(18))
+        } else {
+            self.seed.wrapping_add(XXH32_PRIME_5)
+        };
+        hash = hash.wrapping_add(self.total_len as u32);
+        let mut words = self.buffer[..self.buffered].chunks_exact(4);
+        for word in &mut words {
+            hash = hash
+                .wrapping_add(u32_le(word).wrapping_mul(XXH32_PRIME_3))
+                .rotate_left(17)
+                .wrapping_mul(XXH32_PRIME_4);
+        }
+        for byte in words.remainder() {
+            hash = hash
+                .wrapping_add((*byte as u32).wrapping_mul(XXH32_PRIME_5))
+                .rotate_left(11)
+                .wrapping_mul(XXH32_PRIME_1);
+        }
+        hash ^= hash >> 15;
+        hash = hash.wrapping_mul(XXH32_PRIME_2);
+        hash ^= hash >> 13;
+        hash = hash.wrapping_mul(XXH32_PRIME_3);
+        hash ^ (hash >> 16)
+    }
+}
+
+const XXH64_PRIME_1: u64 = 0x9e3779b185ebca87;
+const XXH64_PRIME_2: u64 = 0xc2b2ae3d27d4eb4f;
+const XXH64_PRIME_3: u64 = 0x165667b19e3779f9;
+const XXH64_PRIME_4: u64 = 0x85ebca77c2b2ae63;
+const XXH64_PRIME_5: u64 = 0x27d4eb2f165667c5;
+
+#[inline]
+fn xxh64_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(XXH64_PRIME_2))
+        .rotate_left(31)
+        .wrapping_mul(XXH64_PRIME_1)
+}
+
+#[inline]
+fn xxh64_merge(hash: u64, acc: u64) -> u64 {
+    (hash ^ xxh64_round(0, acc))
+        .wrapping_mul(XXH64_PRIME_1)
+        .wrapping_add(XXH64_PRIME_4)
+}
+
+/// The 64-bit version of xxHash. By default, the seed is zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Xxh64 {
+    seed: u64,
+    acc: [u64; 4],
+    total_len: u64,
+    buffer: [u8; 32],
+    buffered: usize,
+}
+
+impl Xxh64 {
+    /// Creates a new hasher using the provided seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            acc: [
+                seed.wrapping_add(XXH64_PRIME_1).wrapping_add(XXH64_PRIME_2),
+                seed.wrapping_add(XXH64_PRIME_2),
+                seed,
+                seed.wrapping_sub(XXH64_PRIME_1),
+            ],
+            total_len: 0,
+            buffer: [0; 32],
+            buffered: 0,
+        }
+    }
+
+    fn stripe(&mut self, stripe: &[u8]) {
+        for (i, acc) in self.acc.iter_mut().enumerate() {
+            *acc = xxh64_round(*acc, u64_le(&stripe[i * 8..]));
+        }
+    }
+}
+
+impl Default for Xxh64 {
+    #[inline]
+    fn default() -> Self {
+        Self::with_seed(0)
+    }
+}
+
+impl Checksum for Xxh64 {
+    type Output = u64;
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+        if self.buffered > 0 {
+            let take = data.len().min(32 - self.buffered);
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&data[..take]);
+            self.buffered += take;
+            data = &data[take..];
+            if self.buffered < 32 {
+                return;
+            }
+            let buffer = self.buffer;
+            self.stripe(&buffer);
+            self.buffered = 0;
+        }
+        let mut stripes = data.chunks_exact(32);
+        for stripe in &mut stripes {
+            self.stripe(stripe);
+        }
+        let rest = stripes.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    fn finish(&self) -> u64 {
+        let mut hash = if self.total_len >= 32 {
+            let [a, b, c, d] = self.acc;
+            let hash = a
+                .rotate_left(1)
+                .wrapping_add(b.rotate_left(7))
+                .wrapping_add(c.rotate_left(12))
+                .wrapping_add(d.rotate_left(18));
+            self.acc
+                .iter()
+                .fold(hash, |hash, acc| xxh64_merge(hash, *acc))
+        } else {
+            self.seed.wrapping_add(XXH64_PRIME_5)
+        };
+        hash = hash.wrapping_add(self.total_len);
+        let mut rest = &self.buffer[..self.buffered];
+        while rest.len() >= 8 {
+            hash = (hash ^ xxh64_round(0, u64_le(rest)))
+                .rotate_left(27)
+                .wrapping_mul(XXH64_PRIME_1)
+                .wrapping_add(XXH64_PRIME_4);
+            rest = &rest[8..];
+        }
+        if rest.len() >= 4 {
+            hash = (hash ^ (u32_le(rest) as u64).wrapping_mul(XXH64_PRIME_1))
+                .rotate_left(23)
+                .wrapping_mul(XXH64_PRIME_2)
+                .wrapping_add(XXH64_PRIME_3);
+            rest = &rest[4..];
+        }
+        for byte in rest {
+            hash = (hash ^ (*byte as u64).wrapping_mul(XXH64_PRIME_5))
+                .rotate_left(11)
+                .wrapping_mul(XXH64_PRIME_1);
+        }
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(XXH64_PRIME_2);
+        hash ^= hash >> 29;
+        hash = hash.wrapping_mul(XXH64_PRIME_3);
+        hash ^ (hash >> 32)
+    }
+}
@@ -1,5 +1,8 @@
 #![allow(unused_imports, unused_variables, unused_mut)]
-use crate::segment::Segment;
+use crate::{
+    error::Error,
+    segment::{Segment, ViewField},
+};
 #[cfg(not(feature = "std"))]
 use alloc::vec::{IntoIter as VecIter, Vec};
 use core::{
@@ -37,3 +40,128 @@ where
         Ok(tmp_vec.into_iter())
     }
 }
+
+/// Holds an error from a field while a derived parser adds context to it.
+///
+/// Derived parsers don't know their fields' error types, so this uses method resolution to pick
+/// between [`AddErrorContext`] (which is implemented for `ErrorContext<Error>` and records the
+/// context) and [`KeepErrorContext`] (which is implemented for `&mut ErrorContext<E>` and returns
+/// any other error unchanged). Because the former doesn't need an extra reference, it's always
+/// picked for segsource's [`Error`].
+pub struct ErrorContext<E>(pub Option<E>);
+
+/// See [`ErrorContext`].
+pub trait AddErrorContext {
+    fn in_field(
+        &mut self,
+        type_name: &'static str,
+        field: &'static str,
+        start: usize,
+        end: usize,
+    ) -> Error;
+    fn at_index(&mut self, index: usize, start: usize, end: usize) -> Error;
+    fn in_compressed(&mut self, start: usize, end: usize) -> Error;
+}
+
+impl AddErrorContext for ErrorContext<Error> {
+    #[inline]
+    fn in_field(
+        &mut self,
+        type_name: &'static str,
+        field: &'static str,
+        start: usize,
+        end: usize,
+    ) -> Error {
+        self.0
+            .take()
+            .unwrap()
+            .in_field(type_name, field, start, end)
+    }
+
+    #[inline]
+    fn at_index(&mut self, index: usize, start: usize, end: usize) -> Error {
+        self.0.take().unwrap().at_index(index, start, end)
+    }
+
+    #[inline]
+    fn in_compressed(&mut self, start: usize, end: usize) -> Error {
+        self.0.take().unwrap().in_compressed(start, end)
+    }
+}
+
+/// See [`ErrorContext`].
+pub trait KeepErrorContext<E> {
+    fn in_field(
+        &mut self,
+        type_name: &'static str,
+        field: &'static str,
+        start: usize,
+        end: usize,
+    ) -> E;
+    fn at_index(&mut self, index: usize, start: usize, end: usize) -> E;
+    fn in_compressed(&mut self, start: usize, end: usize) -> E;
+}
+
+impl<E> KeepErrorContext<E> for &mut ErrorContext<E> {
+    #[inline]
+    fn in_field(&mut self, _: &'static str, _: &'static str, _: usize, _: usize) -> E {
+        self.0.take().unwrap()
+    }
+
+    #[inline]
+    fn at_index(&mut self, _: usize, _: usize, _: usize) -> E {
+        self.0.take().unwrap()
+    }
+
+    #[inline]
+    fn in_compressed(&mut self, _: usize, _: usize) -> E {
+        self.0.take().unwrap()
+    }
+}
+
+/// Converts a decoded variable-length integer into the type it's being assigned to.
+pub fn convert_var_int<T, V>(value: V, offset: usize) -> Result<T, Error>
+where
+    T: TryFrom<V>,
+{
+    T::try_from(value).map_err(|_| Error::InvalidVarInt { offset })
+}
+
+/// Converts a value so that it can be written as a variable-length integer.
+pub trait VarIntValue {
+    fn to_var_u64(&self, offset: usize) -> Result<u64, Error>;
+    fn to_var_i64(&self, offset: usize) -> Result<i64, Error>;
+}
+
+macro_rules! impl_var_int_value {
+    ($($type:ty),+) => {
+        $(
+            impl VarIntValue for $type {
+                #[inline]
+                fn to_var_u64(&self, offset: usize) -> Result<u64, Error> {
+                    convert_var_int(*self, offset)
+                }
+
+                #[inline]
+                fn to_var_i64(&self, offset: usize) -> Result<i64, Error> {
+                    convert_var_int(*self, offset)
+                }
+            }
+        )+
+    };
+}
+
+impl_var_int_value! { u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize }
+
+/// Checks that a segment is big enough for a view of something with the provided layout size.
+#[inline]
+pub fn check_view_size(segment: &Segment<u8>, layout_size: usize) -> Result<(), Error> {
+    segment.validate_layout_size(layout_size)
+}
+
+/// Creates a view of a field that's `pos` bytes after the segment's initial offset. The view's
+/// constructor has already made sure the segment is big enough.
+#[inline]
+pub fn view_field<'s, T: ViewField<'s>>(segment: &Segment<'s, u8>, pos: usize) -> T::View {
+    segment.view_at_pos::<T>(pos)
+}
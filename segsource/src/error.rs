@@ -2,7 +2,8 @@
 use std::io;
 
 #[cfg(not(feature = "std"))]
-use alloc::string::String;
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{fmt, ops::Range};
 
 use snafu::Snafu;
 
@@ -15,13 +16,86 @@ pub enum Error {
     /// Returned if an offset is too large.
     #[snafu(display("An offset of 0x{:x} is too large.", offset))]
     OffsetTooLarge { offset: usize },
-    /// Returned if there's not enough data left in a [`crate::Segment`].
-    #[snafu(display("Requested {} bytes, but only {} bytes left.", requested, left))]
-    NotEnoughData { requested: usize, left: usize },
+    /// Returned if there's not enough data left in a [`crate::Segment`]. The offset is where the
+    /// read started.
+    #[snafu(display(
+        "Requested {} bytes at offset 0x{:x}, but only {} bytes left.",
+        requested,
+        offset,
+        left
+    ))]
+    NotEnoughData {
+        requested: usize,
+        left: usize,
+        offset: usize,
+    },
     /// Returned if there's no data left in a [`crate::Segment`] relative to its.
     /// [crate::Segment::current_offset].
-    #[snafu(display("No more data left.",))]
-    NoMoreData,
+    #[snafu(display("No more data left at offset 0x{:x}.", offset))]
+    NoMoreData { offset: usize },
+    /// Returned if an address in a [`crate::SparseSource`] isn't in any of its regions.
+    #[snafu(display("Address 0x{:x} is not mapped.", offset))]
+    Unmapped { offset: usize },
+    /// Returned by a derived enum if none of its variants' conditions matched.
+    #[snafu(display("No variant of {} matched at offset 0x{:x}.", name, offset))]
+    NoMatchingVariant { name: &'static str, offset: usize },
+    /// Returned if a variable-length integer (e.g. a LEB128 or a varint) is too long or won't fit
+    /// in the type it's being read as.
+    #[snafu(display("Invalid variable-length integer at offset 0x{:x}.", offset))]
+    InvalidVarInt { offset: usize },
+    /// Returned if a string isn't valid UTF-8. The offset is that of the first invalid byte.
+    #[snafu(display("Invalid UTF-8 at offset 0x{:x}.", offset))]
+    InvalidUtf8 { offset: usize },
+    /// Returned if a string isn't valid UTF-16. The offset is that of the first invalid code unit.
+    #[snafu(display("Invalid UTF-16 at offset 0x{:x}.", offset))]
+    InvalidUtf16 { offset: usize },
+    /// Returned if a magic number (or any other constant) doesn't match what was expected. If
+    /// there wasn't enough data, then what was found will be shorter than what was expected.
+    #[snafu(display(
+        "Bad magic at offset 0x{:x}: expected {:02x?}, but found {:02x?}.",
+        offset,
+        expected,
+        found
+    ))]
+    BadMagic {
+        expected: Vec<u8>,
+        found: Vec<u8>,
+        offset: usize,
+    },
+    /// Returned if padding that should be zero isn't. The offset is that of the first non-zero
+    /// byte.
+    #[snafu(display("Non-zero padding at offset 0x{:x}.", offset))]
+    NonZeroPadding { offset: usize },
+    /// Returned if something that's padded to a fixed size (e.g. a record) is larger than that
+    /// size. The offset is where it started.
+    #[snafu(display(
+        "Found {} bytes at offset 0x{:x}, but they're padded to {} bytes.",
+        size,
+        offset,
+        padded_size
+    ))]
+    ExceedsPadding {
+        size: usize,
+        padded_size: usize,
+        offset: usize,
+    },
+    /// Returned if a checksum doesn't match the data it covers. The offset is that of the stored
+    /// checksum.
+    #[snafu(display(
+        "Checksum mismatch at offset 0x{:x}: expected 0x{:x}, but got 0x{:x}.",
+        offset,
+        expected,
+        actual
+    ))]
+    ChecksumMismatch {
+        expected: u64,
+        actual: u64,
+        offset: usize,
+    },
+    /// Returned if compressed data couldn't be decompressed. The offset is that of the start of the
+    /// compressed data.
+    #[snafu(display("Failed to decompress the data at offset 0x{:x}: {}", offset, message))]
+    Decompression { offset: usize, message: String },
     #[cfg(feature = "std")]
     /// Wraps a `std::io::Error`.
     #[snafu(display("{}", error))]
@@ -29,6 +103,181 @@ pub enum Error {
     /// Any other sort of error.
     #[snafu(display("{}", message))]
     Other { message: String },
+    /// Wraps an error that occurred while parsing a field of a derived item. The start and end are
+    /// the offsets of the segment the innermost item was being parsed from. See [`Error::path`]
+    /// and [`Error::segment_range`].
+    #[snafu(display("{} (in {}, parsed from 0x{:x} to 0x{:x})", error, path, start, end))]
+    Context {
+        path: ErrorPath,
+        start: usize,
+        end: usize,
+        error: Box<Error>,
+    },
+    /// Wraps an error that occurred while reading decompressed data. The start and end are the
+    /// offsets of the compressed data it was decompressed from, while the wrapped error's offset
+    /// (if any) is an offset in the decompressed data. See [`crate::VecSource::compressed_range`].
+    #[snafu(display("{} (in data decompressed from 0x{:x} to 0x{:x})", error, start, end))]
+    InCompressed {
+        start: usize,
+        end: usize,
+        error: Box<Error>,
+    },
+}
+
+impl Error {
+    fn push_context(self, segments: &[PathSegment], start: usize, end: usize) -> Self {
+        match self {
+            // The innermost segment is kept, since that's where the error actually occurred.
+            Self::Context {
+                mut path,
+                start,
+                end,
+                error,
+            } => {
+                path.0.splice(0..0, segments.iter().cloned());
+                Self::Context {
+                    path,
+                    start,
+                    end,
+                    error,
+                }
+            }
+            error => Self::Context {
+                path: ErrorPath(segments.to_vec()),
+                start,
+                end,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Records that the error occurred while parsing the provided field of the provided type from
+    /// the segment between the provided offsets. This is used by derived parsers.
+    pub fn in_field(
+        self,
+        type_name: &'static str,
+        field: &'static str,
+        start: usize,
+        end: usize,
+    ) -> Self {
+        self.push_context(
+            &[PathSegment::Type(type_name), PathSegment::Field(field)],
+            start,
+            end,
+        )
+    }
+
+    /// Records that the error occurred while parsing the item at the provided index of a
+    /// collection from the segment between the provided offsets. This is used by derived parsers.
+    pub fn at_index(self, index: usize, start: usize, end: usize) -> Self {
+        self.push_context(&[PathSegment::Index(index)], start, end)
+    }
+
+    /// Records that the error occurred while reading data that was decompressed from the
+    /// compressed data between the provided offsets.
+    pub fn in_compressed(self, start: usize, end: usize) -> Self {
+        Self::InCompressed {
+            start,
+            end,
+            error: Box::new(self),
+        }
+    }
+
+    /// The path to the field that was being parsed when the error occurred (if it occurred
+    /// while parsing a derived item).
+    pub fn path(&self) -> Option<&ErrorPath> {
+        match self {
+            Self::Context { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The offsets of the segment the innermost derived item was being parsed from when the error
+    /// occurred (if it occurred while parsing a derived item).
+    pub fn segment_range(&self) -> Option<Range<usize>> {
+        match self {
+            Self::Context { start, end, .. } => Some(*start..*end),
+            _ => None,
+        }
+    }
+
+    /// The underlying error, without any context.
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Self::Context { error, .. } | Self::InCompressed { error, .. } => error.root_cause(),
+            error => error,
+        }
+    }
+
+    /// The offset where the error occurred, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self.root_cause() {
+            Self::OffsetTooSmall { offset }
+            | Self::OffsetTooLarge { offset }
+            | Self::NotEnoughData { offset, .. }
+            | Self::NoMoreData { offset }
+            | Self::Unmapped { offset }
+            | Self::NoMatchingVariant { offset, .. }
+            | Self::InvalidVarInt { offset }
+            | Self::InvalidUtf8 { offset }
+            | Self::InvalidUtf16 { offset }
+            | Self::BadMagic { offset, .. }
+            | Self::NonZeroPadding { offset }
+            | Self::ExceedsPadding { offset, .. }
+            | Self::ChecksumMismatch { offset, .. }
+            | Self::Decompression { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+}
+
+/// One part of an [`ErrorPath`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    /// The name of a type (or, for enums, a type and a variant).
+    Type(&'static str),
+    /// The name of a field (or its index, for tuple structs).
+    Field(&'static str),
+    /// The index of an item in a collection.
+    Index(usize),
+}
+
+/// The path to the field that was being parsed when an error occurred, such as
+/// `Header.sections[3].name`.
+///
+/// The path contains the name of every type along the way, but only the outermost one is
+/// displayed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ErrorPath(Vec<PathSegment>);
+
+impl ErrorPath {
+    /// The parts of the path, from the outermost to the innermost.
+    #[inline]
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+}
+
+impl fmt::Display for ErrorPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Type(name) if i == 0 => write!(f, "{}", name)?,
+                PathSegment::Type(_) => {}
+                PathSegment::Field(name) => write!(f, ".{}", name)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lets values that can't fail to parse (e.g. types that derive `FromSegment` with a context) be
+/// used where segsource's errors are expected.
+impl From<core::convert::Infallible> for Error {
+    fn from(never: core::convert::Infallible) -> Self {
+        match never {}
+    }
 }
 
 #[cfg(feature = "std")]
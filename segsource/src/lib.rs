@@ -17,11 +17,17 @@
 //!
 //! The following features are available for segsource:
 //!
-//! 1. `async` which adds support for various `async` operations using `tokio`.
-//! 2. `derive` which includes several macros for creating structs from [`Segment`]s.
-//! 3. `mmap` which adds support for memory mapped files.
-//! 4. `std` which adds support for file and I/O operations.
-//! 5. `with-bytes` which adds support for using the `bytes` crate.
+//! 1. `async` which adds support for various `async` operations using `tokio` (such as the
+//!    [`AsyncStreamSource`]).
+//! 2. `deflate` which adds support for decompressing raw deflate, zlib, and gzip data (e.g. via
+//!    [`Segment::decompress_zlib`]).
+//! 3. `derive` which includes several macros for creating structs from [`Segment`]s (and for
+//!    writing them back out again).
+//! 4. `lz4` which adds support for decompressing LZ4 data.
+//! 5. `mmap` which adds support for memory mapped files.
+//! 6. `std` which adds support for file and I/O operations.
+//! 7. `with-bytes` which adds support for using the `bytes` crate.
+//! 8. `zstd` which adds support for decompressing Zstandard data.
 //!
 //! Of these, only `derive` and `std` are enabled by default.
 //!
@@ -84,6 +90,22 @@
 //! # let segment = source.all().unwrap();
 //! assert!(matches!(segment.u8_at(99), Err(Error::OffsetTooSmall { offset :99 })));
 //! ```
+//!
+//! ## Writing
+//!
+//! Data can also be written using a [`U8Sink`]. [`SegmentMut`] writes into a mutable slice (and
+//! so can't grow), while [`VecSink`] grows as needed. Both use the same offset model as
+//! [`Segment`]s:
+//!
+//! ```
+//! # use segsource::{SegmentMut, U8Sink as _, Endidness, Error};
+//! let mut data = [0; 6];
+//! let mut segment = SegmentMut::with_offset_and_endidness(&mut data, 100, Endidness::Big);
+//! segment.put_u16(0x0102).unwrap();
+//! segment.put_u32_at(102, 0x03040506).unwrap();
+//! assert!(matches!(segment.put_u16_at(105, 0), Err(Error::NotEnoughData { .. })));
+//! assert_eq!(data, [0x01, 0x02, 0x03, 0x04, 0x05, 0x06]);
+//! ```
 
 #[cfg(not(feature = "std"))]
 extern crate alloc;
@@ -99,6 +121,11 @@ pub use error::*;
 pub(crate) mod segment;
 pub use segment::*;
 
+pub(crate) mod sinks;
+pub use sinks::*;
+
+pub mod checksum;
+
 #[cfg(feature = "derive")]
 #[doc(hidden)]
 pub mod derive_extras;
@@ -107,7 +134,7 @@ pub mod marker;
 
 #[cfg(feature = "derive")]
 #[doc(inline)]
-pub use segsource_derive::{FromSegment, TryFromSegment};
+pub use segsource_derive::{FromSegment, SegmentView, ToSegment, TryFromSegment};
 
 #[cfg(feature = "async")]
 #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
@@ -122,6 +149,11 @@ mod testing;
 pub enum Endidness {
     Big,
     Little,
+    /// Middle endian (also known as mixed endian or PDP endian). Values that are 16 bits or
+    /// smaller are little endian. Larger values are split in half, with the most significant half
+    /// stored first and each half stored little endian. For example, `0x0a0b0c0d_u32` is stored as
+    /// `[0x0b, 0x0a, 0x0d, 0x0c]`, which matches PDP-11 `u32`s and ARM FPA `f64`s.
+    Middle,
 }
 
 impl Endidness {
@@ -151,6 +183,7 @@ impl fmt::Display for Endidness {
         match self {
             Self::Big => write!(f, "Big"),
             Self::Little => write!(f, "Little"),
+            Self::Middle => write!(f, "Middle"),
         }
     }
 }
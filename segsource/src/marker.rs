@@ -2,23 +2,68 @@
 use crate::Endidness;
 use core::convert::TryInto;
 
-/// An extension trait for integers.
-pub trait Integer: Sized {
+/// An extension trait for numbers that can be created from (and written to) a fixed number of
+/// bytes.
+pub trait Numeric: Sized {
     const WIDTH: usize;
     fn from_be(bytes: &[u8]) -> Self;
     fn from_le(bytes: &[u8]) -> Self;
     fn from_ne(bytes: &[u8]) -> Self;
+    /// Reads the number using [`Endidness::Middle`].
+    fn from_me(bytes: &[u8]) -> Self {
+        if Self::WIDTH <= 2 {
+            Self::from_le(bytes)
+        } else {
+            // Swapping the halves turns middle endian into little endian.
+            let mut buf = [0; 16];
+            let buf = &mut buf[..Self::WIDTH];
+            buf.copy_from_slice(bytes);
+            buf.rotate_left(Self::WIDTH / 2);
+            Self::from_le(buf)
+        }
+    }
     fn with_endidness(bytes: &[u8], endidness: Endidness) -> Self {
         match endidness {
             Endidness::Big => Self::from_be(bytes),
             Endidness::Little => Self::from_le(bytes),
+            Endidness::Middle => Self::from_me(bytes),
+        }
+    }
+    fn write_be(&self, buf: &mut [u8]);
+    fn write_le(&self, buf: &mut [u8]);
+    fn write_ne(&self, buf: &mut [u8]);
+    /// Writes the number using [`Endidness::Middle`].
+    fn write_me(&self, buf: &mut [u8]) {
+        self.write_le(buf);
+        if Self::WIDTH > 2 {
+            buf.rotate_left(Self::WIDTH / 2);
+        }
+    }
+    fn write_with_endidness(&self, buf: &mut [u8], endidness: Endidness) {
+        match endidness {
+            Endidness::Big => self.write_be(buf),
+            Endidness::Little => self.write_le(buf),
+            Endidness::Middle => self.write_me(buf),
         }
     }
 }
 
-macro_rules! impl_integer {
-    ($type:ty, $width:literal, $be_method:ident, $le_method:ident, $ne_method:ident) => {
-        impl Integer for $type {
+/// An extension trait for integers.
+pub trait Integer: Numeric {}
+
+/// An extension trait for floating point numbers.
+pub trait Float: Numeric {}
+
+macro_rules! impl_numeric {
+    (
+        $marker:ident,
+        $type:ty,
+        $width:literal,
+        $be_method:ident,
+        $le_method:ident,
+        $ne_method:ident
+    ) => {
+        impl Numeric for $type {
             const WIDTH: usize = $width;
             fn from_be(bytes: &[u8]) -> Self {
                 <$type>::$be_method(bytes.try_into().unwrap())
@@ -29,20 +74,32 @@ macro_rules! impl_integer {
             fn from_ne(bytes: &[u8]) -> Self {
                 <$type>::$ne_method(bytes.try_into().unwrap())
             }
+            fn write_be(&self, buf: &mut [u8]) {
+                buf.copy_from_slice(&self.to_be_bytes())
+            }
+            fn write_le(&self, buf: &mut [u8]) {
+                buf.copy_from_slice(&self.to_le_bytes())
+            }
+            fn write_ne(&self, buf: &mut [u8]) {
+                buf.copy_from_slice(&self.to_ne_bytes())
+            }
         }
+        impl $marker for $type {}
     };
-    ($type:ty, $width:literal) => {
-        impl_integer! {$type, $width, from_be_bytes, from_le_bytes, from_ne_bytes}
+    ($marker:ident, $type:ty, $width:literal) => {
+        impl_numeric! {$marker, $type, $width, from_be_bytes, from_le_bytes, from_ne_bytes}
     };
 }
 
-impl_integer! {u8, 1}
-impl_integer! {u16, 2}
-impl_integer! {u32, 4}
-impl_integer! {u64, 8}
-impl_integer! {u128, 16}
-impl_integer! {i8, 1}
-impl_integer! {i16, 2}
-impl_integer! {i32, 4}
-impl_integer! {i64, 8}
-impl_integer! {i128, 16}
+impl_numeric! {Integer, u8, 1}
+impl_numeric! {Integer, u16, 2}
+impl_numeric! {Integer, u32, 4}
+impl_numeric! {Integer, u64, 8}
+impl_numeric! {Integer, u128, 16}
+impl_numeric! {Integer, i8, 1}
+impl_numeric! {Integer, i16, 2}
+impl_numeric! {Integer, i32, 4}
+impl_numeric! {Integer, i64, 8}
+impl_numeric! {Integer, i128, 16}
+impl_numeric! {Float, f32, 4}
+impl_numeric! {Float, f64, 8}
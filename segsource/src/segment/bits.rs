@@ -0,0 +1,241 @@
+use super::Segment;
+use crate::error::{Error, Result};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+
+/// The order in which bits are read from each byte.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BitOrder {
+    /// The most significant bit of each byte is read first, and the first bit read is the most
+    /// significant bit of the value. This is what most codec headers use.
+    MsbFirst,
+    /// The least significant bit of each byte is read first, and the first bit read is the least
+    /// significant bit of the value. This is what deflate and friends use.
+    LsbFirst,
+}
+
+/// A bit-level cursor over a [`Segment`] of bytes.
+///
+/// A [`BitSegment`] starts at the [`Segment::current_offset`] of the segment it was created from,
+/// and keeps track of its own position (so reading from it never moves the original segment's
+/// cursor). Its position is reported as a byte offset (using the same offsets as the segment) and a
+/// bit index within that byte, where the bit index is the number of bits in that byte that have
+/// already been read.
+///
+/// Like a [`Segment`], a [`BitSegment`] is thread-safe and doesn't need to be mutable to be read
+/// from.
+pub struct BitSegment<'s> {
+    segment: Segment<'s, u8>,
+    bit_order: BitOrder,
+    // The position, in bits, relative to the start of the segment's data.
+    position: AtomicUsize,
+}
+
+impl<'s> BitSegment<'s> {
+    /// Creates a new [`BitSegment`] starting at the segment's [`Segment::current_offset`].
+    pub fn new(segment: Segment<'s, u8>, bit_order: BitOrder) -> Self {
+        let position = segment.get_pos() * 8;
+        Self {
+            segment,
+            bit_order,
+            position: AtomicUsize::new(position),
+        }
+    }
+
+    #[inline]
+    fn get_pos(&self) -> usize {
+        self.position.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    fn size_in_bits(&self) -> usize {
+        self.segment.size() * 8
+    }
+
+    fn validate_bit_pos(&self, pos: usize, num_bits: usize) -> Result<()> {
+        let byte_pos = pos / 8;
+        let end = pos.checked_add(num_bits).ok_or(Error::OffsetTooLarge {
+            offset: self.segment.pos_to_offset(byte_pos),
+        })?;
+        if end > self.size_in_bits() {
+            Err(Error::NotEnoughData {
+                requested: (pos % 8 + num_bits).div_ceil(8),
+                left: self.segment.size().saturating_sub(byte_pos),
+                offset: self.segment.pos_to_offset(byte_pos),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn adj_pos(&self, num_bits: usize) -> Result<usize> {
+        let mut result = Ok(());
+        let rval = self
+            .position
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |p| {
+                result = self.validate_bit_pos(p, num_bits);
+                if result.is_ok() {
+                    Some(p + num_bits)
+                } else {
+                    None
+                }
+            });
+        result?;
+        Ok(rval.unwrap_or_else(|p| p))
+    }
+
+    fn bits_at_pos(&self, pos: usize, num_bits: u8) -> u64 {
+        let data = self.segment.data;
+        let mut value = 0;
+        for i in 0..num_bits as usize {
+            let bit_pos = pos + i;
+            let byte = data[bit_pos / 8];
+            let bit_idx = bit_pos % 8;
+            match self.bit_order {
+                BitOrder::MsbFirst => {
+                    value = (value << 1) | ((byte >> (7 - bit_idx)) & 1) as u64;
+                }
+                BitOrder::LsbFirst => value |= (((byte >> bit_idx) & 1) as u64) << i,
+            }
+        }
+        value
+    }
+
+    #[inline]
+    fn check_num_bits(num_bits: u8) -> Result<()> {
+        if num_bits > 64 {
+            Err(Error::Other {
+                message: format!("Can only read up to 64 bits at a time, not {}.", num_bits),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The order bits are read in.
+    #[inline]
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
+    /// The current position as a byte offset and the index of the next bit in that byte.
+    pub fn position(&self) -> (usize, u8) {
+        let pos = self.get_pos();
+        (self.segment.pos_to_offset(pos / 8), (pos % 8) as u8)
+    }
+
+    /// The number of bits left.
+    #[inline]
+    pub fn bits_remaining(&self) -> usize {
+        self.size_in_bits() - self.get_pos()
+    }
+
+    #[inline]
+    /// Returns `true` if there are more bits to read.
+    pub fn has_more(&self) -> bool {
+        self.bits_remaining() > 0
+    }
+
+    #[inline]
+    /// Returns `true` if the current position is at the start of a byte.
+    pub fn is_aligned(&self) -> bool {
+        self.get_pos().is_multiple_of(8)
+    }
+
+    /// Reads the next n bits (where n is `num_bits`) as an unsigned value and then advances the
+    /// position by n.
+    ///
+    /// Returns an error if more than 64 bits are requested.
+    pub fn next_bits(&self, num_bits: u8) -> Result<u64> {
+        Self::check_num_bits(num_bits)?;
+        let pos = self.adj_pos(num_bits as usize)?;
+        Ok(self.bits_at_pos(pos, num_bits))
+    }
+
+    /// Reads the next n bits (where n is `num_bits`) as an unsigned value without advancing the
+    /// position.
+    ///
+    /// Returns an error if more than 64 bits are requested.
+    pub fn peek_bits(&self, num_bits: u8) -> Result<u64> {
+        Self::check_num_bits(num_bits)?;
+        let pos = self.get_pos();
+        self.validate_bit_pos(pos, num_bits as usize)?;
+        Ok(self.bits_at_pos(pos, num_bits))
+    }
+
+    /// Reads the next n bits (where n is `num_bits`) as a two's complement signed value and then
+    /// advances the position by n.
+    ///
+    /// Returns an error if more than 64 bits are requested.
+    pub fn next_signed_bits(&self, num_bits: u8) -> Result<i64> {
+        Ok(Self::sign_extend(self.next_bits(num_bits)?, num_bits))
+    }
+
+    /// Reads the next n bits (where n is `num_bits`) as a two's complement signed value without
+    /// advancing the position.
+    ///
+    /// Returns an error if more than 64 bits are requested.
+    pub fn peek_signed_bits(&self, num_bits: u8) -> Result<i64> {
+        Ok(Self::sign_extend(self.peek_bits(num_bits)?, num_bits))
+    }
+
+    fn sign_extend(value: u64, num_bits: u8) -> i64 {
+        if num_bits == 0 || num_bits == 64 {
+            value as i64
+        } else {
+            let shift = 64 - num_bits;
+            ((value << shift) as i64) >> shift
+        }
+    }
+
+    /// Reads the next bit and then advances the position by 1.
+    #[inline]
+    pub fn next_bool(&self) -> Result<bool> {
+        Ok(self.next_bits(1)? == 1)
+    }
+
+    /// Reads the next bit without advancing the position.
+    #[inline]
+    pub fn peek_bool(&self) -> Result<bool> {
+        Ok(self.peek_bits(1)? == 1)
+    }
+
+    /// Advances the position by the provided number of bits.
+    pub fn skip_bits(&self, num_bits: usize) -> Result<()> {
+        self.adj_pos(num_bits)?;
+        Ok(())
+    }
+
+    /// Advances the position to the start of the next byte. If the position is already at the
+    /// start of a byte, then this does nothing.
+    pub fn align_to_byte(&self) {
+        // This can never go past the end, since the end is always aligned.
+        let _ = self
+            .position
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |p| {
+                Some(p.div_ceil(8) * 8)
+            });
+    }
+
+    /// Aligns the position to the start of the next byte (see [`BitSegment::align_to_byte`]), and
+    /// then turns the [`BitSegment`] back into a [`Segment`] whose [`Segment::current_offset`] is
+    /// that byte.
+    pub fn into_segment(self) -> Segment<'s, u8> {
+        self.align_to_byte();
+        let pos = self.get_pos() / 8;
+        self.segment.position.store(pos, Ordering::Relaxed);
+        self.segment
+    }
+}
+
+impl<'s> Clone for BitSegment<'s> {
+    fn clone(&self) -> Self {
+        Self {
+            segment: self.segment.clone(),
+            bit_order: self.bit_order,
+            position: AtomicUsize::new(self.get_pos()),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use super::Segment;
+use crate::{
+    checksum::Checksum,
+    error::{Error, Result},
+};
+
+impl<'s> Segment<'s, u8> {
+    /// Calculates the checksum of all of the segment's data, regardless of the
+    /// [`Segment::current_offset`].
+    ///
+    /// ```
+    /// # use segsource::{checksum::Crc32, Endidness, Segment};
+    /// let segment = Segment::with_offset_and_endidness(b"123456789", 0x100, Endidness::Big);
+    /// assert_eq!(segment.checksum::<Crc32>(), 0xcbf43926);
+    /// ```
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn checksum<C: Checksum>(&self) -> C::Output {
+        C::checksum(self.data)
+    }
+
+    /// Calculates the checksum of the data from the start offset (inclusive) to the end offset
+    /// (exclusive). This does not alter the [`Segment::current_offset`].
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn checksum_range<C: Checksum>(&self, start: usize, end: usize) -> Result<C::Output> {
+        self.validate_offset(start, 0)?;
+        if end < start {
+            return Err(Error::OffsetTooSmall { offset: end });
+        }
+        let pos = self.to_pos(start);
+        self.validate_pos(pos, end - start)?;
+        Ok(C::checksum(&self.data[pos..pos + end - start]))
+    }
+
+    /// Calculates the checksum of the data from the start offset (inclusive) to the end offset
+    /// (exclusive) and compares it to the expected value. If they don't match, an
+    /// [`Error::ChecksumMismatch`] is returned, using the provided offset (usually that of the
+    /// stored checksum) as its offset.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn verify_checksum<C: Checksum>(
+        &self,
+        start: usize,
+        end: usize,
+        expected: C::Output,
+        offset: usize,
+    ) -> Result<()> {
+        let actual = self.checksum_range::<C>(start, end)?;
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(Error::ChecksumMismatch {
+                expected: expected.into(),
+                actual: actual.into(),
+                offset,
+            })
+        }
+    }
+}
@@ -1,10 +1,10 @@
-use super::Segment;
+use super::{BitOrder, BitSegment, Segment};
 use crate::{
     error::{Error, Result},
-    marker::Integer,
+    marker::{Float, Integer, Numeric},
     Endidness,
 };
-use core::convert::TryFrom;
+use core::{convert::TryFrom, sync::atomic::Ordering};
 /// An alias for a segment that deals with binary data.
 pub type DataSegment<'s> = Segment<'s, u8>;
 
@@ -25,6 +25,42 @@ macro_rules! make_num_method_with_offset {
     };
 }
 
+macro_rules! make_var_int_methods {
+    (
+        $type:ty,
+        $decode:ident,
+        $at_name:ident,
+        $peek_name:ident,
+        $next_name:ident,
+        $desc:literal
+    ) => {
+        #[doc = $desc]
+        #[doc = "at the given offset without altering the [`Segment::current_offset`].\n\n"]
+        #[doc = "Note: Only available if the [`Segment`]'s I is `u8`."]
+        pub fn $at_name(&self, offset: usize) -> Result<$type> {
+            self.validate_offset(offset, 1)?;
+            Ok(self.$decode(self.to_pos(offset))?.0 as $type)
+        }
+
+        #[doc = $desc]
+        #[doc = "starting at the [`Segment::current_offset`] but without advancing the"]
+        #[doc = "[`Segment::current_offset`].\n\n"]
+        #[doc = "Note: Only available if the [`Segment`]'s I is `u8`."]
+        #[inline]
+        pub fn $peek_name(&self) -> Result<$type> {
+            self.$at_name(self.current_offset())
+        }
+
+        #[doc = $desc]
+        #[doc = "starting at the [`Segment::current_offset`] and then advances the"]
+        #[doc = "[`Segment::current_offset`] by the number of bytes it used.\n\n"]
+        #[doc = "Note: Only available if the [`Segment`]'s I is `u8`."]
+        pub fn $next_name(&self) -> Result<$type> {
+            Ok(self.next_with_len(Self::$decode)? as $type)
+        }
+    };
+}
+
 impl<'s> DataSegment<'s> {
     /// Creates a new [`Segment`] using the provided endidness.
     ///
@@ -51,7 +87,25 @@ impl<'s> DataSegment<'s> {
     ///
     /// Note: Only available if the [`Segment`]'s I is `u8`.
     pub fn endidness(&self) -> Endidness {
-        self.endidness
+        self.endidness.load()
+    }
+
+    /// Changes the endidness used for any future reads. This is useful for formats (like TIFF)
+    /// whose endidness is only known after reading part of them.
+    ///
+    /// ```
+    /// # use segsource::{Endidness, Segment};
+    /// let segment = Segment::new(b"MM\x00\x2a");
+    /// if segment.next_n_as_slice(2).unwrap() == b"MM" {
+    ///     segment.set_endidness(Endidness::Big);
+    /// }
+    /// assert_eq!(segment.next_u16().unwrap(), 42);
+    /// ```
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn set_endidness(&self, endidness: Endidness) {
+        self.endidness.store(endidness)
     }
 
     /// Fills the provided buffer with the next n bytes, where n is the length of the buffer. This
@@ -66,19 +120,25 @@ impl<'s> DataSegment<'s> {
         Ok(())
     }
 
-    fn int_at_pos<N: Integer>(&self, pos: usize) -> Result<N> {
+    /// Creates a [`BitSegment`] that reads bits in the provided order, starting at the
+    /// [`Segment::current_offset`]. Reading from the [`BitSegment`] does not alter this segment's
+    /// [`Segment::current_offset`].
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn bits(&self, bit_order: BitOrder) -> BitSegment<'s> {
+        BitSegment::new(self.clone(), bit_order)
+    }
+
+    pub(super) fn num_at_pos<N: Numeric>(&self, pos: usize) -> Result<N> {
         self.validate_pos(pos, N::WIDTH - 1)?;
         Ok(N::with_endidness(
             &self.data[pos..pos + N::WIDTH],
-            self.endidness,
+            self.endidness.load(),
         ))
     }
 
-    pub fn int_at_with_endidness<N: Integer>(
-        &self,
-        offset: usize,
-        endidness: Endidness,
-    ) -> Result<N> {
+    fn num_at_with_endidness<N: Numeric>(&self, offset: usize, endidness: Endidness) -> Result<N> {
         self.validate_offset(offset, N::WIDTH - 1)?;
         Ok(N::with_endidness(
             &self[offset..offset + N::WIDTH],
@@ -86,6 +146,18 @@ impl<'s> DataSegment<'s> {
         ))
     }
 
+    /// Like [`Segment::int_at`], but uses the provided endidness instead of the [`Segment`]'s.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn int_at_with_endidness<N: Integer>(
+        &self,
+        offset: usize,
+        endidness: Endidness,
+    ) -> Result<N> {
+        self.num_at_with_endidness(offset, endidness)
+    }
+
     /// Gets an integer of the provided type (e.g. `u8`, `i8`, `u16`, `i16`, etcetera) at the given
     /// offset without altering the [`Segment::current_offset`]. In most cases, you should use
     /// methods like [`Segment::u8_at`] instead.
@@ -93,7 +165,7 @@ impl<'s> DataSegment<'s> {
     /// Note: Only available if the [`Segment`]'s I is `u8`.
     #[inline]
     pub fn int_at<N: Integer>(&self, offset: usize) -> Result<N> {
-        self.int_at_with_endidness(offset, self.endidness)
+        self.int_at_with_endidness(offset, self.endidness.load())
     }
 
     #[inline]
@@ -190,14 +262,14 @@ impl<'s> DataSegment<'s> {
     "Note: Only available if the [`Segment`]'s I is `u8`."}
 
     /// Gets an integer of the provided type (e.g. `u8`, `i8`, `u16`, `i16`, etcetera) starting at
-    /// the at the [`Segment::current_offset`] but without advancing the
-    /// [`Segment::current_offset`]. In most
-    /// cases, you should use methods like [`Segment::peek_u8`] instead.
+    /// the [`Segment::current_offset`] but without advancing the [`Segment::current_offset`]. This
+    /// is the same as [`Segment::current_int`]. In most cases, you should use methods like
+    /// [`Segment::peek_u8`] instead.
     ///
     /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
     pub fn peek_int<N: Integer>(&self) -> Result<N> {
-        let pos = self.adj_pos(N::WIDTH as i128)?;
-        self.int_at(self.pos_to_offset(pos))
+        self.current_int()
     }
 
     make_num_method! {u8, peek_u8, peek_int,
@@ -251,6 +323,14 @@ impl<'s> DataSegment<'s> {
         self.int_at(self.pos_to_offset(pos))
     }
 
+    /// Like [`Segment::next_int`], but uses the provided endidness instead of the [`Segment`]'s.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_int_with_endidness<N: Integer>(&self, endidness: Endidness) -> Result<N> {
+        let pos = self.adj_pos(N::WIDTH as i128)?;
+        self.int_at_with_endidness(self.pos_to_offset(pos), endidness)
+    }
+
     #[inline]
     /// See the documentation for [`Segment::next_int`].
     ///
@@ -294,6 +374,303 @@ impl<'s> DataSegment<'s> {
     make_num_method! {i128, next_i128, next_int,
     "See the documentation for [`Segment::next_int`].\n\n",
     "Note: Only available if the [`Segment`]'s I is `u8`."}
+
+    /// Like [`Segment::float_at`], but uses the provided endidness instead of the [`Segment`]'s.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn float_at_with_endidness<N: Float>(
+        &self,
+        offset: usize,
+        endidness: Endidness,
+    ) -> Result<N> {
+        self.num_at_with_endidness(offset, endidness)
+    }
+
+    /// Gets a floating point number of the provided type (i.e. `f32` or `f64`) at the given offset
+    /// without altering the [`Segment::current_offset`]. In most cases, you should use methods
+    /// like [`Segment::f32_at`] instead.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn float_at<N: Float>(&self, offset: usize) -> Result<N> {
+        self.float_at_with_endidness(offset, self.endidness.load())
+    }
+
+    make_num_method_with_offset! {f32, f32_at, float_at,
+    "See the documentation for [`Segment::float_at`].\n\n",
+    "Note: Only available if the [`Segment`]'s I is `u8`."}
+
+    make_num_method_with_offset! {f64, f64_at, float_at,
+    "See the documentation for [`Segment::float_at`].\n\n",
+    "Note: Only available if the [`Segment`]'s I is `u8`."}
+
+    /// Gets a floating point number of the provided type (i.e. `f32` or `f64`) starting at the
+    /// [`Segment::current_offset`] without altering it. In most cases, you should use methods like
+    /// [`Segment::current_f32`] instead.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn current_float<N: Float>(&self) -> Result<N> {
+        self.float_at(self.current_offset())
+    }
+
+    make_num_method! {f32, current_f32, current_float,
+    "See the documentation for [`Segment::current_float`].\n\n",
+    "Note: Only available if the [`Segment`]'s I is `u8`."}
+
+    make_num_method! {f64, current_f64, current_float,
+    "See the documentation for [`Segment::current_float`].\n\n",
+    "Note: Only available if the [`Segment`]'s I is `u8`."}
+
+    /// Gets a floating point number of the provided type (i.e. `f32` or `f64`) starting at the
+    /// [`Segment::current_offset`] but without advancing the [`Segment::current_offset`]. This is
+    /// the same as [`Segment::current_float`]. In most cases, you should use methods like
+    /// [`Segment::peek_f32`] instead.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn peek_float<N: Float>(&self) -> Result<N> {
+        self.current_float()
+    }
+
+    make_num_method! {f32, peek_f32, peek_float,
+    "See the documentation for [`Segment::peek_float`].\n\n",
+    "Note: Only available if the [`Segment`]'s I is `u8`."}
+
+    make_num_method! {f64, peek_f64, peek_float,
+    "See the documentation for [`Segment::peek_float`].\n\n",
+    "Note: Only available if the [`Segment`]'s I is `u8`."}
+
+    /// Gets a floating point number of the provided type (i.e. `f32` or `f64`) starting at the
+    /// [`Segment::current_offset`] and then advances the [`Segment::current_offset`] by n, where n
+    /// is the number of bytes required to create the requested type. In most cases, you should use
+    /// methods like [`Segment::next_f32`] instead.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_float<N: Float>(&self) -> Result<N> {
+        let pos = self.adj_pos(N::WIDTH as i128)?;
+        self.float_at(self.pos_to_offset(pos))
+    }
+
+    /// Like [`Segment::next_float`], but uses the provided endidness instead of the
+    /// [`Segment`]'s.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_float_with_endidness<N: Float>(&self, endidness: Endidness) -> Result<N> {
+        let pos = self.adj_pos(N::WIDTH as i128)?;
+        self.float_at_with_endidness(self.pos_to_offset(pos), endidness)
+    }
+
+    make_num_method! {f32, next_f32, next_float,
+    "See the documentation for [`Segment::next_float`].\n\n",
+    "Note: Only available if the [`Segment`]'s I is `u8`."}
+
+    make_num_method! {f64, next_f64, next_float,
+    "See the documentation for [`Segment::next_float`].\n\n",
+    "Note: Only available if the [`Segment`]'s I is `u8`."}
+
+    fn var_int_byte(&self, pos: usize, idx: usize) -> Result<u8> {
+        if pos + idx >= self.size {
+            Err(Error::NotEnoughData {
+                requested: idx + 1,
+                left: self.calc_remaining(pos),
+                offset: self.pos_to_offset(pos),
+            })
+        } else {
+            Ok(self.data[pos + idx])
+        }
+    }
+
+    /// Decodes a LEB128 encoded integer starting at the given position, returning its bits (sign
+    /// extended if `signed` is true) and the number of bytes it used. Only the shortest encoding
+    /// of a value is accepted.
+    fn leb128_at_pos(&self, pos: usize, signed: bool) -> Result<(u64, usize)> {
+        let mut value = 0;
+        let mut shift = 0;
+        let mut len = 0;
+        let mut prev_byte = 0;
+        loop {
+            let byte = self.var_int_byte(pos, len)?;
+            len += 1;
+            if len > 1 && byte & 0x80 == 0 {
+                // The last group is redundant if it's nothing but zeros (or, if it's signed, a
+                // copy of the previous group's sign bit).
+                let redundant = if signed {
+                    (byte == 0 && prev_byte & 0x40 == 0) || (byte == 0x7f && prev_byte & 0x40 != 0)
+                } else {
+                    byte == 0
+                };
+                if redundant {
+                    return Err(Error::InvalidVarInt {
+                        offset: self.pos_to_offset(pos),
+                    });
+                }
+            }
+            prev_byte = byte;
+            let group = (byte & 0x7f) as u64;
+            if shift == 63 {
+                // There's only room for one more bit, so the rest of the group can only be a sign
+                // extension of it, and there can't be any more groups.
+                let valid = if signed {
+                    group == 0 || group == 0x7f
+                } else {
+                    group <= 1
+                };
+                if !valid || byte & 0x80 != 0 {
+                    return Err(Error::InvalidVarInt {
+                        offset: self.pos_to_offset(pos),
+                    });
+                }
+            }
+            value |= group << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if signed && shift < 64 && byte & 0x40 != 0 {
+                    value |= !0 << shift;
+                }
+                return Ok((value, len));
+            }
+        }
+    }
+
+    #[inline]
+    fn uleb128_at_pos(&self, pos: usize) -> Result<(u64, usize)> {
+        self.leb128_at_pos(pos, false)
+    }
+
+    #[inline]
+    fn sleb128_at_pos(&self, pos: usize) -> Result<(u64, usize)> {
+        self.leb128_at_pos(pos, true)
+    }
+
+    fn zigzag_at_pos(&self, pos: usize) -> Result<(u64, usize)> {
+        let (value, len) = self.leb128_at_pos(pos, false)?;
+        Ok(((value >> 1) ^ 0u64.wrapping_sub(value & 1), len))
+    }
+
+    /// Decodes a VLQ starting at the given position, returning its value and the number of bytes
+    /// it used. As with LEB128s, only the shortest encoding of a value is accepted.
+    fn vlq_at_pos(&self, pos: usize) -> Result<(u64, usize)> {
+        let mut value: u64 = 0;
+        let mut len = 0;
+        loop {
+            let byte = self.var_int_byte(pos, len)?;
+            len += 1;
+            // A leading group of nothing but zeros is redundant. Since every other encoding starts
+            // with a non-zero group, any value that's too long won't fit in 64 bits.
+            if (len == 1 && byte == 0x80) || value >> 57 != 0 {
+                return Err(Error::InvalidVarInt {
+                    offset: self.pos_to_offset(pos),
+                });
+            }
+            value = (value << 7) | (byte & 0x7f) as u64;
+            if byte & 0x80 == 0 {
+                return Ok((value, len));
+            }
+        }
+    }
+
+    /// Reads a value whose length isn't known until it's been decoded (using the provided decode
+    /// function, which returns the value and the number of bytes it used) starting at the
+    /// [`Segment::current_offset`], and then advances the [`Segment::current_offset`] by that
+    /// number of bytes.
+    pub(super) fn next_with_len<V, F>(&self, decode: F) -> Result<V>
+    where
+        F: Fn(&Self, usize) -> Result<(V, usize)>,
+    {
+        let mut result = None;
+        let _ =
+            self.position
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |pos| {
+                    match decode(self, pos) {
+                        Ok((value, len)) => {
+                            result = Some(Ok(value));
+                            Some(pos + len)
+                        }
+                        Err(error) => {
+                            result = Some(Err(error));
+                            None
+                        }
+                    }
+                });
+        // The closure is always called at least once.
+        result.unwrap()
+    }
+
+    make_var_int_methods! {u64, uleb128_at_pos, uleb128_at, peek_uleb128, next_uleb128,
+    "Gets an unsigned LEB128 encoded integer (as used by DWARF and WebAssembly)"}
+
+    make_var_int_methods! {i64, sleb128_at_pos, sleb128_at, peek_sleb128, next_sleb128,
+    "Gets a signed LEB128 encoded integer (as used by DWARF and WebAssembly)"}
+
+    make_var_int_methods! {u64, uleb128_at_pos, varint_at, peek_varint, next_varint,
+    "Gets a protobuf-style varint (which is encoded the same way as an unsigned LEB128)"}
+
+    make_var_int_methods! {i64, zigzag_at_pos, zigzag_at, peek_zigzag, next_zigzag,
+    "Gets a zig-zag encoded protobuf-style varint (i.e. an `sint32` or `sint64`)"}
+
+    make_var_int_methods! {u64, vlq_at_pos, vlq_at, peek_vlq, next_vlq,
+    "Gets a MIDI-style variable-length quantity (i.e. big endian groups of 7 bits)"}
+
+    fn magic_at_pos(&self, pos: usize, expected: &[u8]) -> Result<((), usize)> {
+        let found = &self.data[pos..pos + expected.len()];
+        if found == expected {
+            Ok(((), expected.len()))
+        } else {
+            Err(Error::BadMagic {
+                expected: expected.to_vec(),
+                found: found.to_vec(),
+                offset: self.pos_to_offset(pos),
+            })
+        }
+    }
+
+    /// Checks that the bytes at the given offset are the expected magic number (or any other
+    /// constant) without altering the [`Segment::current_offset`]. If they aren't, an
+    /// [`Error::BadMagic`] is returned. If there aren't enough bytes left for the magic number, the
+    /// same error as any other read that's too short (e.g. [`Error::NotEnoughData`]) is returned
+    /// instead.
+    ///
+    /// ```
+    /// # use segsource::{Error, Segment};
+    /// let segment = Segment::new(b"\x7fELF\x02");
+    /// segment.next_magic(b"\x7fELF").unwrap();
+    /// assert_eq!(segment.current_offset(), 4);
+    /// assert!(matches!(
+    ///     segment.peek_magic(b"\x01"),
+    ///     Err(Error::BadMagic { offset: 4, .. })
+    /// ));
+    /// ```
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn magic_at(&self, offset: usize, expected: &[u8]) -> Result<()> {
+        self.validate_offset(offset, expected.len())?;
+        self.magic_at_pos(self.to_pos(offset), expected)?;
+        Ok(())
+    }
+
+    /// See the documentation for [`Segment::magic_at`]. This uses the [`Segment::current_offset`]
+    /// but does not alter it.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn peek_magic(&self, expected: &[u8]) -> Result<()> {
+        self.magic_at(self.current_offset(), expected)
+    }
+
+    /// Checks that the bytes at the [`Segment::current_offset`] are the expected magic number (or
+    /// any other constant) and then advances the [`Segment::current_offset`] past them. If they
+    /// aren't, an [`Error::BadMagic`] is returned and the [`Segment::current_offset`] isn't
+    /// changed.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_magic(&self, expected: &[u8]) -> Result<()> {
+        self.next_with_len(|me, pos| {
+            me.validate_pos(pos, expected.len())?;
+            me.magic_at_pos(pos, expected)
+        })
+    }
 }
 impl<'s> TryFrom<&DataSegment<'s>> for () {
     type Error = Error;
@@ -309,25 +686,28 @@ impl<'s> TryFrom<&Segment<'s, u8>> for u8 {
 }
 
 macro_rules! impl_try_from {
-    ($type:ty) => {
+    ($type:ty, $zero:literal, $method:ident) => {
         impl<'s> TryFrom<&Segment<'s, u8>> for $type {
             type Error = Error;
             fn try_from(segment: &Segment<'s, u8>) -> Result<Self> {
-                segment.next_int()
+                segment.$method()
             }
         }
         impl<'s, const N: usize> TryFrom<&Segment<'s, u8>> for [$type; N] {
             type Error = Error;
             fn try_from(segment: &Segment<'s, u8>) -> Result<Self> {
                 let pos = segment.adj_pos((<$type>::WIDTH * N) as i128)?;
-                let mut array = [0; N];
+                let mut array = [$zero; N];
                 for i in 0..N {
-                    array[i] = segment.int_at_pos(pos + (i * <$type>::WIDTH))?
+                    array[i] = segment.num_at_pos(pos + (i * <$type>::WIDTH))?
                 }
                 Ok(array)
             }
         }
     };
+    ($type:ty) => {
+        impl_try_from! { $type, 0, next_int }
+    };
 }
 
 impl_try_from! { u16 }
@@ -340,3 +720,6 @@ impl_try_from! { i16 }
 impl_try_from! { i32 }
 impl_try_from! { i64 }
 impl_try_from! { i128 }
+
+impl_try_from! { f32, 0.0, next_float }
+impl_try_from! { f64, 0.0, next_float }
@@ -0,0 +1,189 @@
+use super::Segment;
+use crate::{
+    error::{Error, Result},
+    VecSource,
+};
+use core::fmt::Display;
+#[cfg(feature = "deflate")]
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use std::io::{self, Read};
+
+/// The most data (in bytes) that the `decompress_*` methods decompress before returning an
+/// error, so that a small amount of malicious compressed data can't use up all of the available
+/// memory. The `decompress_*_with_max_size` methods can be used to pick a different limit.
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 256 * 1024 * 1024;
+
+/// Reads everything from a decoder, returning an error if there's more than `max_size` bytes.
+fn read_all<R: Read>(decoder: R, max_size: usize) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    // Reading one more byte than the limit is how we know if the limit was exceeded.
+    decoder
+        .take((max_size as u64).saturating_add(1))
+        .read_to_end(&mut data)?;
+    if data.len() > max_size {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "The decompressed data is more than {} bytes long.",
+                max_size
+            ),
+        ))
+    } else {
+        Ok(data)
+    }
+}
+
+impl<'s> Segment<'s, u8> {
+    /// Creates the [`VecSource`] for the decompressed data, linking it back to the segment.
+    fn decompressed<E: Display>(
+        &self,
+        result: core::result::Result<Vec<u8>, E>,
+    ) -> Result<VecSource<u8>> {
+        match result {
+            Ok(data) => Ok(VecSource::decompressed(
+                data,
+                self.endidness.load(),
+                self.lower_offset_limit()..self.upper_offset_limit(),
+            )),
+            Err(error) => Err(Error::Decompression {
+                offset: self.initial_offset,
+                message: error.to_string(),
+            }),
+        }
+    }
+
+    /// Decompresses all of the segment's data (regardless of the [`Segment::current_offset`]) as a
+    /// raw deflate stream. The new [`VecSource`] has an initial offset of zero and the same
+    /// endidness as the segment, and its [`VecSource::compressed_range`] is the segment's offsets.
+    /// If the data decompresses to more than [`DEFAULT_MAX_DECOMPRESSED_SIZE`] bytes, an error is
+    /// returned instead.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8` and the `deflate` feature is enabled.
+    #[cfg(feature = "deflate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+    #[inline]
+    pub fn decompress_deflate(&self) -> Result<VecSource<u8>> {
+        self.decompress_deflate_with_max_size(DEFAULT_MAX_DECOMPRESSED_SIZE)
+    }
+
+    /// Like [`Segment::decompress_deflate`], but returns an error if the data decompresses to more
+    /// than `max_size` bytes instead.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8` and the `deflate` feature is enabled.
+    #[cfg(feature = "deflate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+    pub fn decompress_deflate_with_max_size(&self, max_size: usize) -> Result<VecSource<u8>> {
+        self.decompressed(read_all(DeflateDecoder::new(self.data), max_size))
+    }
+
+    /// Decompresses all of the segment's data as a zlib stream. See
+    /// [`Segment::decompress_deflate`] for more details.
+    ///
+    /// ```
+    /// # use segsource::{Endidness, Segment, Source as _};
+    /// let compressed = [
+    ///     0x78, 0x9c, 0x63, 0x60, 0x60, 0x60, 0x02, 0x00, 0x00, 0x06, 0x00, 0x03,
+    /// ];
+    /// let segment = Segment::with_offset_and_endidness(&compressed, 0x40, Endidness::Big);
+    /// let source = segment.decompress_zlib().unwrap();
+    /// assert_eq!(source.compressed_range(), Some(0x40..0x4c));
+    /// assert_eq!(source.all().unwrap().next_u32().unwrap(), 2);
+    /// ```
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8` and the `deflate` feature is enabled.
+    #[cfg(feature = "deflate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+    #[inline]
+    pub fn decompress_zlib(&self) -> Result<VecSource<u8>> {
+        self.decompress_zlib_with_max_size(DEFAULT_MAX_DECOMPRESSED_SIZE)
+    }
+
+    /// Like [`Segment::decompress_zlib`], but returns an error if the data decompresses to more
+    /// than `max_size` bytes instead.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8` and the `deflate` feature is enabled.
+    #[cfg(feature = "deflate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+    pub fn decompress_zlib_with_max_size(&self, max_size: usize) -> Result<VecSource<u8>> {
+        self.decompressed(read_all(ZlibDecoder::new(self.data), max_size))
+    }
+
+    /// Decompresses all of the segment's data as gzip data. See [`Segment::decompress_deflate`]
+    /// for more details.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8` and the `deflate` feature is enabled.
+    #[cfg(feature = "deflate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+    #[inline]
+    pub fn decompress_gzip(&self) -> Result<VecSource<u8>> {
+        self.decompress_gzip_with_max_size(DEFAULT_MAX_DECOMPRESSED_SIZE)
+    }
+
+    /// Like [`Segment::decompress_gzip`], but returns an error if the data decompresses to more
+    /// than `max_size` bytes instead.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8` and the `deflate` feature is enabled.
+    #[cfg(feature = "deflate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+    pub fn decompress_gzip_with_max_size(&self, max_size: usize) -> Result<VecSource<u8>> {
+        self.decompressed(read_all(GzDecoder::new(self.data), max_size))
+    }
+
+    /// Decompresses all of the segment's data as an LZ4 frame. See
+    /// [`Segment::decompress_deflate`] for more details.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8` and the `lz4` feature is enabled.
+    #[cfg(feature = "lz4")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "lz4")))]
+    #[inline]
+    pub fn decompress_lz4(&self) -> Result<VecSource<u8>> {
+        self.decompress_lz4_with_max_size(DEFAULT_MAX_DECOMPRESSED_SIZE)
+    }
+
+    /// Like [`Segment::decompress_lz4`], but returns an error if the data decompresses to more
+    /// than `max_size` bytes instead.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8` and the `lz4` feature is enabled.
+    #[cfg(feature = "lz4")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "lz4")))]
+    pub fn decompress_lz4_with_max_size(&self, max_size: usize) -> Result<VecSource<u8>> {
+        let decoder = lz4_flex::frame::FrameDecoder::new(self.data);
+        self.decompressed(read_all(decoder, max_size))
+    }
+
+    /// Decompresses all of the segment's data as a single LZ4 block. Since blocks don't record
+    /// how large they are once decompressed, the decompressed size has to be provided (and it's
+    /// also the most that will be decompressed). See [`Segment::decompress_deflate`] for more
+    /// details.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8` and the `lz4` feature is enabled.
+    #[cfg(feature = "lz4")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "lz4")))]
+    pub fn decompress_lz4_block(&self, decompressed_size: usize) -> Result<VecSource<u8>> {
+        self.decompressed(lz4_flex::block::decompress(self.data, decompressed_size))
+    }
+
+    /// Decompresses all of the segment's data as a Zstandard frame. See
+    /// [`Segment::decompress_deflate`] for more details.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8` and the `zstd` feature is enabled.
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    #[inline]
+    pub fn decompress_zstd(&self) -> Result<VecSource<u8>> {
+        self.decompress_zstd_with_max_size(DEFAULT_MAX_DECOMPRESSED_SIZE)
+    }
+
+    /// Like [`Segment::decompress_zstd`], but returns an error if the data decompresses to more
+    /// than `max_size` bytes instead.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8` and the `zstd` feature is enabled.
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    pub fn decompress_zstd_with_max_size(&self, max_size: usize) -> Result<VecSource<u8>> {
+        let data = ruzstd::StreamingDecoder::new(self.data)
+            .map_err(|error| error.to_string())
+            .and_then(|decoder| read_all(decoder, max_size).map_err(|error| error.to_string()));
+        self.decompressed(data)
+    }
+}
@@ -0,0 +1,48 @@
+use super::Segment;
+use core::convert::TryFrom;
+
+/// An item that can be parsed from a [`Segment`] (starting at its [`Segment::current_offset`]),
+/// given some context (e.g. a version number from a header that was parsed earlier).
+///
+/// This can be derived via `#[derive(TryFromSegment)]` using the `ctx` attribute, which names the
+/// context's values so that the type's fields can use them. Fields can then pass context down to
+/// their own types via their `ctx` attribute.
+///
+/// Anything that implements `TryFrom<&Segment>` (which includes anything that derives
+/// `TryFromSegment` without a `ctx`) implements this trait with a context of `()`.
+///
+/// ```
+/// # use segsource::{Error, FromSegment, Segment};
+/// struct Version(u16);
+///
+/// impl<'s> FromSegment<'s, u8> for Version {
+///     type Error = Error;
+///
+///     fn parse(segment: &Segment<'s, u8>, major: u8) -> Result<Self, Self::Error> {
+///         Ok(Self(((major as u16) << 8) | segment.next_u8()? as u16))
+///     }
+/// }
+///
+/// let segment = Segment::new(&[0x02]);
+/// assert_eq!(Version::parse(&segment, 0x01).unwrap().0, 0x0102);
+/// assert_eq!(u8::parse(&Segment::new(&[0x03]), ()).unwrap(), 0x03);
+/// ```
+pub trait FromSegment<'s, Ctx = (), I = u8>: Sized {
+    /// The type returned if the item can't be parsed.
+    type Error;
+
+    /// Parses the item from the segment using the provided context.
+    fn parse(segment: &Segment<'s, I>, ctx: Ctx) -> Result<Self, Self::Error>;
+}
+
+impl<'s, I, T, E> FromSegment<'s, (), I> for T
+where
+    T: for<'a> TryFrom<&'a Segment<'s, I>, Error = E>,
+{
+    type Error = E;
+
+    #[inline]
+    fn parse(segment: &Segment<'s, I>, _: ()) -> Result<Self, Self::Error> {
+        T::try_from(segment)
+    }
+}
@@ -7,7 +7,7 @@ use core::{
     borrow::Borrow,
     convert::TryFrom,
     ops::{self, Bound, Index, RangeBounds as _},
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::atomic::{AtomicU8, AtomicUsize, Ordering},
 };
 #[cfg(feature = "std")]
 use std::io;
@@ -15,6 +15,35 @@ use std::io;
 mod data;
 pub use data::*;
 
+mod mutable;
+pub use mutable::*;
+
+mod bits;
+pub use bits::*;
+
+mod checksum;
+
+#[cfg(any(feature = "deflate", feature = "lz4", feature = "zstd"))]
+mod decompress;
+#[cfg(any(feature = "deflate", feature = "lz4", feature = "zstd"))]
+pub use decompress::DEFAULT_MAX_DECOMPRESSED_SIZE;
+
+mod from_segment;
+pub use from_segment::*;
+
+pub(crate) mod padding;
+
+mod parse_iter;
+pub use parse_iter::*;
+
+mod text;
+
+mod view;
+pub use view::*;
+
+mod search;
+pub use search::*;
+
 /// A segment of a [`crate::Source`].
 ///
 /// This is where data is actually read from. Each segment keeps track of a few things:
@@ -39,7 +68,41 @@ pub struct Segment<'s, I> {
     // We use the slice's len a lot, and it never changes, so we might as well cache it.
     size: usize,
     // Used for u8 segments
-    endidness: Endidness,
+    endidness: AtomicEndidness,
+}
+
+/// An [`Endidness`] that can be changed through a shared reference (so that a [`Segment`]'s
+/// endidness can be changed part way through reading it).
+struct AtomicEndidness(AtomicU8);
+
+impl AtomicEndidness {
+    #[inline]
+    fn new(endidness: Endidness) -> Self {
+        Self(AtomicU8::new(Self::to_u8(endidness)))
+    }
+
+    #[inline]
+    fn to_u8(endidness: Endidness) -> u8 {
+        match endidness {
+            Endidness::Big => 0,
+            Endidness::Little => 1,
+            Endidness::Middle => 2,
+        }
+    }
+
+    #[inline]
+    fn load(&self) -> Endidness {
+        match self.0.load(Ordering::Relaxed) {
+            0 => Endidness::Big,
+            1 => Endidness::Little,
+            _ => Endidness::Middle,
+        }
+    }
+
+    #[inline]
+    fn store(&self, endidness: Endidness) {
+        self.0.store(Self::to_u8(endidness), Ordering::Relaxed)
+    }
 }
 
 impl<'s, I> Segment<'s, I> {
@@ -53,11 +116,17 @@ impl<'s, I> Segment<'s, I> {
             initial_offset,
             position: AtomicUsize::new(position),
             data,
-            endidness,
+            endidness: AtomicEndidness::new(endidness),
             size: data.len(),
         }
     }
 
+    /// The segment's data, borrowed for as long as the data itself is.
+    #[inline]
+    pub(crate) fn data(&self) -> &'s [I] {
+        self.data
+    }
+
     #[inline]
     fn get_pos(&self) -> usize {
         self.position.load(Ordering::Relaxed)
@@ -139,7 +208,7 @@ impl<'s, I> Segment<'s, I> {
             &self.data[pos..pos + num_items],
             self.initial_offset + pos,
             0,
-            self.endidness,
+            self.endidness.load(),
         ))
     }
 
@@ -221,7 +290,7 @@ impl<'s, I> Segment<'s, I> {
             &self.data[pos..pos + remaining],
             self.initial_offset + pos,
             0,
-            self.endidness,
+            self.endidness.load(),
         ))
     }
 
@@ -279,15 +348,18 @@ impl<'s, I> Segment<'s, I> {
 
     fn validate_pos(&self, pos: usize, size: usize) -> Result<()> {
         if size > 0 && self.calc_remaining(pos) == 0 {
-            Err(Error::NoMoreData)
+            Err(Error::NoMoreData {
+                offset: self.pos_to_offset(pos),
+            })
         } else if pos > self.size {
             Err(Error::OffsetTooLarge {
                 offset: self.pos_to_offset(pos),
             })
-        } else if pos > self.size - size as usize {
+        } else if size > self.size - pos {
             Err(Error::NotEnoughData {
                 requested: size,
                 left: self.size - pos,
+                offset: self.pos_to_offset(pos),
             })
         } else {
             Ok(())
@@ -321,7 +393,7 @@ impl<'s, I> Segment<'s, I> {
         Ok(Segment::inner_with_offset(
             self.get_as_slice(offset, offset + num_items as usize)?,
             offset,
-            self.endidness,
+            self.endidness.load(),
         ))
     }
 
@@ -341,7 +413,7 @@ impl<'s, I> Segment<'s, I> {
         Ok(Segment::inner_with_offset(
             &self[start..end],
             start,
-            self.endidness,
+            self.endidness.load(),
         ))
     }
 
@@ -351,7 +423,7 @@ impl<'s, I> Segment<'s, I> {
         Ok(Segment::inner_with_offset(
             &self[offset..],
             offset,
-            self.endidness,
+            self.endidness.load(),
         ))
     }
 
@@ -361,7 +433,7 @@ impl<'s, I> Segment<'s, I> {
         Ok(Segment::inner_with_offset(
             &self[..offset],
             self.initial_offset,
-            self.endidness,
+            self.endidness.load(),
         ))
     }
 }
@@ -549,7 +621,7 @@ impl<'s, I> Clone for Segment<'s, I> {
             initial_offset: self.initial_offset,
             position: AtomicUsize::new(self.get_pos()),
             data: self.data,
-            endidness: self.endidness,
+            endidness: AtomicEndidness::new(self.endidness.load()),
             size: self.size,
         }
     }
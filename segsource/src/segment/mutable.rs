@@ -0,0 +1,335 @@
+use super::Segment;
+use crate::{
+    error::{Error, Result},
+    sinks::offset_by,
+    Endidness, U8Sink,
+};
+#[cfg(feature = "std")]
+use std::io;
+
+/// The mutable counterpart to a [`Segment`].
+///
+/// A [`SegmentMut`] uses the same offset model as a [`Segment`] (i.e. it has an initial offset and
+/// a cursor), but allows the underlying data to be changed. Because its data is a mutable slice, it
+/// can never grow, so any write that goes past its end will return an error.
+///
+/// Unlike a [`Segment`], a [`SegmentMut`] requires a mutable reference to move its cursor.
+pub struct SegmentMut<'s, I> {
+    initial_offset: usize,
+    position: usize,
+    data: &'s mut [I],
+    // Used for u8 segments
+    endidness: Endidness,
+}
+
+impl<'s, I> SegmentMut<'s, I> {
+    pub(crate) fn new_full(
+        data: &'s mut [I],
+        initial_offset: usize,
+        position: usize,
+        endidness: Endidness,
+    ) -> Self {
+        Self {
+            initial_offset,
+            position,
+            data,
+            endidness,
+        }
+    }
+
+    #[inline]
+    fn to_pos(&self, offset: usize) -> usize {
+        offset - self.initial_offset
+    }
+
+    #[inline]
+    fn pos_to_offset(&self, pos: usize) -> usize {
+        pos + self.initial_offset
+    }
+
+    fn validate_pos(&self, pos: usize, size: usize) -> Result<()> {
+        if pos > self.data.len() {
+            Err(Error::OffsetTooLarge {
+                offset: self.pos_to_offset(pos),
+            })
+        } else if size > self.data.len() - pos {
+            Err(Error::NotEnoughData {
+                requested: size,
+                left: self.data.len() - pos,
+                offset: self.pos_to_offset(pos),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    #[inline]
+    pub fn new(data: &'s mut [I]) -> Self {
+        Self::new_full(data, 0, 0, Endidness::default())
+    }
+
+    #[inline]
+    /// Generates a new [`SegmentMut`] using the provided slice and initial offset.
+    pub fn with_offset(data: &'s mut [I], initial_offset: usize) -> Self {
+        Self::new_full(data, initial_offset, 0, Endidness::default())
+    }
+
+    /// Changes the initial offset.
+    #[inline]
+    pub fn change_initial_offset(&mut self, offset: usize) {
+        self.initial_offset = offset;
+    }
+
+    #[inline]
+    /// The initial offset of the [`SegmentMut`].
+    pub fn initial_offset(&self) -> usize {
+        self.initial_offset
+    }
+
+    #[inline]
+    /// The number of items in the [`SegmentMut`].
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    #[inline]
+    /// The current offset of the [`SegmentMut`]'s cursor.
+    pub fn current_offset(&self) -> usize {
+        self.pos_to_offset(self.position)
+    }
+
+    #[inline]
+    /// The lowest valid offset that can be requested.
+    pub fn lower_offset_limit(&self) -> usize {
+        self.initial_offset
+    }
+
+    #[inline]
+    /// The highest valid offset that can be requested.
+    pub fn upper_offset_limit(&self) -> usize {
+        self.initial_offset + self.data.len()
+    }
+
+    #[inline]
+    /// The amount of space left, relative to the [`SegmentMut::current_offset`].
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    #[inline]
+    /// Checks whether or not there is any space left, relative to the
+    /// [`SegmentMut::current_offset`].
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    #[inline]
+    /// Returns `true` if there is more space after the [`SegmentMut::current_offset`].
+    pub fn has_more(&self) -> bool {
+        self.remaining() > 0
+    }
+
+    /// A helper method that validates an offset.
+    ///
+    /// If the offset is valid, then `Ok(())` will be returned. Otherwise, the appropriate
+    /// [`Error`] is returned.
+    pub fn validate_offset(&self, offset: usize, size: usize) -> Result<()> {
+        // We can't just pass the offset along, because it might be too small and cause an overflow.
+        if offset < self.lower_offset_limit() {
+            Err(Error::OffsetTooSmall { offset })
+        } else {
+            self.validate_pos(self.to_pos(offset), size)
+        }
+    }
+
+    /// Sets the [`SegmentMut::current_offset`].
+    pub fn move_to(&mut self, offset: usize) -> Result<()> {
+        self.validate_offset(offset, 0)?;
+        self.position = self.to_pos(offset);
+        Ok(())
+    }
+
+    /// Alters the [`SegmentMut::current_offset`] by the given amount.
+    pub fn move_by(&mut self, num_items: i128) -> Result<()> {
+        self.move_to(offset_by(self.current_offset(), num_items)?)
+    }
+
+    /// Gets a mutable reference to the item at the provided offset without altering the
+    /// [`SegmentMut::current_offset`].
+    pub fn item_mut_at(&mut self, offset: usize) -> Result<&mut I> {
+        self.validate_offset(offset, 1)?;
+        let pos = self.to_pos(offset);
+        Ok(&mut self.data[pos])
+    }
+
+    /// Sets the item at the provided offset without altering the [`SegmentMut::current_offset`].
+    pub fn put_item_at(&mut self, offset: usize, item: I) -> Result<()> {
+        *self.item_mut_at(offset)? = item;
+        Ok(())
+    }
+
+    /// Sets the item at the [`SegmentMut::current_offset`] and then advances it by 1.
+    pub fn put_item(&mut self, item: I) -> Result<()> {
+        self.put_item_at(self.current_offset(), item)?;
+        self.position += 1;
+        Ok(())
+    }
+
+    /// Returns a [`Segment`] that can be used to read the data. The new segment will have the
+    /// same initial offset, current offset, and endidness as this one.
+    pub fn as_segment(&self) -> Segment<'_, I> {
+        Segment::new_full(
+            self.data,
+            self.initial_offset,
+            self.position,
+            self.endidness,
+        )
+    }
+
+    /// Creates a new [`SegmentMut`] from the start offset (inclusive) to the end offset
+    /// (exclusive). The original can't be used until the new one is dropped.
+    pub fn segment_mut(&mut self, start: usize, end: usize) -> Result<SegmentMut<'_, I>> {
+        if end < start {
+            return Err(Error::OffsetTooSmall { offset: end });
+        }
+        self.validate_offset(start, end - start)?;
+        let (start_pos, end_pos) = (self.to_pos(start), self.to_pos(end));
+        Ok(SegmentMut::new_full(
+            &mut self.data[start_pos..end_pos],
+            start,
+            0,
+            self.endidness,
+        ))
+    }
+}
+
+impl<'s, I: Clone> SegmentMut<'s, I> {
+    /// Copies the provided items into the segment, starting at the provided offset. This does not
+    /// alter the [`SegmentMut::current_offset`].
+    pub fn put_items_at(&mut self, offset: usize, items: &[I]) -> Result<()> {
+        self.validate_offset(offset, items.len())?;
+        let pos = self.to_pos(offset);
+        self.data[pos..pos + items.len()].clone_from_slice(items);
+        Ok(())
+    }
+
+    /// Copies the provided items into the segment, starting at the
+    /// [`SegmentMut::current_offset`], and then advances the [`SegmentMut::current_offset`] by the
+    /// number of items.
+    pub fn put_items(&mut self, items: &[I]) -> Result<()> {
+        self.put_items_at(self.current_offset(), items)?;
+        self.position += items.len();
+        Ok(())
+    }
+
+    /// Gets the item at the provided offset without altering the [`SegmentMut::current_offset`].
+    pub fn item_at(&self, offset: usize) -> Result<I> {
+        self.validate_offset(offset, 1)?;
+        Ok(self.data[self.to_pos(offset)].clone())
+    }
+}
+
+impl<'s> SegmentMut<'s, u8> {
+    /// Creates a new [`SegmentMut`] using the provided endidness.
+    ///
+    /// Note: Only available if the [`SegmentMut`]'s I is `u8`.
+    #[inline]
+    pub fn with_endidness(data: &'s mut [u8], endidness: Endidness) -> Self {
+        Self::new_full(data, 0, 0, endidness)
+    }
+
+    /// Creates a new [`SegmentMut`] using the provided endidness and initial offset.
+    ///
+    /// Note: Only available if the [`SegmentMut`]'s I is `u8`.
+    #[inline]
+    pub fn with_offset_and_endidness(
+        data: &'s mut [u8],
+        initial_offset: usize,
+        endidness: Endidness,
+    ) -> Self {
+        Self::new_full(data, initial_offset, 0, endidness)
+    }
+
+    /// Changes the endidness used for any future writes.
+    ///
+    /// Note: Only available if the [`SegmentMut`]'s I is `u8`.
+    #[inline]
+    pub fn change_endidness(&mut self, endidness: Endidness) {
+        self.endidness = endidness
+    }
+}
+
+impl<'s> U8Sink for SegmentMut<'s, u8> {
+    #[inline]
+    fn endidness(&self) -> Endidness {
+        self.endidness
+    }
+
+    #[inline]
+    fn initial_offset(&self) -> usize {
+        self.initial_offset
+    }
+
+    #[inline]
+    fn current_offset(&self) -> usize {
+        self.pos_to_offset(self.position)
+    }
+
+    #[inline]
+    fn move_to(&mut self, offset: usize) -> Result<()> {
+        SegmentMut::move_to(self, offset)
+    }
+
+    #[inline]
+    fn move_by(&mut self, num_bytes: i128) -> Result<()> {
+        SegmentMut::move_by(self, num_bytes)
+    }
+
+    #[inline]
+    fn put_bytes_at(&mut self, offset: usize, bytes: &[u8]) -> Result<()> {
+        self.put_items_at(offset, bytes)
+    }
+}
+
+impl<'s, I> AsRef<[I]> for SegmentMut<'s, I> {
+    #[inline]
+    fn as_ref(&self) -> &[I] {
+        self.data
+    }
+}
+
+impl<'s, I> AsMut<[I]> for SegmentMut<'s, I> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [I] {
+        self.data
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'s> io::Write for SegmentMut<'s, u8> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = buf.len().min(self.remaining());
+        self.put_bytes(&buf[..written])?;
+        Ok(written)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'s> io::Seek for SegmentMut<'s, u8> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let offset = match pos {
+            io::SeekFrom::Start(to) => offset_by(0, to as i128),
+            io::SeekFrom::Current(by) => offset_by(self.current_offset(), by as i128),
+            io::SeekFrom::End(point) => offset_by(self.upper_offset_limit(), point as i128),
+        };
+        offset
+            .and_then(|offset| SegmentMut::move_to(self, offset))
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        Ok(self.current_offset() as u64)
+    }
+}
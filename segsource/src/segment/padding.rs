@@ -0,0 +1,82 @@
+use super::Segment;
+use crate::error::{Error, Result};
+
+/// The number of items from the offset to the next offset that's a multiple of the alignment
+/// (relative to the base). An alignment of zero is treated as an alignment of one.
+pub(crate) fn padding_to_align(offset: usize, alignment: usize, base: usize) -> usize {
+    let alignment = alignment.max(1);
+    if offset >= base {
+        (alignment - (offset - base) % alignment) % alignment
+    } else {
+        (base - offset) % alignment
+    }
+}
+
+/// The number of items from the offset to the end of something that started at the start offset
+/// and is padded to the provided size.
+pub(crate) fn padding_to(offset: usize, start: usize, padded_size: usize) -> Result<usize> {
+    let size = offset.saturating_sub(start);
+    if size > padded_size {
+        Err(Error::ExceedsPadding {
+            size,
+            padded_size,
+            offset: start,
+        })
+    } else {
+        Ok(padded_size - size)
+    }
+}
+
+impl<'s, I> Segment<'s, I> {
+    /// The number of items between the [`Segment::current_offset`] and the next offset that's a
+    /// multiple of the alignment, relative to the provided base offset. An alignment of zero is
+    /// treated as an alignment of one (i.e. no padding is needed).
+    #[inline]
+    pub fn padding_to_align(&self, alignment: usize, base: usize) -> usize {
+        padding_to_align(self.current_offset(), alignment, base)
+    }
+
+    /// Advances the [`Segment::current_offset`] to the next offset that's a multiple of the
+    /// alignment, relative to the [`Segment::initial_offset`]. If the
+    /// [`Segment::current_offset`] is already aligned, then it isn't changed.
+    ///
+    /// ```
+    /// # use segsource::Segment;
+    /// let segment = Segment::with_offset(&[0; 16], 0x102);
+    /// segment.move_by(3).unwrap();
+    /// segment.align(4).unwrap();
+    /// assert_eq!(segment.current_offset(), 0x106);
+    /// ```
+    #[inline]
+    pub fn align(&self, alignment: usize) -> Result<()> {
+        self.move_by(self.padding_to_align(alignment, self.initial_offset) as i128)
+    }
+
+    /// The number of items between the [`Segment::current_offset`] and the end of something that
+    /// started at the start offset and is padded to the provided size (e.g. a fixed-size record).
+    /// If more than that has already been read, then an [`Error::ExceedsPadding`] is returned.
+    #[inline]
+    pub fn padding_to(&self, start: usize, padded_size: usize) -> Result<usize> {
+        padding_to(self.current_offset(), start, padded_size)
+    }
+}
+
+impl<'s> Segment<'s, u8> {
+    /// Advances the [`Segment::current_offset`] past the provided number of padding bytes,
+    /// checking that they're all zero. If they aren't, an [`Error::NonZeroPadding`] (with the
+    /// offset of the first non-zero byte) is returned and the [`Segment::current_offset`] isn't
+    /// changed.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_zero_padding(&self, num_bytes: usize) -> Result<()> {
+        self.next_with_len(|me, pos| {
+            me.validate_pos(pos, num_bytes)?;
+            match me.data[pos..pos + num_bytes].iter().position(|b| *b != 0) {
+                Some(idx) => Err(Error::NonZeroPadding {
+                    offset: me.pos_to_offset(pos + idx),
+                }),
+                None => Ok(((), num_bytes)),
+            }
+        })
+    }
+}
@@ -0,0 +1,207 @@
+use super::Segment;
+use core::{convert::TryFrom, iter::FusedIterator, marker::PhantomData, ops::Range};
+
+/// An item along with the range of offsets it was parsed from.
+type WithRange<T, E> = Result<(T, Range<usize>), E>;
+
+/// When a [`ParseIter`] stops.
+enum Mode<F> {
+    Count(usize),
+    Exhausted,
+    Sentinel(F),
+}
+
+/// An iterator that repeatedly parses items from a [`Segment`] (starting at its
+/// [`Segment::current_offset`]) using their `TryFrom<&Segment>` implementations, which includes
+/// anything that derives `TryFromSegment`. Created by [`Segment::parse_iter`],
+/// [`Segment::parse_n`], and [`Segment::parse_until`].
+///
+/// If an item can't be parsed, its error is returned, the [`Segment::current_offset`] is moved back
+/// to where that item started, and the iterator stops.
+pub struct ParseIter<'a, 's, I, T, F = fn(&T) -> bool> {
+    segment: &'a Segment<'s, I>,
+    mode: Mode<F>,
+    finished: bool,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<'a, 's, I, T, F> ParseIter<'a, 's, I, T, F>
+where
+    T: TryFrom<&'a Segment<'s, I>>,
+    F: FnMut(&T) -> bool,
+{
+    fn new(segment: &'a Segment<'s, I>, mode: Mode<F>) -> Self {
+        Self {
+            segment,
+            mode,
+            finished: false,
+            _item: PhantomData,
+        }
+    }
+
+    /// Changes the iterator so that each item comes with the range of offsets it was parsed from.
+    ///
+    /// ```
+    /// # use segsource::{Endidness, Segment};
+    /// let segment = Segment::with_offset_and_endidness(&[0, 1, 0, 2], 0x10, Endidness::Big);
+    /// let items: Vec<_> = segment
+    ///     .parse_iter::<u16>()
+    ///     .with_offsets()
+    ///     .collect::<Result<_, _>>()
+    ///     .unwrap();
+    /// assert_eq!(items, [(1, 0x10..0x12), (2, 0x12..0x14)]);
+    /// ```
+    #[inline]
+    pub fn with_offsets(self) -> WithOffsets<'a, 's, I, T, F> {
+        WithOffsets(self)
+    }
+
+    fn next_with_offsets(&mut self) -> Option<WithRange<T, T::Error>> {
+        if self.finished {
+            return None;
+        }
+        let segment = self.segment;
+        match &mut self.mode {
+            Mode::Count(0) => {
+                self.finished = true;
+                return None;
+            }
+            Mode::Exhausted if !segment.has_more() => {
+                self.finished = true;
+                return None;
+            }
+            _ => {}
+        }
+        let start = segment.current_offset();
+        let item = match T::try_from(segment) {
+            Ok(item) => item,
+            Err(error) => {
+                self.finished = true;
+                // The start was valid when we got it, so moving back to it can't fail.
+                let _ = segment.move_to(start);
+                return Some(Err(error));
+            }
+        };
+        let end = segment.current_offset();
+        match &mut self.mode {
+            Mode::Count(count) => *count -= 1,
+            Mode::Sentinel(is_sentinel) => {
+                if is_sentinel(&item) {
+                    self.finished = true;
+                    return None;
+                }
+            }
+            Mode::Exhausted => {}
+        }
+        // Otherwise, an item that doesn't take up any space would be parsed forever.
+        if start == end && !matches!(self.mode, Mode::Count(_)) {
+            self.finished = true;
+        }
+        Some(Ok((item, start..end)))
+    }
+}
+
+impl<'a, 's, I, T, F> Iterator for ParseIter<'a, 's, I, T, F>
+where
+    T: TryFrom<&'a Segment<'s, I>>,
+    F: FnMut(&T) -> bool,
+{
+    type Item = Result<T, T::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_offsets()
+            .map(|result| result.map(|(item, _)| item))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.mode {
+            _ if self.finished => (0, Some(0)),
+            Mode::Count(count) => (0, Some(count)),
+            _ => (0, None),
+        }
+    }
+}
+
+impl<'a, 's, I, T, F> FusedIterator for ParseIter<'a, 's, I, T, F>
+where
+    T: TryFrom<&'a Segment<'s, I>>,
+    F: FnMut(&T) -> bool,
+{
+}
+
+/// A [`ParseIter`] whose items come with the range of offsets they were parsed from. Created by
+/// [`ParseIter::with_offsets`].
+pub struct WithOffsets<'a, 's, I, T, F = fn(&T) -> bool>(ParseIter<'a, 's, I, T, F>);
+
+impl<'a, 's, I, T, F> Iterator for WithOffsets<'a, 's, I, T, F>
+where
+    T: TryFrom<&'a Segment<'s, I>>,
+    F: FnMut(&T) -> bool,
+{
+    type Item = WithRange<T, T::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_with_offsets()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, 's, I, T, F> FusedIterator for WithOffsets<'a, 's, I, T, F>
+where
+    T: TryFrom<&'a Segment<'s, I>>,
+    F: FnMut(&T) -> bool,
+{
+}
+
+impl<'s, I> Segment<'s, I> {
+    /// Returns an iterator that parses items until there's no data left. See [`ParseIter`] for
+    /// more details.
+    ///
+    /// ```
+    /// # use segsource::{Endidness, Segment};
+    /// let segment = Segment::with_endidness(&[0, 1, 0, 2, 0, 3], Endidness::Big);
+    /// let items: Result<Vec<u16>, _> = segment.parse_iter().collect();
+    /// assert_eq!(items.unwrap(), [1, 2, 3]);
+    /// assert!(!segment.has_more());
+    /// ```
+    pub fn parse_iter<'a, T>(&'a self) -> ParseIter<'a, 's, I, T>
+    where
+        T: TryFrom<&'a Segment<'s, I>>,
+    {
+        ParseIter::new(self, Mode::Exhausted)
+    }
+
+    /// Returns an iterator that parses the provided number of items. See [`ParseIter`] for more
+    /// details.
+    pub fn parse_n<'a, T>(&'a self, count: usize) -> ParseIter<'a, 's, I, T>
+    where
+        T: TryFrom<&'a Segment<'s, I>>,
+    {
+        ParseIter::new(self, Mode::Count(count))
+    }
+
+    /// Returns an iterator that parses items until one of them is a sentinel (e.g. an all-zero
+    /// entry at the end of a table). The sentinel is consumed, but isn't returned. If the data runs
+    /// out before a sentinel is found, then the error from parsing the next item is returned. See
+    /// [`ParseIter`] for more details.
+    ///
+    /// ```
+    /// # use segsource::Segment;
+    /// let segment = Segment::new(&[3, 2, 1, 0, 9]);
+    /// let items: Result<Vec<u8>, _> = segment.parse_until(|item| *item == 0).collect();
+    /// assert_eq!(items.unwrap(), [3, 2, 1]);
+    /// assert_eq!(segment.current_offset(), 4);
+    /// ```
+    pub fn parse_until<'a, T, F>(&'a self, is_sentinel: F) -> ParseIter<'a, 's, I, T, F>
+    where
+        T: TryFrom<&'a Segment<'s, I>>,
+        F: FnMut(&T) -> bool,
+    {
+        ParseIter::new(self, Mode::Sentinel(is_sentinel))
+    }
+}
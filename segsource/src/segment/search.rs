@@ -0,0 +1,289 @@
+use super::Segment;
+use crate::{error::Result, Endidness};
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+// Searching uses Knuth-Morris-Pratt, which only needs `PartialEq` and is O(n + m). The pattern
+// and haystack are accessed through closures so that the same code can search backwards. Patterns
+// that are a single item skip all of that and just scan for the item.
+
+// Patterns up to this long have their tables built on the stack, so that one-off searches don't
+// need to allocate.
+const STACK_TABLE_LEN: usize = 32;
+
+fn build_table<'a, I, P>(pattern: P, table: &mut [usize])
+where
+    I: PartialEq + 'a,
+    P: Fn(usize) -> &'a I,
+{
+    let mut matched = 0;
+    for i in 1..table.len() {
+        while matched > 0 && pattern(i) != pattern(matched) {
+            matched = table[matched - 1];
+        }
+        if pattern(i) == pattern(matched) {
+            matched += 1;
+        }
+        table[i] = matched;
+    }
+}
+
+/// Builds the table for a pattern that's `len` items long and passes it to the provided function.
+fn with_table<'a, I, P, F, R>(pattern: P, len: usize, f: F) -> R
+where
+    I: PartialEq + 'a,
+    P: Fn(usize) -> &'a I,
+    F: FnOnce(&[usize]) -> R,
+{
+    if len <= STACK_TABLE_LEN {
+        let mut table = [0; STACK_TABLE_LEN];
+        build_table(pattern, &mut table[..len]);
+        f(&table[..len])
+    } else {
+        let mut table = vec![0; len];
+        build_table(pattern, &mut table);
+        f(&table)
+    }
+}
+
+fn find_item<I: PartialEq>(data: &[I], item: &I, start: usize) -> Option<usize> {
+    data.get(start..)?
+        .iter()
+        .position(|i| i == item)
+        .map(|p| p + start)
+}
+
+fn search<'a, I, P, H>(
+    pattern: P,
+    table: &[usize],
+    haystack: H,
+    haystack_len: usize,
+    start: usize,
+) -> Option<usize>
+where
+    I: PartialEq + 'a,
+    P: Fn(usize) -> &'a I,
+    H: Fn(usize) -> &'a I,
+{
+    let len = table.len();
+    if len == 0 {
+        return if start <= haystack_len {
+            Some(start)
+        } else {
+            None
+        };
+    }
+    let mut matched = 0;
+    for i in start..haystack_len {
+        while matched > 0 && haystack(i) != pattern(matched) {
+            matched = table[matched - 1];
+        }
+        if haystack(i) == pattern(matched) {
+            matched += 1;
+        }
+        if matched == len {
+            return Some(i + 1 - len);
+        }
+    }
+    None
+}
+
+/// An iterator over the offsets of every non-overlapping occurrence of a pattern in a
+/// [`Segment`]. Created by [`Segment::find_all`].
+pub struct FindAll<'s, 'p, I> {
+    data: &'s [I],
+    initial_offset: usize,
+    pattern: &'p [I],
+    table: Vec<usize>,
+    pos: usize,
+}
+
+impl<'s, 'p, I: PartialEq> FindAll<'s, 'p, I> {
+    fn new(data: &'s [I], initial_offset: usize, pattern: &'p [I], pos: usize) -> Self {
+        // The table is built once, and then reused for every match.
+        let mut table = Vec::new();
+        if pattern.len() > 1 {
+            table.resize(pattern.len(), 0);
+            build_table(|i| &pattern[i], &mut table);
+        }
+        Self {
+            data,
+            initial_offset,
+            pattern,
+            table,
+            pos,
+        }
+    }
+
+    fn next_pos(&mut self) -> Option<usize> {
+        let (data, pattern) = (self.data, self.pattern);
+        let found = match pattern {
+            [item] => find_item(data, item, self.pos),
+            _ => search(
+                |i| &pattern[i],
+                &self.table,
+                |i| &data[i],
+                data.len(),
+                self.pos,
+            ),
+        }?;
+        // An empty pattern matches everywhere, so we still need to move forward.
+        self.pos = found + pattern.len().max(1);
+        Some(found)
+    }
+}
+
+impl<'s, 'p, I: PartialEq> Iterator for FindAll<'s, 'p, I> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.next_pos().map(|pos| pos + self.initial_offset)
+    }
+}
+
+/// An iterator over the [`Segment`]s between occurrences of a delimiter. Created by
+/// [`Segment::split_on`].
+pub struct SplitOn<'s, 'p, I> {
+    finder: FindAll<'s, 'p, I>,
+    start: usize,
+    endidness: Endidness,
+    finished: bool,
+}
+
+impl<'s, 'p, I: PartialEq> Iterator for SplitOn<'s, 'p, I> {
+    type Item = Segment<'s, I>;
+
+    fn next(&mut self) -> Option<Segment<'s, I>> {
+        if self.finished {
+            return None;
+        }
+        let data = self.finder.data;
+        let start = self.start;
+        let end = match self.finder.next_pos() {
+            Some(pos) => {
+                self.start = pos + self.finder.pattern.len();
+                pos
+            }
+            None => {
+                self.finished = true;
+                data.len()
+            }
+        };
+        Some(Segment::new_full(
+            &data[start..end],
+            self.finder.initial_offset + start,
+            0,
+            self.endidness,
+        ))
+    }
+}
+
+impl<'s, I> Segment<'s, I>
+where
+    I: PartialEq,
+{
+    fn find_from_pos(&self, pos: usize, pattern: &[I]) -> Option<usize> {
+        let data = self.data;
+        let found = match pattern {
+            [item] => find_item(data, item, pos),
+            _ => with_table(
+                |i| &pattern[i],
+                pattern.len(),
+                |table| search(|i| &pattern[i], table, |i| &data[i], data.len(), pos),
+            ),
+        };
+        found.map(|p| self.pos_to_offset(p))
+    }
+
+    /// Finds the first occurrence of the provided pattern at or after the
+    /// [`Segment::current_offset`] and returns its offset. This does not alter the
+    /// [`Segment::current_offset`].
+    ///
+    /// ```
+    /// # use segsource::Segment;
+    /// let segment = Segment::with_offset(b"..\xff\xd8..\xff\xd8", 0x10);
+    /// assert_eq!(segment.find(b"\xff\xd8"), Some(0x12));
+    /// segment.move_to(0x13).unwrap();
+    /// assert_eq!(segment.find(b"\xff\xd8"), Some(0x16));
+    /// assert_eq!(segment.find(b"\xff\xd9"), None);
+    /// ```
+    #[inline]
+    pub fn find(&self, pattern: &[I]) -> Option<usize> {
+        self.find_from_pos(self.get_pos(), pattern)
+    }
+
+    /// Finds the first occurrence of the provided pattern at or after the provided offset and
+    /// returns its offset. This does not alter the [`Segment::current_offset`].
+    pub fn find_from(&self, offset: usize, pattern: &[I]) -> Result<Option<usize>> {
+        self.validate_offset(offset, 0)?;
+        Ok(self.find_from_pos(self.to_pos(offset), pattern))
+    }
+
+    /// Finds the last occurrence of the provided pattern at or after the
+    /// [`Segment::current_offset`] and returns its offset. This does not alter the
+    /// [`Segment::current_offset`].
+    pub fn rfind(&self, pattern: &[I]) -> Option<usize> {
+        let pos = self.get_pos();
+        let data = &self.data[pos.min(self.size)..];
+        let (data_len, len) = (data.len(), pattern.len());
+        if let [item] = pattern {
+            return data
+                .iter()
+                .rposition(|i| i == item)
+                .map(|p| self.pos_to_offset(pos + p));
+        }
+        // Search the reversed data for the reversed pattern.
+        with_table(
+            |i| &pattern[len - 1 - i],
+            len,
+            |table| {
+                search(
+                    |i| &pattern[len - 1 - i],
+                    table,
+                    |i| &data[data_len - 1 - i],
+                    data_len,
+                    0,
+                )
+            },
+        )
+        .map(|p| self.pos_to_offset(pos + data_len - p - len))
+    }
+
+    /// Returns an iterator over the offsets of every non-overlapping occurrence of the provided
+    /// pattern at or after the [`Segment::current_offset`]. This does not alter the
+    /// [`Segment::current_offset`].
+    #[inline]
+    pub fn find_all<'p>(&self, pattern: &'p [I]) -> FindAll<'s, 'p, I> {
+        FindAll::new(self.data, self.initial_offset, pattern, self.get_pos())
+    }
+
+    /// Returns an iterator over the [`Segment`]s between each occurrence of the provided
+    /// delimiter, starting at the [`Segment::current_offset`] and ending at the end of this
+    /// segment. The delimiters aren't included, and each [`Segment`]'s initial offset is the
+    /// offset it had in this one. Like `str::split`, adjacent delimiters produce empty segments,
+    /// and an empty delimiter matches before and after every item (so the segments are an empty
+    /// one, each item on its own, and then another empty one). This does not alter the
+    /// [`Segment::current_offset`].
+    ///
+    /// ```
+    /// # use segsource::Segment;
+    /// let segment = Segment::new(b"a,bc,,d");
+    /// let pieces: Vec<_> = segment
+    ///     .split_on(b",")
+    ///     .map(|piece| (piece.initial_offset(), piece.as_ref().to_vec()))
+    ///     .collect();
+    /// assert_eq!(
+    ///     pieces,
+    ///     vec![(0, b"a".to_vec()), (2, b"bc".to_vec()), (5, vec![]), (6, b"d".to_vec())]
+    /// );
+    /// ```
+    pub fn split_on<'p>(&self, delimiter: &'p [I]) -> SplitOn<'s, 'p, I> {
+        let start = self.get_pos().min(self.size);
+        SplitOn {
+            finder: FindAll::new(self.data, self.initial_offset, delimiter, start),
+            start,
+            endidness: self.endidness.load(),
+            finished: false,
+        }
+    }
+}
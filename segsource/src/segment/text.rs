@@ -0,0 +1,357 @@
+use super::Segment;
+use crate::{
+    error::{Error, Result},
+    marker::{Integer, Numeric},
+};
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use core::{char, convert::TryInto, str};
+
+impl<'s> Segment<'s, u8> {
+    fn bytes_at_pos(&self, pos: usize, len: usize) -> Result<&'s [u8]> {
+        self.validate_pos(pos, len)?;
+        let data: &'s [u8] = self.data;
+        Ok(&data[pos..pos + len])
+    }
+
+    fn to_str(&self, bytes: &'s [u8], pos: usize) -> Result<&'s str> {
+        str::from_utf8(bytes).map_err(|e| Error::InvalidUtf8 {
+            offset: self.pos_to_offset(pos + e.valid_up_to()),
+        })
+    }
+
+    fn with_str(&self, (bytes, len): (&'s [u8], usize), pos: usize) -> Result<(&'s str, usize)> {
+        Ok((self.to_str(bytes, pos)?, len))
+    }
+
+    fn cstr_at_pos(&self, pos: usize) -> Result<(&'s [u8], usize)> {
+        self.validate_pos(pos, 1)?;
+        let data: &'s [u8] = self.data;
+        match data[pos..].iter().position(|b| *b == 0) {
+            Some(len) => Ok((&data[pos..pos + len], len + 1)),
+            None => {
+                let left = self.calc_remaining(pos);
+                Err(Error::NotEnoughData {
+                    requested: left + 1,
+                    left,
+                    offset: self.pos_to_offset(pos),
+                })
+            }
+        }
+    }
+
+    fn prefixed_at_pos<N>(&self, pos: usize) -> Result<(&'s [u8], usize)>
+    where
+        N: Integer + TryInto<usize>,
+    {
+        self.validate_pos(pos, N::WIDTH)?;
+        // If the length doesn't fit in a usize (or is negative), there's definitely not enough
+        // data for it.
+        let len = self.num_at_pos::<N>(pos)?.try_into().unwrap_or(usize::MAX);
+        Ok((self.bytes_at_pos(pos + N::WIDTH, len)?, N::WIDTH + len))
+    }
+
+    fn padded_at_pos(&self, pos: usize, width: usize, pad: u8) -> Result<(&'s [u8], usize)> {
+        let mut bytes = self.bytes_at_pos(pos, width)?;
+        if let Some(nul) = bytes.iter().position(|b| *b == 0) {
+            bytes = &bytes[..nul];
+        }
+        while let Some((last, rest)) = bytes.split_last() {
+            if *last != pad {
+                break;
+            }
+            bytes = rest;
+        }
+        Ok((bytes, width))
+    }
+
+    fn utf16_at_pos(&self, pos: usize, num_units: usize) -> Result<(String, usize)> {
+        let bytes = self.bytes_at_pos(pos, num_units.saturating_mul(2))?;
+        let units = bytes
+            .chunks_exact(2)
+            .map(|unit| u16::with_endidness(unit, self.endidness.load()));
+        let mut string = String::with_capacity(num_units);
+        let mut unit_idx = 0;
+        for c in char::decode_utf16(units) {
+            match c {
+                Ok(c) => {
+                    string.push(c);
+                    unit_idx += c.len_utf16();
+                }
+                Err(_) => {
+                    return Err(Error::InvalidUtf16 {
+                        offset: self.pos_to_offset(pos + unit_idx * 2),
+                    })
+                }
+            }
+        }
+        Ok((string, num_units * 2))
+    }
+
+    fn utf16_cstr_at_pos(&self, pos: usize) -> Result<(String, usize)> {
+        self.validate_pos(pos, 2)?;
+        match self.data[pos..]
+            .chunks_exact(2)
+            .position(|unit| unit == [0, 0])
+        {
+            Some(num_units) => {
+                let (string, len) = self.utf16_at_pos(pos, num_units)?;
+                Ok((string, len + 2))
+            }
+            None => {
+                let left = self.calc_remaining(pos);
+                Err(Error::NotEnoughData {
+                    requested: left - left % 2 + 2,
+                    left,
+                    offset: self.pos_to_offset(pos),
+                })
+            }
+        }
+    }
+
+    /// Gets the bytes of a NUL-terminated string (not including the NUL) at the given offset
+    /// without altering the [`Segment::current_offset`].
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn cstr_bytes_at(&self, offset: usize) -> Result<&'s [u8]> {
+        self.validate_offset(offset, 0)?;
+        Ok(self.cstr_at_pos(self.to_pos(offset))?.0)
+    }
+
+    /// See the documentation for [`Segment::cstr_bytes_at`]. This uses the
+    /// [`Segment::current_offset`] but does not alter it.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn peek_cstr_bytes(&self) -> Result<&'s [u8]> {
+        self.cstr_bytes_at(self.current_offset())
+    }
+
+    /// Gets the bytes of a NUL-terminated string (not including the NUL) starting at the
+    /// [`Segment::current_offset`] and then advances the [`Segment::current_offset`] past the NUL.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_cstr_bytes(&self) -> Result<&'s [u8]> {
+        self.next_with_len(Self::cstr_at_pos)
+    }
+
+    /// Gets a NUL-terminated UTF-8 string (not including the NUL) at the given offset without
+    /// altering the [`Segment::current_offset`].
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn cstr_at(&self, offset: usize) -> Result<&'s str> {
+        self.validate_offset(offset, 0)?;
+        let pos = self.to_pos(offset);
+        self.to_str(self.cstr_at_pos(pos)?.0, pos)
+    }
+
+    /// See the documentation for [`Segment::cstr_at`]. This uses the [`Segment::current_offset`]
+    /// but does not alter it.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn peek_cstr(&self) -> Result<&'s str> {
+        self.cstr_at(self.current_offset())
+    }
+
+    /// Gets a NUL-terminated UTF-8 string (not including the NUL) starting at the
+    /// [`Segment::current_offset`] and then advances the [`Segment::current_offset`] past the NUL.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_cstr(&self) -> Result<&'s str> {
+        self.next_with_len(|me, pos| me.with_str(me.cstr_at_pos(pos)?, pos))
+    }
+
+    /// Gets a UTF-8 string that's `len` bytes long at the given offset without altering the
+    /// [`Segment::current_offset`].
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn str_at(&self, offset: usize, len: usize) -> Result<&'s str> {
+        self.validate_offset(offset, 0)?;
+        let pos = self.to_pos(offset);
+        self.to_str(self.bytes_at_pos(pos, len)?, pos)
+    }
+
+    /// See the documentation for [`Segment::str_at`]. This uses the [`Segment::current_offset`]
+    /// but does not alter it.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn peek_str(&self, len: usize) -> Result<&'s str> {
+        self.str_at(self.current_offset(), len)
+    }
+
+    /// Gets a UTF-8 string that's `len` bytes long starting at the [`Segment::current_offset`] and
+    /// then advances the [`Segment::current_offset`] by `len`.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_str(&self, len: usize) -> Result<&'s str> {
+        self.next_with_len(|me, pos| Ok((me.to_str(me.bytes_at_pos(pos, len)?, pos)?, len)))
+    }
+
+    /// Gets a run of bytes that's prefixed by its length at the given offset without altering the
+    /// [`Segment::current_offset`]. The length is an integer of type N (e.g. `u8`, `u16`, or
+    /// `u32`), read using the [`Segment`]'s endidness, and does not include itself.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn prefixed_bytes_at<N>(&self, offset: usize) -> Result<&'s [u8]>
+    where
+        N: Integer + TryInto<usize>,
+    {
+        self.validate_offset(offset, 0)?;
+        Ok(self.prefixed_at_pos::<N>(self.to_pos(offset))?.0)
+    }
+
+    /// See the documentation for [`Segment::prefixed_bytes_at`]. This uses the
+    /// [`Segment::current_offset`] but does not alter it.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn peek_prefixed_bytes<N>(&self) -> Result<&'s [u8]>
+    where
+        N: Integer + TryInto<usize>,
+    {
+        self.prefixed_bytes_at::<N>(self.current_offset())
+    }
+
+    /// Gets a run of bytes that's prefixed by its length (see
+    /// [`Segment::prefixed_bytes_at`]) starting at the [`Segment::current_offset`] and then
+    /// advances the [`Segment::current_offset`] past it.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_prefixed_bytes<N>(&self) -> Result<&'s [u8]>
+    where
+        N: Integer + TryInto<usize>,
+    {
+        self.next_with_len(Self::prefixed_at_pos::<N>)
+    }
+
+    /// Gets a UTF-8 string that's prefixed by its length in bytes at the given offset without
+    /// altering the [`Segment::current_offset`]. The length is an integer of type N (e.g. `u8`,
+    /// `u16`, or `u32`), read using the [`Segment`]'s endidness, and does not include itself.
+    ///
+    /// ```
+    /// # use segsource::{Endidness, Segment};
+    /// let segment = Segment::with_endidness(b"\x00\x05hello", Endidness::Big);
+    /// assert_eq!(segment.prefixed_str_at::<u16>(0).unwrap(), "hello");
+    /// ```
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn prefixed_str_at<N>(&self, offset: usize) -> Result<&'s str>
+    where
+        N: Integer + TryInto<usize>,
+    {
+        self.validate_offset(offset, 0)?;
+        let pos = self.to_pos(offset);
+        self.to_str(self.prefixed_at_pos::<N>(pos)?.0, pos + N::WIDTH)
+    }
+
+    /// See the documentation for [`Segment::prefixed_str_at`]. This uses the
+    /// [`Segment::current_offset`] but does not alter it.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn peek_prefixed_str<N>(&self) -> Result<&'s str>
+    where
+        N: Integer + TryInto<usize>,
+    {
+        self.prefixed_str_at::<N>(self.current_offset())
+    }
+
+    /// Gets a UTF-8 string that's prefixed by its length (see [`Segment::prefixed_str_at`])
+    /// starting at the [`Segment::current_offset`] and then advances the
+    /// [`Segment::current_offset`] past it.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_prefixed_str<N>(&self) -> Result<&'s str>
+    where
+        N: Integer + TryInto<usize>,
+    {
+        self.next_with_len(|me, pos| me.with_str(me.prefixed_at_pos::<N>(pos)?, pos + N::WIDTH))
+    }
+
+    /// Gets a UTF-8 string from a fixed-width field that's `width` bytes long at the given offset
+    /// without altering the [`Segment::current_offset`]. The string ends at the first NUL (if
+    /// there is one), and any trailing `pad` bytes are removed (so a `pad` of `b' '` handles
+    /// space-padded fields, and a `pad` of `0` handles NUL-padded ones).
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn padded_str_at(&self, offset: usize, width: usize, pad: u8) -> Result<&'s str> {
+        self.validate_offset(offset, 0)?;
+        let pos = self.to_pos(offset);
+        self.to_str(self.padded_at_pos(pos, width, pad)?.0, pos)
+    }
+
+    /// See the documentation for [`Segment::padded_str_at`]. This uses the
+    /// [`Segment::current_offset`] but does not alter it.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn peek_padded_str(&self, width: usize, pad: u8) -> Result<&'s str> {
+        self.padded_str_at(self.current_offset(), width, pad)
+    }
+
+    /// Gets a UTF-8 string from a fixed-width field (see [`Segment::padded_str_at`]) starting at
+    /// the [`Segment::current_offset`] and then advances the [`Segment::current_offset`] by
+    /// `width`.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_padded_str(&self, width: usize, pad: u8) -> Result<&'s str> {
+        self.next_with_len(|me, pos| me.with_str(me.padded_at_pos(pos, width, pad)?, pos))
+    }
+
+    /// Decodes a UTF-16 string that's `num_units` code units (i.e. `num_units * 2` bytes) long at
+    /// the given offset without altering the [`Segment::current_offset`]. The code units are
+    /// read using the [`Segment`]'s endidness.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn utf16_at(&self, offset: usize, num_units: usize) -> Result<String> {
+        self.validate_offset(offset, 0)?;
+        Ok(self.utf16_at_pos(self.to_pos(offset), num_units)?.0)
+    }
+
+    /// See the documentation for [`Segment::utf16_at`]. This uses the
+    /// [`Segment::current_offset`] but does not alter it.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn peek_utf16(&self, num_units: usize) -> Result<String> {
+        self.utf16_at(self.current_offset(), num_units)
+    }
+
+    /// Decodes a UTF-16 string that's `num_units` code units long (see [`Segment::utf16_at`])
+    /// starting at the [`Segment::current_offset`] and then advances the
+    /// [`Segment::current_offset`] by `num_units * 2`.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_utf16(&self, num_units: usize) -> Result<String> {
+        self.next_with_len(|me, pos| me.utf16_at_pos(pos, num_units))
+    }
+
+    /// Decodes a UTF-16 string that's terminated by a NUL code unit (which isn't included) at the
+    /// given offset without altering the [`Segment::current_offset`]. The code units are read
+    /// using the [`Segment`]'s endidness.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn utf16_cstr_at(&self, offset: usize) -> Result<String> {
+        self.validate_offset(offset, 0)?;
+        Ok(self.utf16_cstr_at_pos(self.to_pos(offset))?.0)
+    }
+
+    /// See the documentation for [`Segment::utf16_cstr_at`]. This uses the
+    /// [`Segment::current_offset`] but does not alter it.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    #[inline]
+    pub fn peek_utf16_cstr(&self) -> Result<String> {
+        self.utf16_cstr_at(self.current_offset())
+    }
+
+    /// Decodes a NUL-terminated UTF-16 string (see [`Segment::utf16_cstr_at`]) starting at the
+    /// [`Segment::current_offset`] and then advances the [`Segment::current_offset`] past the NUL.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_utf16_cstr(&self) -> Result<String> {
+        self.next_with_len(Self::utf16_cstr_at_pos)
+    }
+}
@@ -0,0 +1,131 @@
+use super::Segment;
+#[cfg(feature = "derive")]
+use crate::error::Error;
+use crate::{error::Result, marker::Numeric};
+use core::convert::TryFrom;
+
+/// Something that always takes up the same number of bytes.
+///
+/// This is implemented for all of the numeric primitives, `u8` arrays, and anything that derives
+/// `SegmentView`. Deriving `SegmentView` on a struct generates a view type (named after the struct
+/// with `View` appended) that wraps a [`Segment`] and has an accessor for each field. Fields are
+/// read at fixed offsets using the segment's endidness every time their accessor is called, so
+/// creating a view never copies or allocates.
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// # use segsource::{Endidness, FixedLayout, Segment, SegmentView};
+/// #[derive(SegmentView)]
+/// struct Entry {
+///     id: u32,
+///     name: [u8; 4],
+///     size: u16,
+/// }
+///
+/// let data = b"\x00\x00\x00\x01abcd\x01\x00\x00\x00\x00\x02efgh\x02\x00";
+/// let segment = Segment::with_endidness(data, Endidness::Big);
+/// assert_eq!(Entry::LAYOUT_SIZE, 10);
+/// let first = segment.next_view::<Entry>().unwrap();
+/// let second = segment.next_view::<Entry>().unwrap();
+/// assert_eq!(first.id(), 1);
+/// assert_eq!(second.name(), b"efgh");
+/// assert_eq!(second.size(), 0x0200);
+/// ```
+pub trait FixedLayout {
+    /// The number of bytes the item takes up.
+    const LAYOUT_SIZE: usize;
+}
+
+/// Something with a [`FixedLayout`] that can be lazily read from a [`Segment`] without copying.
+///
+/// Numeric primitives are their own views (they're read when the view is created), `u8` arrays
+/// are viewed as references to the segment's data, and anything that derives `SegmentView` is
+/// viewed via its generated view type.
+pub trait ViewField<'s>: FixedLayout {
+    /// The type used to view the item.
+    type View;
+
+    /// Creates a view from a [`Segment`] that's exactly [`FixedLayout::LAYOUT_SIZE`] bytes long.
+    fn view(segment: Segment<'s, u8>) -> Self::View;
+}
+
+macro_rules! impl_numeric_view {
+    ($($type:ty),+) => {
+        $(
+            impl FixedLayout for $type {
+                const LAYOUT_SIZE: usize = <$type as Numeric>::WIDTH;
+            }
+
+            impl<'s> ViewField<'s> for $type {
+                type View = $type;
+
+                #[inline]
+                fn view(segment: Segment<'s, u8>) -> $type {
+                    <$type>::with_endidness(segment.data, segment.endidness.load())
+                }
+            }
+        )+
+    };
+}
+
+impl_numeric_view! { u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64 }
+
+impl<const N: usize> FixedLayout for [u8; N] {
+    const LAYOUT_SIZE: usize = N;
+}
+
+impl<'s, const N: usize> ViewField<'s> for [u8; N] {
+    type View = &'s [u8; N];
+
+    #[inline]
+    fn view(segment: Segment<'s, u8>) -> &'s [u8; N] {
+        // The segment is always exactly N bytes long.
+        <&[u8; N]>::try_from(segment.data).unwrap()
+    }
+}
+
+impl<'s> Segment<'s, u8> {
+    /// Creates a view of the item at the provided position. Panics if the item doesn't fit.
+    pub(crate) fn view_at_pos<T: ViewField<'s>>(&self, pos: usize) -> T::View {
+        T::view(Self::new_full(
+            &self.data[pos..pos + T::LAYOUT_SIZE],
+            self.pos_to_offset(pos),
+            0,
+            self.endidness.load(),
+        ))
+    }
+
+    /// Creates a view of the item with a [`FixedLayout`] at the given offset without altering the
+    /// [`Segment::current_offset`]. Nothing is read until the view's accessors are called.
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn view_at<T: ViewField<'s>>(&self, offset: usize) -> Result<T::View> {
+        self.validate_offset(offset, T::LAYOUT_SIZE)?;
+        Ok(self.view_at_pos::<T>(self.to_pos(offset)))
+    }
+
+    /// Creates a view of the item with a [`FixedLayout`] at the [`Segment::current_offset`] and
+    /// then advances the [`Segment::current_offset`] by its [`FixedLayout::LAYOUT_SIZE`].
+    ///
+    /// Note: Only available if the [`Segment`]'s I is `u8`.
+    pub fn next_view<T: ViewField<'s>>(&self) -> Result<T::View> {
+        self.next_with_len(|me, pos| {
+            me.validate_pos(pos, T::LAYOUT_SIZE)?;
+            Ok((me.view_at_pos::<T>(pos), T::LAYOUT_SIZE))
+        })
+    }
+
+    /// Checks that the segment is big enough to hold an item with the provided layout size.
+    #[cfg(feature = "derive")]
+    pub(crate) fn validate_layout_size(&self, layout_size: usize) -> Result<()> {
+        if self.size < layout_size {
+            Err(Error::NotEnoughData {
+                requested: layout_size,
+                left: self.size,
+                offset: self.initial_offset,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,529 @@
+use crate::{
+    error::{Error, Result},
+    marker::{Float, Integer, Numeric},
+    segment::padding,
+    Endidness,
+};
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+use core::convert::TryFrom;
+
+mod vec_sink;
+pub use vec_sink::VecSink;
+
+/// The offset that's the provided number of bytes away from the offset. Offsets that would be
+/// negative (or too large for a `usize`) are errors instead of wrapping around.
+pub(crate) fn offset_by(offset: usize, num_bytes: i128) -> Result<usize> {
+    match (offset as i128).checked_add(num_bytes) {
+        Some(new_offset) if new_offset < 0 => Err(Error::OffsetTooSmall { offset: 0 }),
+        Some(new_offset) => {
+            usize::try_from(new_offset).map_err(|_| Error::OffsetTooLarge { offset: usize::MAX })
+        }
+        None if num_bytes < 0 => Err(Error::OffsetTooSmall { offset: 0 }),
+        None => Err(Error::OffsetTooLarge { offset: usize::MAX }),
+    }
+}
+
+/// Encodes the provided string as UTF-16 using the provided endidness, optionally followed by a
+/// NUL code unit.
+fn utf16_bytes(value: &str, endidness: Endidness, nul_terminated: bool) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity((value.len() + 1) * 2);
+    let nul = if nul_terminated { Some(0) } else { None };
+    for unit in value.encode_utf16().chain(nul) {
+        let mut buf = [0; 2];
+        unit.write_with_endidness(&mut buf, endidness);
+        bytes.extend_from_slice(&buf);
+    }
+    bytes
+}
+
+macro_rules! make_put_method {
+    ($type:ty, $name:ident, $method:ident, $($doc:literal),+) => {
+        $(#[doc = $doc])+
+        #[inline]
+        fn $name(&mut self, value: $type) -> Result<()> {
+            self.$method::<$type>(value)
+        }
+    };
+}
+macro_rules! make_put_method_with_offset {
+    ($type:ty, $name:ident, $method:ident, $($doc:literal),+) => {
+        $(#[doc = $doc])+
+        #[inline]
+        fn $name(&mut self, offset: usize, value: $type) -> Result<()> {
+            self.$method::<$type>(offset, value)
+        }
+    };
+}
+
+macro_rules! make_put_var_int_methods {
+    ($type:ty, $encode:ident, $at_name:ident, $name:ident, $desc:literal) => {
+        #[doc = $desc]
+        #[doc = "at the given offset without altering the [`U8Sink::current_offset`]."]
+        fn $at_name(&mut self, offset: usize, value: $type) -> Result<()> {
+            let mut buf = [0; 10];
+            let len = $encode(value, &mut buf);
+            self.put_bytes_at(offset, &buf[..len])
+        }
+
+        #[doc = $desc]
+        #[doc = "at the [`U8Sink::current_offset`] and then advances the"]
+        #[doc = "[`U8Sink::current_offset`] by the number of bytes used."]
+        fn $name(&mut self, value: $type) -> Result<()> {
+            let mut buf = [0; 10];
+            let len = $encode(value, &mut buf);
+            self.put_bytes(&buf[..len])
+        }
+    };
+}
+
+fn encode_leb128(mut value: u64, signed: bool, buf: &mut [u8; 10]) -> usize {
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        let done = if signed {
+            value = ((value as i64) >> 7) as u64;
+            (value == 0 && byte & 0x40 == 0) || (value == u64::MAX && byte & 0x40 != 0)
+        } else {
+            value >>= 7;
+            value == 0
+        };
+        if done {
+            buf[len] = byte;
+            return len + 1;
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+}
+
+#[inline]
+fn encode_uleb128(value: u64, buf: &mut [u8; 10]) -> usize {
+    encode_leb128(value, false, buf)
+}
+
+#[inline]
+fn encode_sleb128(value: i64, buf: &mut [u8; 10]) -> usize {
+    encode_leb128(value as u64, true, buf)
+}
+
+#[inline]
+fn encode_zigzag(value: i64, buf: &mut [u8; 10]) -> usize {
+    encode_leb128(((value << 1) ^ (value >> 63)) as u64, false, buf)
+}
+
+fn encode_vlq(value: u64, buf: &mut [u8; 10]) -> usize {
+    let mut len = 1;
+    while len < 10 && value >> (7 * len) != 0 {
+        len += 1;
+    }
+    for (i, byte) in buf[..len].iter_mut().enumerate() {
+        *byte = ((value >> (7 * (len - i - 1))) & 0x7f) as u8;
+        if i + 1 < len {
+            *byte |= 0x80;
+        }
+    }
+    len
+}
+
+/// Sinks are the writable counterpart to [`crate::Segment`]s: they have an initial offset, a
+/// cursor, and an [`Endidness`], and binary data can be written to them. The following sinks are
+/// included with segsource:
+///
+/// 1. [`crate::SegmentMut`]: A sink that writes into a mutable slice. Because the slice can't
+///    grow, any write that doesn't fit will return an error.
+/// 2. [`VecSink`]: A sink that stores its data in a `Vec`, growing as needed. It can be turned into
+///    a [`crate::VecSource`] once you're done writing.
+///
+/// Like with [`crate::Segment`]s, all methods use offsets instead of indexes.
+pub trait U8Sink: Sized {
+    /// The endidness used when writing numbers.
+    fn endidness(&self) -> Endidness;
+
+    /// The initial offset of the sink.
+    fn initial_offset(&self) -> usize;
+
+    /// The current offset of the sink's cursor.
+    fn current_offset(&self) -> usize;
+
+    /// Sets the sink's [`U8Sink::current_offset`].
+    fn move_to(&mut self, offset: usize) -> Result<()>;
+
+    /// Alters the [`U8Sink::current_offset`] by the given amount.
+    fn move_by(&mut self, num_bytes: i128) -> Result<()> {
+        self.move_to(offset_by(self.current_offset(), num_bytes)?)
+    }
+
+    /// Writes all of the provided bytes starting at the given offset without altering the
+    /// [`U8Sink::current_offset`].
+    fn put_bytes_at(&mut self, offset: usize, bytes: &[u8]) -> Result<()>;
+
+    /// Writes all of the provided bytes starting at the [`U8Sink::current_offset`] and then
+    /// advances the [`U8Sink::current_offset`] by the number of bytes written.
+    fn put_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        self.put_bytes_at(self.current_offset(), bytes)?;
+        self.move_by(bytes.len() as i128)
+    }
+
+    /// Writes the provided number of zero bytes (e.g. padding) starting at the
+    /// [`U8Sink::current_offset`] and then advances the [`U8Sink::current_offset`] past them.
+    fn put_zeros(&mut self, num_bytes: usize) -> Result<()> {
+        // Written in chunks so that large amounts of padding don't need an allocation.
+        let zeros = [0; 64];
+        let mut left = num_bytes;
+        while left > 0 {
+            let len = left.min(zeros.len());
+            self.put_bytes(&zeros[..len])?;
+            left -= len;
+        }
+        Ok(())
+    }
+
+    /// The number of bytes between the [`U8Sink::current_offset`] and the next offset that's a
+    /// multiple of the alignment, relative to the provided base offset. An alignment of zero is
+    /// treated as an alignment of one.
+    #[inline]
+    fn padding_to_align(&self, alignment: usize, base: usize) -> usize {
+        padding::padding_to_align(self.current_offset(), alignment, base)
+    }
+
+    /// Writes zeros until the [`U8Sink::current_offset`] is a multiple of the alignment, relative
+    /// to the [`U8Sink::initial_offset`].
+    #[inline]
+    fn align(&mut self, alignment: usize) -> Result<()> {
+        self.put_zeros(self.padding_to_align(alignment, self.initial_offset()))
+    }
+
+    /// The number of bytes between the [`U8Sink::current_offset`] and the end of something that
+    /// started at the start offset and is padded to the provided size. If more than that has
+    /// already been written, then an [`Error::ExceedsPadding`] is returned.
+    #[inline]
+    fn padding_to(&self, start: usize, padded_size: usize) -> Result<usize> {
+        padding::padding_to(self.current_offset(), start, padded_size)
+    }
+
+    /// Writes the provided number at the given offset using the provided endidness. This does not
+    /// alter the [`U8Sink::current_offset`].
+    fn put_num_at_with_endidness<N: Numeric>(
+        &mut self,
+        offset: usize,
+        value: N,
+        endidness: Endidness,
+    ) -> Result<()> {
+        // 16 bytes is enough to hold the largest number we support (i.e. a u128).
+        let mut buf = [0; 16];
+        value.write_with_endidness(&mut buf[..N::WIDTH], endidness);
+        self.put_bytes_at(offset, &buf[..N::WIDTH])
+    }
+
+    /// Writes an integer of the provided type (e.g. `u8`, `i8`, `u16`, `i16`, etcetera) at the
+    /// given offset without altering the [`U8Sink::current_offset`]. In most cases, you should use
+    /// methods like [`U8Sink::put_u16_at`] instead.
+    #[inline]
+    fn put_int_at<N: Integer>(&mut self, offset: usize, value: N) -> Result<()> {
+        self.put_num_at_with_endidness(offset, value, self.endidness())
+    }
+
+    make_put_method_with_offset! {u8, put_u8_at, put_int_at,
+    "See the documentation for [`U8Sink::put_int_at`]."}
+    make_put_method_with_offset! {u16, put_u16_at, put_int_at,
+    "See the documentation for [`U8Sink::put_int_at`]."}
+    make_put_method_with_offset! {u32, put_u32_at, put_int_at,
+    "See the documentation for [`U8Sink::put_int_at`]."}
+    make_put_method_with_offset! {u64, put_u64_at, put_int_at,
+    "See the documentation for [`U8Sink::put_int_at`]."}
+    make_put_method_with_offset! {u128, put_u128_at, put_int_at,
+    "See the documentation for [`U8Sink::put_int_at`]."}
+    make_put_method_with_offset! {i8, put_i8_at, put_int_at,
+    "See the documentation for [`U8Sink::put_int_at`]."}
+    make_put_method_with_offset! {i16, put_i16_at, put_int_at,
+    "See the documentation for [`U8Sink::put_int_at`]."}
+    make_put_method_with_offset! {i32, put_i32_at, put_int_at,
+    "See the documentation for [`U8Sink::put_int_at`]."}
+    make_put_method_with_offset! {i64, put_i64_at, put_int_at,
+    "See the documentation for [`U8Sink::put_int_at`]."}
+    make_put_method_with_offset! {i128, put_i128_at, put_int_at,
+    "See the documentation for [`U8Sink::put_int_at`]."}
+
+    /// Writes an integer of the provided type (e.g. `u8`, `i8`, `u16`, `i16`, etcetera) at the
+    /// [`U8Sink::current_offset`] and then advances the [`U8Sink::current_offset`] by n, where n is
+    /// the size of the integer in bytes. In most cases, you should use methods like
+    /// [`U8Sink::put_u16`] instead.
+    fn put_int<N: Integer>(&mut self, value: N) -> Result<()> {
+        self.put_int_at(self.current_offset(), value)?;
+        self.move_by(N::WIDTH as i128)
+    }
+
+    make_put_method! {u8, put_u8, put_int,
+    "See the documentation for [`U8Sink::put_int`]."}
+    make_put_method! {u16, put_u16, put_int,
+    "See the documentation for [`U8Sink::put_int`]."}
+    make_put_method! {u32, put_u32, put_int,
+    "See the documentation for [`U8Sink::put_int`]."}
+    make_put_method! {u64, put_u64, put_int,
+    "See the documentation for [`U8Sink::put_int`]."}
+    make_put_method! {u128, put_u128, put_int,
+    "See the documentation for [`U8Sink::put_int`]."}
+    make_put_method! {i8, put_i8, put_int,
+    "See the documentation for [`U8Sink::put_int`]."}
+    make_put_method! {i16, put_i16, put_int,
+    "See the documentation for [`U8Sink::put_int`]."}
+    make_put_method! {i32, put_i32, put_int,
+    "See the documentation for [`U8Sink::put_int`]."}
+    make_put_method! {i64, put_i64, put_int,
+    "See the documentation for [`U8Sink::put_int`]."}
+    make_put_method! {i128, put_i128, put_int,
+    "See the documentation for [`U8Sink::put_int`]."}
+
+    /// Writes a floating point number of the provided type (i.e. `f32` or `f64`) at the given
+    /// offset without altering the [`U8Sink::current_offset`]. In most cases, you should use
+    /// methods like [`U8Sink::put_f32_at`] instead.
+    #[inline]
+    fn put_float_at<N: Float>(&mut self, offset: usize, value: N) -> Result<()> {
+        self.put_num_at_with_endidness(offset, value, self.endidness())
+    }
+
+    make_put_method_with_offset! {f32, put_f32_at, put_float_at,
+    "See the documentation for [`U8Sink::put_float_at`]."}
+    make_put_method_with_offset! {f64, put_f64_at, put_float_at,
+    "See the documentation for [`U8Sink::put_float_at`]."}
+
+    /// Writes a floating point number of the provided type (i.e. `f32` or `f64`) at the
+    /// [`U8Sink::current_offset`] and then advances the [`U8Sink::current_offset`] by n, where n is
+    /// the size of the number in bytes. In most cases, you should use methods like
+    /// [`U8Sink::put_f32`] instead.
+    fn put_float<N: Float>(&mut self, value: N) -> Result<()> {
+        self.put_float_at(self.current_offset(), value)?;
+        self.move_by(N::WIDTH as i128)
+    }
+
+    make_put_method! {f32, put_f32, put_float,
+    "See the documentation for [`U8Sink::put_float`]."}
+    make_put_method! {f64, put_f64, put_float,
+    "See the documentation for [`U8Sink::put_float`]."}
+
+    make_put_var_int_methods! {u64, encode_uleb128, put_uleb128_at, put_uleb128,
+    "Writes an unsigned LEB128 encoded integer (as used by DWARF and WebAssembly)"}
+
+    make_put_var_int_methods! {i64, encode_sleb128, put_sleb128_at, put_sleb128,
+    "Writes a signed LEB128 encoded integer (as used by DWARF and WebAssembly)"}
+
+    make_put_var_int_methods! {u64, encode_uleb128, put_varint_at, put_varint,
+    "Writes a protobuf-style varint (which is encoded the same way as an unsigned LEB128)"}
+
+    make_put_var_int_methods! {i64, encode_zigzag, put_zigzag_at, put_zigzag,
+    "Writes a zig-zag encoded protobuf-style varint (i.e. an `sint32` or `sint64`)"}
+
+    make_put_var_int_methods! {u64, encode_vlq, put_vlq_at, put_vlq,
+    "Writes a MIDI-style variable-length quantity (i.e. big endian groups of 7 bits)"}
+
+    /// Writes the provided string's UTF-8 bytes (without a terminator or length) at the given
+    /// offset without altering the [`U8Sink::current_offset`].
+    #[inline]
+    fn put_str_at(&mut self, offset: usize, value: &str) -> Result<()> {
+        self.put_bytes_at(offset, value.as_bytes())
+    }
+
+    /// Writes the provided string's UTF-8 bytes (without a terminator or length) at the
+    /// [`U8Sink::current_offset`] and then advances the [`U8Sink::current_offset`] past them.
+    #[inline]
+    fn put_str(&mut self, value: &str) -> Result<()> {
+        self.put_bytes(value.as_bytes())
+    }
+
+    /// Writes the provided string followed by a NUL at the given offset without altering the
+    /// [`U8Sink::current_offset`].
+    fn put_cstr_at(&mut self, offset: usize, value: &str) -> Result<()> {
+        let mut bytes = Vec::with_capacity(value.len() + 1);
+        bytes.extend_from_slice(value.as_bytes());
+        bytes.push(0);
+        self.put_bytes_at(offset, &bytes)
+    }
+
+    /// Writes the provided string followed by a NUL at the [`U8Sink::current_offset`] and then
+    /// advances the [`U8Sink::current_offset`] past the NUL.
+    fn put_cstr(&mut self, value: &str) -> Result<()> {
+        self.put_cstr_at(self.current_offset(), value)?;
+        self.move_by(value.len() as i128 + 1)
+    }
+
+    /// Writes the provided string's length in bytes (as an integer of type N, using the sink's
+    /// endidness) followed by the string at the given offset without altering the
+    /// [`U8Sink::current_offset`]. Returns an error if the length doesn't fit in an N.
+    fn put_prefixed_str_at<N>(&mut self, offset: usize, value: &str) -> Result<()>
+    where
+        N: Integer + TryFrom<usize>,
+    {
+        let len = N::try_from(value.len()).map_err(|_| Error::Other {
+            message: format!(
+                "A string that's {} bytes long is too long for its length prefix.",
+                value.len()
+            ),
+        })?;
+        let mut bytes = Vec::with_capacity(N::WIDTH + value.len());
+        bytes.resize(N::WIDTH, 0);
+        len.write_with_endidness(&mut bytes, self.endidness());
+        bytes.extend_from_slice(value.as_bytes());
+        self.put_bytes_at(offset, &bytes)
+    }
+
+    /// Writes the provided string prefixed by its length (see [`U8Sink::put_prefixed_str_at`]) at
+    /// the [`U8Sink::current_offset`] and then advances the [`U8Sink::current_offset`] past it.
+    fn put_prefixed_str<N>(&mut self, value: &str) -> Result<()>
+    where
+        N: Integer + TryFrom<usize>,
+    {
+        self.put_prefixed_str_at::<N>(self.current_offset(), value)?;
+        self.move_by((N::WIDTH + value.len()) as i128)
+    }
+
+    /// Writes the provided string into a fixed-width field that's `width` bytes long at the given
+    /// offset without altering the [`U8Sink::current_offset`]. Any space left after the string is
+    /// filled with `pad`. Returns an error if the string is longer than `width`.
+    fn put_padded_str_at(
+        &mut self,
+        offset: usize,
+        value: &str,
+        width: usize,
+        pad: u8,
+    ) -> Result<()> {
+        if value.len() > width {
+            return Err(Error::NotEnoughData {
+                requested: value.len(),
+                left: width,
+                offset,
+            });
+        }
+        let mut bytes = Vec::with_capacity(width);
+        bytes.extend_from_slice(value.as_bytes());
+        bytes.resize(width, pad);
+        self.put_bytes_at(offset, &bytes)
+    }
+
+    /// Writes the provided string into a fixed-width field (see [`U8Sink::put_padded_str_at`]) at
+    /// the [`U8Sink::current_offset`] and then advances the [`U8Sink::current_offset`] by `width`.
+    fn put_padded_str(&mut self, value: &str, width: usize, pad: u8) -> Result<()> {
+        self.put_padded_str_at(self.current_offset(), value, width, pad)?;
+        self.move_by(width as i128)
+    }
+
+    /// Writes the provided string as UTF-16 (using the sink's endidness, and without a terminator)
+    /// at the given offset without altering the [`U8Sink::current_offset`]. Returns the number of
+    /// bytes written.
+    fn put_utf16_at(&mut self, offset: usize, value: &str) -> Result<usize> {
+        let bytes = utf16_bytes(value, self.endidness(), false);
+        self.put_bytes_at(offset, &bytes)?;
+        Ok(bytes.len())
+    }
+
+    /// Writes the provided string as UTF-16 (see [`U8Sink::put_utf16_at`]) at the
+    /// [`U8Sink::current_offset`] and then advances the [`U8Sink::current_offset`] past it.
+    fn put_utf16(&mut self, value: &str) -> Result<()> {
+        let len = self.put_utf16_at(self.current_offset(), value)?;
+        self.move_by(len as i128)
+    }
+
+    /// Writes the provided string as UTF-16 followed by a NUL code unit at the given offset without
+    /// altering the [`U8Sink::current_offset`]. Returns the number of bytes written.
+    fn put_utf16_cstr_at(&mut self, offset: usize, value: &str) -> Result<usize> {
+        let bytes = utf16_bytes(value, self.endidness(), true);
+        self.put_bytes_at(offset, &bytes)?;
+        Ok(bytes.len())
+    }
+
+    /// Writes the provided string as UTF-16 followed by a NUL code unit at the
+    /// [`U8Sink::current_offset`] and then advances the [`U8Sink::current_offset`] past it.
+    fn put_utf16_cstr(&mut self, value: &str) -> Result<()> {
+        let len = self.put_utf16_cstr_at(self.current_offset(), value)?;
+        self.move_by(len as i128)
+    }
+}
+
+/// The inverse of creating an item from a [`crate::Segment`]: writes the item to a [`U8Sink`].
+///
+/// This can be derived via `#[derive(ToSegment)]`, which uses the same `from_seg` attributes as
+/// the `FromSegment` and `TryFromSegment` derives. As long as the attributes are the same,
+/// something written using a derived [`ToSegment`] can be read back using a derived
+/// `TryFromSegment`. Attributes that can't be reversed (e.g. `parser`, `map_each`, or `from` and
+/// `try_from` with another type) cause the derive to fail.
+pub trait ToSegment {
+    /// Writes the item to the sink, starting at its [`U8Sink::current_offset`].
+    fn write_to<S: U8Sink>(&self, sink: &mut S) -> Result<()>;
+
+    /// Writes the item to a new `Vec` using the provided endidness.
+    fn to_bytes_with_endidness(&self, endidness: Endidness) -> Result<Vec<u8>> {
+        let mut sink = VecSink::with_endidness(endidness);
+        self.write_to(&mut sink)?;
+        Ok(sink.into_vec())
+    }
+
+    /// Writes the item to a new `Vec` using the native endidness.
+    #[inline]
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        self.to_bytes_with_endidness(Endidness::default())
+    }
+}
+
+impl ToSegment for () {
+    #[inline]
+    fn write_to<S: U8Sink>(&self, _: &mut S) -> Result<()> {
+        Ok(())
+    }
+}
+
+macro_rules! impl_to_segment {
+    ($($type:ty),+) => {
+        $(
+            impl ToSegment for $type {
+                #[inline]
+                fn write_to<S: U8Sink>(&self, sink: &mut S) -> Result<()> {
+                    sink.put_num_at_with_endidness(sink.current_offset(), *self, sink.endidness())?;
+                    sink.move_by(<$type>::WIDTH as i128)
+                }
+            }
+        )+
+    };
+}
+
+impl_to_segment! { u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64 }
+
+impl<T: ToSegment + ?Sized> ToSegment for &T {
+    #[inline]
+    fn write_to<S: U8Sink>(&self, sink: &mut S) -> Result<()> {
+        (**self).write_to(sink)
+    }
+}
+
+impl<T: ToSegment> ToSegment for [T] {
+    fn write_to<S: U8Sink>(&self, sink: &mut S) -> Result<()> {
+        for item in self.iter() {
+            item.write_to(sink)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: ToSegment, const N: usize> ToSegment for [T; N] {
+    #[inline]
+    fn write_to<S: U8Sink>(&self, sink: &mut S) -> Result<()> {
+        self[..].write_to(sink)
+    }
+}
+
+impl<T: ToSegment> ToSegment for Vec<T> {
+    #[inline]
+    fn write_to<S: U8Sink>(&self, sink: &mut S) -> Result<()> {
+        self[..].write_to(sink)
+    }
+}
+
+/// Writes nothing if the value is `None`.
+impl<T: ToSegment> ToSegment for Option<T> {
+    #[inline]
+    fn write_to<S: U8Sink>(&self, sink: &mut S) -> Result<()> {
+        match self {
+            Some(value) => value.write_to(sink),
+            None => Ok(()),
+        }
+    }
+}
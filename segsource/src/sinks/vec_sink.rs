@@ -0,0 +1,187 @@
+#[cfg(feature = "std")]
+use super::offset_by;
+use crate::{Endidness, Error, Result, Segment, U8Sink, VecSource};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io;
+
+/// A [`U8Sink`] that uses a `Vec` to store its data.
+///
+/// Unlike a [`crate::SegmentMut`], a [`VecSink`] grows as needed: writing past the end of its data
+/// (or moving the cursor past the end and then writing) will extend it, filling any gaps with
+/// zeros.
+pub struct VecSink {
+    initial_offset: usize,
+    position: usize,
+    data: Vec<u8>,
+    endidness: Endidness,
+}
+
+impl VecSink {
+    /// Creates a new, empty [`VecSink`] with an initial offset of 0 and the native endidness.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_offset_and_endidness(0, Endidness::default())
+    }
+
+    /// Creates a new, empty [`VecSink`] using the provided endidness.
+    #[inline]
+    pub fn with_endidness(endidness: Endidness) -> Self {
+        Self::with_offset_and_endidness(0, endidness)
+    }
+
+    /// Creates a new, empty [`VecSink`] using the provided initial offset and endidness.
+    #[inline]
+    pub fn with_offset_and_endidness(initial_offset: usize, endidness: Endidness) -> Self {
+        Self::from_vec_with_offset(Vec::new(), initial_offset, endidness)
+    }
+
+    /// Creates a new [`VecSink`] whose data starts off as the provided `Vec`. The cursor will be
+    /// at the start of the data, so any writes will overwrite the existing data.
+    #[inline]
+    pub fn from_vec_with_offset(
+        data: Vec<u8>,
+        initial_offset: usize,
+        endidness: Endidness,
+    ) -> Self {
+        Self {
+            initial_offset,
+            position: 0,
+            data,
+            endidness,
+        }
+    }
+
+    /// Changes the endidness used for any future writes.
+    #[inline]
+    pub fn change_endidness(&mut self, endidness: Endidness) {
+        self.endidness = endidness
+    }
+
+    /// The number of bytes currently in the sink.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The lowest valid offset.
+    #[inline]
+    pub fn lower_offset_limit(&self) -> usize {
+        self.initial_offset
+    }
+
+    /// The offset just past the last byte written so far.
+    #[inline]
+    pub fn upper_offset_limit(&self) -> usize {
+        self.initial_offset + self.data.len()
+    }
+
+    /// A [`Segment`] over everything written so far. The segment will have the same initial
+    /// offset, endidness, and current offset as the sink (if the sink's cursor is past the end of
+    /// its data, then the segment's will be at the end).
+    pub fn as_segment(&self) -> Segment<'_, u8> {
+        Segment::new_full(
+            &self.data,
+            self.initial_offset,
+            self.position.min(self.data.len()),
+            self.endidness,
+        )
+    }
+
+    /// Consumes the sink and returns its data.
+    #[inline]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Consumes the sink and turns it into a [`VecSource`] with the same initial offset and
+    /// endidness.
+    #[inline]
+    pub fn into_source(self) -> VecSource<u8> {
+        VecSource::new(self.data, self.initial_offset, self.endidness)
+    }
+}
+
+impl Default for VecSink {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsRef<[u8]> for VecSink {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl U8Sink for VecSink {
+    #[inline]
+    fn endidness(&self) -> Endidness {
+        self.endidness
+    }
+
+    #[inline]
+    fn initial_offset(&self) -> usize {
+        self.initial_offset
+    }
+
+    #[inline]
+    fn current_offset(&self) -> usize {
+        self.initial_offset + self.position
+    }
+
+    fn move_to(&mut self, offset: usize) -> Result<()> {
+        if offset < self.initial_offset {
+            Err(Error::OffsetTooSmall { offset })
+        } else {
+            self.position = offset - self.initial_offset;
+            Ok(())
+        }
+    }
+
+    fn put_bytes_at(&mut self, offset: usize, bytes: &[u8]) -> Result<()> {
+        if offset < self.initial_offset {
+            return Err(Error::OffsetTooSmall { offset });
+        }
+        let pos = offset - self.initial_offset;
+        let end = pos
+            .checked_add(bytes.len())
+            .ok_or(Error::OffsetTooLarge { offset })?;
+        if end > self.data.len() {
+            self.data.resize(end, 0);
+        }
+        self.data[pos..end].copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl io::Write for VecSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.put_bytes(buf)?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl io::Seek for VecSink {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let offset = match pos {
+            io::SeekFrom::Start(to) => offset_by(0, to as i128),
+            io::SeekFrom::Current(by) => offset_by(self.current_offset(), by as i128),
+            io::SeekFrom::End(point) => offset_by(self.upper_offset_limit(), point as i128),
+        };
+        offset
+            .and_then(|offset| self.move_to(offset))
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        Ok(self.current_offset() as u64)
+    }
+}
@@ -0,0 +1,310 @@
+use super::stream::{finish_page, PageCache, Window, DEFAULT_MAX_PAGES, DEFAULT_PAGE_SIZE};
+use crate::{
+    error::{Error, Result},
+    marker::Numeric,
+    Endidness, SourceOptions,
+};
+use paste::paste;
+use std::{io::SeekFrom, sync::Arc};
+use tokio::io::{AsyncRead, AsyncReadExt as _, AsyncSeek, AsyncSeekExt as _};
+
+macro_rules! make_async_num_methods {
+    ($($type:ty),+) => {
+        paste! {
+            $(
+                #[doc = "Asynchronously reads a `" $type "` at the given offset without altering the"]
+                #[doc = "[`AsyncStreamSource::current_offset`]."]
+                #[inline]
+                pub async fn [<$type _at_async>](&mut self, offset: usize) -> Result<$type> {
+                    self.num_at_async::<$type>(offset).await
+                }
+
+                #[doc = "Asynchronously reads a `" $type "` at the"]
+                #[doc = "[`AsyncStreamSource::current_offset`] without altering it."]
+                #[inline]
+                pub async fn [<peek_ $type _async>](&mut self) -> Result<$type> {
+                    self.num_at_async::<$type>(self.position).await
+                }
+
+                #[doc = "Asynchronously reads a `" $type "` at the"]
+                #[doc = "[`AsyncStreamSource::current_offset`] and then advances the"]
+                #[doc = "[`AsyncStreamSource::current_offset`] past it."]
+                #[inline]
+                pub async fn [<next_ $type _async>](&mut self) -> Result<$type> {
+                    self.next_num_async::<$type>().await
+                }
+            )+
+        }
+    };
+}
+
+/// An async version of [`crate::StreamSource`] that lazily reads its data from anything that
+/// implements tokio's `AsyncRead` and `AsyncSeek`.
+///
+/// Like [`crate::StreamSource`], pages are only read when they're needed and the most recently
+/// used ones are kept in a bounded cache. Since reading requires awaiting, an
+/// [`AsyncStreamSource`] also keeps its own cursor (the [`AsyncStreamSource::current_offset`]) so
+/// that data can be read sequentially via methods like [`AsyncStreamSource::next_n_async`] and
+/// [`AsyncStreamSource::next_u32_async`].
+///
+/// ```
+/// # use segsource::{AsyncStreamSource, Endidness};
+/// # use std::io::Cursor;
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let data = Cursor::new(b"\x00\x00\x00\x02hi".to_vec());
+/// let mut source = AsyncStreamSource::new(data, 0x10, Endidness::Big).await.unwrap();
+/// let len = source.next_u32_async().await.unwrap() as usize;
+/// let window = source.next_n_async(len).await.unwrap();
+/// assert_eq!(window.initial_offset(), 0x14);
+/// assert_eq!(window.as_ref(), b"hi");
+/// # });
+/// ```
+pub struct AsyncStreamSource<R> {
+    reader: R,
+    cache: PageCache,
+    initial_offset: usize,
+    size: usize,
+    endidness: Endidness,
+    position: usize,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncStreamSource<R> {
+    /// Creates a new source that uses [`DEFAULT_PAGE_SIZE`] byte pages and caches at most
+    /// [`DEFAULT_MAX_PAGES`] of them.
+    #[inline]
+    pub async fn new(reader: R, initial_offset: usize, endidness: Endidness) -> Result<Self> {
+        let options = SourceOptions::new()
+            .with_initial_offset(initial_offset)
+            .with_endidness(endidness);
+        Self::with_options(reader, &options).await
+    }
+
+    /// Creates a new source using the provided [`SourceOptions`]. The page size is the options'
+    /// buffer size (or [`DEFAULT_PAGE_SIZE`]), and at most [`DEFAULT_MAX_PAGES`] pages are cached.
+    #[inline]
+    pub async fn with_options(reader: R, options: &SourceOptions) -> Result<Self> {
+        Self::with_cache(
+            reader,
+            options.initial_offset(),
+            options.endidness(),
+            options.buffer_size().unwrap_or(DEFAULT_PAGE_SIZE),
+            DEFAULT_MAX_PAGES,
+        )
+        .await
+    }
+
+    /// Creates a new source that uses pages of the provided size and caches at most the provided
+    /// number of them. The size of the data is found by seeking to the end of the reader.
+    ///
+    /// Returns an error if either the page size or the maximum number of pages is zero.
+    pub async fn with_cache(
+        mut reader: R,
+        initial_offset: usize,
+        endidness: Endidness,
+        page_size: usize,
+        max_pages: usize,
+    ) -> Result<Self> {
+        let cache = PageCache::new(page_size, max_pages)?;
+        let size = reader.seek(SeekFrom::End(0)).await? as usize;
+        Ok(Self {
+            reader,
+            cache,
+            initial_offset,
+            size,
+            endidness,
+            position: initial_offset,
+        })
+    }
+
+    /// Gets the page with the provided number, reading it if it's not already cached.
+    async fn page(&mut self, page_num: usize) -> Result<Arc<[u8]>> {
+        if let Some(page) = self.cache.get(page_num) {
+            return Ok(page);
+        }
+        let (start, len) = self.cache.page_span(page_num, self.size);
+        let mut buffer = vec![0; len];
+        self.reader.seek(SeekFrom::Start(start as u64)).await?;
+        let mut filled = 0;
+        while filled < len {
+            match self.reader.read(&mut buffer[filled..]).await? {
+                0 => break,
+                read => filled += read,
+            }
+        }
+        let page = finish_page(buffer, filled, start)?;
+        self.cache.insert(page_num, Arc::clone(&page));
+        Ok(page)
+    }
+
+    /// Asynchronously creates a [`Window`] from the start offset (inclusive) to the end offset
+    /// (exclusive). This does not alter the [`AsyncStreamSource::current_offset`].
+    pub async fn window_async(&mut self, start: usize, end: usize) -> Result<Window> {
+        self.validate_offset(start)?;
+        self.validate_offset(end)?;
+        if end < start {
+            return Err(Error::OffsetTooSmall { offset: end });
+        }
+        let (start_pos, end_pos) = (start - self.initial_offset, end - self.initial_offset);
+        let mut pages = Vec::new();
+        for page_num in self.cache.page_nums(start_pos, end_pos) {
+            pages.push(self.page(page_num).await?);
+        }
+        Ok(Window::from_pages(
+            pages,
+            self.cache.page_size(),
+            start_pos,
+            end_pos,
+            start as u64,
+            self.endidness,
+        ))
+    }
+
+    /// Asynchronously gets a [`Window`] of n items, starting at the given offset. This does not
+    /// alter the [`AsyncStreamSource::current_offset`].
+    #[inline]
+    pub async fn get_n_async(&mut self, offset: usize, num_items: usize) -> Result<Window> {
+        let end = offset
+            .checked_add(num_items)
+            .ok_or(Error::OffsetTooLarge { offset: usize::MAX })?;
+        self.window_async(offset, end).await
+    }
+
+    /// Asynchronously gets a [`Window`] of the next n items without altering the
+    /// [`AsyncStreamSource::current_offset`].
+    #[inline]
+    pub async fn peek_n_async(&mut self, num_items: usize) -> Result<Window> {
+        self.get_n_async(self.position, num_items).await
+    }
+
+    /// Asynchronously gets a [`Window`] of the next n items and then advances the
+    /// [`AsyncStreamSource::current_offset`] past them.
+    pub async fn next_n_async(&mut self, num_items: usize) -> Result<Window> {
+        let window = self.peek_n_async(num_items).await?;
+        self.position += num_items;
+        Ok(window)
+    }
+
+    async fn num_at_async<N: Numeric>(&mut self, offset: usize) -> Result<N> {
+        let window = self.get_n_async(offset, N::WIDTH).await?;
+        Ok(N::with_endidness(window.as_ref(), self.endidness))
+    }
+
+    async fn next_num_async<N: Numeric>(&mut self) -> Result<N> {
+        let num = self.num_at_async::<N>(self.position).await?;
+        self.position += N::WIDTH;
+        Ok(num)
+    }
+
+    make_async_num_methods! { u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64 }
+
+    /// Consumes the source, returning the underlying reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R> AsyncStreamSource<R> {
+    /// The current offset of the source's cursor.
+    #[inline]
+    pub fn current_offset(&self) -> usize {
+        self.position
+    }
+
+    /// Moves the cursor to the provided offset.
+    pub fn move_to(&mut self, offset: usize) -> Result<()> {
+        self.validate_offset(offset)?;
+        self.position = offset;
+        Ok(())
+    }
+
+    /// Moves the cursor by the provided number of items (which may be negative).
+    pub fn move_by(&mut self, num_items: i128) -> Result<()> {
+        let offset = self.position as i128 + num_items;
+        if offset < 0 {
+            Err(Error::OffsetTooSmall { offset: 0 })
+        } else {
+            self.move_to(offset as usize)
+        }
+    }
+
+    /// The number of items left after the [`AsyncStreamSource::current_offset`].
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.upper_offset_limit() - self.position
+    }
+
+    /// Checks to make sure that the provided offset is valid. If it is, then an `Ok(())` will be
+    /// returned. Otherwise, the appropriate error will be returned.
+    pub fn validate_offset(&self, offset: usize) -> Result<()> {
+        if offset < self.lower_offset_limit() {
+            Err(Error::OffsetTooSmall { offset })
+        } else if offset > self.upper_offset_limit() {
+            Err(Error::OffsetTooLarge { offset })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The number of pages currently cached.
+    #[inline]
+    pub fn cached_pages(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Removes all pages from the cache. Any [`Window`]s that have already been created are
+    /// unaffected.
+    #[inline]
+    pub fn clear_cache(&mut self) {
+        self.cache.clear()
+    }
+
+    /// The amount of data in the source.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of bytes in each page.
+    #[inline]
+    pub fn page_size(&self) -> usize {
+        self.cache.page_size()
+    }
+
+    /// The maximum number of pages that will be cached.
+    #[inline]
+    pub fn max_pages(&self) -> usize {
+        self.cache.max_pages()
+    }
+
+    /// The initial offset of the source.
+    #[inline]
+    pub fn initial_offset(&self) -> usize {
+        self.initial_offset
+    }
+
+    /// The endidness of the source.
+    #[inline]
+    pub fn endidness(&self) -> Endidness {
+        self.endidness
+    }
+
+    /// Changes the default endidness. This does **not** change the endidness for any [`Window`]s
+    /// that have already been created.
+    #[inline]
+    pub fn change_endidness(&mut self, endidness: Endidness) {
+        self.endidness = endidness
+    }
+
+    /// The lowest valid offset that can be requested.
+    #[inline]
+    pub fn lower_offset_limit(&self) -> usize {
+        self.initial_offset
+    }
+
+    /// The highest valid offset that can be requested.
+    #[inline]
+    pub fn upper_offset_limit(&self) -> usize {
+        self.size + self.initial_offset
+    }
+}
@@ -4,7 +4,7 @@ use crate::sync::async_bytes_from_file;
 use crate::sync::async_u8_vec_from_file;
 #[cfg(feature = "async")]
 use crate::AsyncU8Source;
-use crate::{Endidness, Result, Segment, Source, U8Source};
+use crate::{Endidness, Error, Result, Segment, Source, SourceOptions, U8Source};
 use bytes::{BufMut as _, Bytes, BytesMut};
 use std::{fs, io, path::Path};
 
@@ -38,13 +38,20 @@ impl Source for BytesSource {
 }
 
 impl U8Source for BytesSource {
+    impl_from_file_with_offset! {}
+
     #[inline]
-    fn from_file_with_offset<P: AsRef<Path>>(
-        path: P,
-        initial_offset: usize,
-        endidness: Endidness,
-    ) -> Result<Self> {
-        Ok(Self::new(bytes_from_file(path)?, initial_offset, endidness))
+    fn from_file_with_options<P: AsRef<Path>>(path: P, options: &SourceOptions) -> Result<Self> {
+        if options.buffer_size() == Some(0) {
+            return Err(Error::Other {
+                message: "The buffer size must be greater than zero.".into(),
+            });
+        }
+        Ok(Self::new(
+            bytes_from_file(path, options.buffer_size())?,
+            options.initial_offset(),
+            options.endidness(),
+        ))
     }
 
     #[inline]
@@ -88,43 +95,54 @@ impl U8Source for BytesSource {
 #[cfg(feature = "async")]
 #[async_trait]
 impl AsyncU8Source for BytesSource {
-    #[cfg(all(feature = "async", feature = "with-bytes"))]
+    #[inline]
     async fn from_file_with_offset_async<P>(
         path: P,
         initial_offset: usize,
         endidness: Endidness,
     ) -> Result<Self>
+    where
+        P: AsRef<Path> + Sync + Send,
+    {
+        let options = SourceOptions::new()
+            .with_initial_offset(initial_offset)
+            .with_endidness(endidness);
+        Self::from_file_with_options_async(path, &options).await
+    }
+
+    #[cfg(all(feature = "async", feature = "with-bytes"))]
+    async fn from_file_with_options_async<P>(path: P, options: &SourceOptions) -> Result<Self>
     where
         P: AsRef<Path> + Sync + Send,
     {
         Ok(Self::new(
             async_bytes_from_file(path).await?,
-            initial_offset,
-            endidness,
+            options.initial_offset(),
+            options.endidness(),
         ))
     }
 
     #[cfg(all(feature = "async", not(feature = "with-bytes")))]
-    async fn from_file_with_offset_async<P>(
-        path: P,
-        initial_offset: usize,
-        endidness: Endidness,
-    ) -> Result<Self>
+    async fn from_file_with_options_async<P>(path: P, options: &SourceOptions) -> Result<Self>
     where
         P: AsRef<Path> + Sync + Send,
     {
         Ok(Self::new(
             async_u8_vec_from_file(path).await?,
-            initial_offset,
-            endidness,
+            options.initial_offset(),
+            options.endidness(),
         ))
     }
 }
 
-fn bytes_from_file<P: AsRef<Path>>(path: P) -> io::Result<Bytes> {
+fn bytes_from_file<P: AsRef<Path>>(path: P, buffer_size: Option<usize>) -> io::Result<Bytes> {
     let capacity = fs::metadata(&path)?.len();
     let file = fs::File::open(path)?;
-    bytes_from_bufread(io::BufReader::new(file), Some(capacity as usize))
+    let reader = match buffer_size {
+        Some(buffer_size) => io::BufReader::with_capacity(buffer_size, file),
+        None => io::BufReader::new(file),
+    };
+    bytes_from_bufread(reader, Some(capacity as usize))
 }
 
 fn bytes_from_bufread<R: io::BufRead>(mut reader: R, capacity: Option<usize>) -> io::Result<Bytes> {
@@ -0,0 +1,288 @@
+use super::ErasedSource;
+use crate::{
+    error::{Error, Result},
+    Endidness, Segment, Source,
+};
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, vec::Vec};
+
+enum ChainedData<'s, I> {
+    Borrowed(&'s [I]),
+    Stitched(Vec<I>),
+}
+
+/// Data from a [`ChainedSource`] or a [`crate::SparseSource`]. Data that's entirely within one
+/// source is borrowed from it, while data that crosses from one source into the next is copied
+/// (stitched together) into its own buffer. Either way, [`Segment`]s are created from it via
+/// [`ChainedSegment::segment`].
+pub struct ChainedSegment<'s, I> {
+    data: ChainedData<'s, I>,
+    initial_offset: usize,
+    endidness: Endidness,
+}
+
+impl<'s, I> ChainedSegment<'s, I> {
+    #[inline]
+    pub(super) fn borrowed(data: &'s [I], initial_offset: usize, endidness: Endidness) -> Self {
+        Self {
+            data: ChainedData::Borrowed(data),
+            initial_offset,
+            endidness,
+        }
+    }
+
+    #[inline]
+    pub(super) fn stitched(data: Vec<I>, initial_offset: usize, endidness: Endidness) -> Self {
+        Self {
+            data: ChainedData::Stitched(data),
+            initial_offset,
+            endidness,
+        }
+    }
+
+    /// Creates a [`Segment`] over the data. The segment has the same initial offset and endidness
+    /// as the [`ChainedSource`] the data came from.
+    #[inline]
+    pub fn segment(&self) -> Segment<'_, I> {
+        Segment::new_full(self.as_ref(), self.initial_offset, 0, self.endidness)
+    }
+
+    /// The offset of the first item.
+    #[inline]
+    pub fn initial_offset(&self) -> usize {
+        self.initial_offset
+    }
+
+    /// The number of items.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.as_ref().len()
+    }
+
+    /// Whether or not there are no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether or not the data crossed from one chained source into another (and so had to be
+    /// copied).
+    #[inline]
+    pub fn is_stitched(&self) -> bool {
+        matches!(self.data, ChainedData::Stitched(_))
+    }
+}
+
+impl<'s, I> AsRef<[I]> for ChainedSegment<'s, I> {
+    fn as_ref(&self) -> &[I] {
+        match &self.data {
+            ChainedData::Borrowed(data) => data,
+            ChainedData::Stitched(data) => data,
+        }
+    }
+}
+
+/// Joins several [`Source`]s (which don't need to be the same type) into one contiguous offset
+/// space, such as the parts of a split archive.
+///
+/// Each source's own initial offset is ignored: the first source starts at the
+/// [`ChainedSource::initial_offset`], the second starts right after the first ends, and so on.
+/// Like a [`crate::StreamSource`], a [`ChainedSource`] doesn't implement [`Source`], since data
+/// that crosses from one source into the next has to be copied. Instead, it hands out
+/// [`ChainedSegment`]s.
+///
+/// ```
+/// # use segsource::{ChainedSource, Endidness, Source as _, U8Source as _, VecSource};
+/// let mut source = ChainedSource::new(0x100, Endidness::Big);
+/// source.push(VecSource::from_u8_slice(&[0, 1, 2, 3], Endidness::Little).unwrap()).unwrap();
+/// source.push(VecSource::from_u8_slice(&[4, 5], Endidness::Little).unwrap()).unwrap();
+/// assert_eq!(source.upper_offset_limit(), 0x106);
+/// let data = source.segment(0x102, 0x106).unwrap();
+/// assert!(data.is_stitched());
+/// assert_eq!(data.segment().next_u32().unwrap(), 0x02030405);
+/// assert!(!source.get_n(0x104, 2).unwrap().is_stitched());
+/// ```
+pub struct ChainedSource<I> {
+    parts: Vec<Box<dyn ErasedSource<I>>>,
+    // The position each part starts at.
+    starts: Vec<usize>,
+    size: usize,
+    initial_offset: usize,
+    endidness: Endidness,
+}
+
+impl<I> ChainedSource<I> {
+    /// Creates a new [`ChainedSource`] with no sources in it.
+    pub fn new(initial_offset: usize, endidness: Endidness) -> Self {
+        Self {
+            parts: Vec::new(),
+            starts: Vec::new(),
+            size: 0,
+            initial_offset,
+            endidness,
+        }
+    }
+
+    /// Adds a source to the end of the chain.
+    pub fn push<S>(&mut self, source: S) -> Result<()>
+    where
+        S: Source<Item = I> + Send + Sync + 'static,
+    {
+        let len = source.items()?.len();
+        self.starts.push(self.size);
+        self.parts.push(Box::new(source));
+        self.size += len;
+        Ok(())
+    }
+
+    /// The number of sources in the chain.
+    #[inline]
+    pub fn num_parts(&self) -> usize {
+        self.parts.len()
+    }
+
+    /// The offset that the source at the provided index starts at (if there is one).
+    #[inline]
+    pub fn part_offset(&self, index: usize) -> Option<usize> {
+        self.starts
+            .get(index)
+            .map(|start| start + self.initial_offset)
+    }
+
+    /// Finds the index of the source that the item at the provided offset is in.
+    pub fn part_index_at(&self, offset: usize) -> Result<usize> {
+        self.validate_offset(offset)?;
+        if offset == self.upper_offset_limit() {
+            return Err(Error::OffsetTooLarge { offset });
+        }
+        Ok(self.part_index(offset - self.initial_offset))
+    }
+
+    /// The index of the part the provided position is in. Empty parts are skipped.
+    fn part_index(&self, pos: usize) -> usize {
+        self.starts.partition_point(|&start| start <= pos) - 1
+    }
+
+    /// Checks to make sure that the provided offset is valid. If it is, then an `Ok(())` will be
+    /// returned. Otherwise, the appropriate error will be returned.
+    pub fn validate_offset(&self, offset: usize) -> Result<()> {
+        if offset < self.lower_offset_limit() {
+            Err(Error::OffsetTooSmall { offset })
+        } else if offset > self.upper_offset_limit() {
+            Err(Error::OffsetTooLarge { offset })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The total amount of data in all of the chained sources.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The initial offset of the chain.
+    #[inline]
+    pub fn initial_offset(&self) -> usize {
+        self.initial_offset
+    }
+
+    /// Changes the initial offset. This does **not** change the initial offset for any
+    /// [`ChainedSegment`]s that have already been created.
+    #[inline]
+    pub fn change_initial_offset(&mut self, offset: usize) {
+        self.initial_offset = offset
+    }
+
+    /// The endidness used for all [`Segment`]s created from the chain, regardless of the
+    /// endidness of the individual sources.
+    #[inline]
+    pub fn endidness(&self) -> Endidness {
+        self.endidness
+    }
+
+    /// Changes the endidness. This does **not** change the endidness for any
+    /// [`ChainedSegment`]s that have already been created.
+    #[inline]
+    pub fn change_endidness(&mut self, endidness: Endidness) {
+        self.endidness = endidness
+    }
+
+    /// The lowest valid offset that can be requested.
+    #[inline]
+    pub fn lower_offset_limit(&self) -> usize {
+        self.initial_offset
+    }
+
+    /// The highest valid offset that can be requested.
+    #[inline]
+    pub fn upper_offset_limit(&self) -> usize {
+        self.size + self.initial_offset
+    }
+}
+
+impl<I: Clone> ChainedSource<I> {
+    /// Gets the data from the start offset (inclusive) to the end offset (exclusive). If all of
+    /// the data is in one source, it's borrowed; otherwise it's stitched together.
+    pub fn segment(&self, start: usize, end: usize) -> Result<ChainedSegment<'_, I>> {
+        self.validate_offset(start)?;
+        self.validate_offset(end)?;
+        if end < start {
+            return Err(Error::OffsetTooSmall { offset: end });
+        }
+        let (start_pos, end_pos) = (start - self.initial_offset, end - self.initial_offset);
+        let data = if start_pos == end_pos {
+            ChainedData::Borrowed(&[][..])
+        } else {
+            let mut idx = self.part_index(start_pos);
+            let items = self.parts[idx].items()?;
+            let part_start = self.starts[idx];
+            if end_pos <= part_start + items.len() {
+                ChainedData::Borrowed(&items[start_pos - part_start..end_pos - part_start])
+            } else {
+                let mut buffer = Vec::with_capacity(end_pos - start_pos);
+                buffer.extend_from_slice(&items[start_pos - part_start..]);
+                while buffer.len() < end_pos - start_pos {
+                    idx += 1;
+                    let items = self.parts[idx].items()?;
+                    let take = (end_pos - self.starts[idx]).min(items.len());
+                    buffer.extend_from_slice(&items[..take]);
+                }
+                ChainedData::Stitched(buffer)
+            }
+        };
+        Ok(ChainedSegment {
+            data,
+            initial_offset: start,
+            endidness: self.endidness,
+        })
+    }
+
+    /// Gets n items, starting at the given offset.
+    #[inline]
+    pub fn get_n(&self, offset: usize, num_items: usize) -> Result<ChainedSegment<'_, I>> {
+        let end = offset
+            .checked_add(num_items)
+            .ok_or(Error::OffsetTooLarge { offset: usize::MAX })?;
+        self.segment(offset, end)
+    }
+
+    /// Gets all of the data in the chain. Unless there's only one source with data in it, this
+    /// will copy all of it.
+    #[inline]
+    pub fn all(&self) -> Result<ChainedSegment<'_, I>> {
+        self.segment(self.lower_offset_limit(), self.upper_offset_limit())
+    }
+
+    /// Gets all items before the provided offset (exclusive).
+    #[inline]
+    pub fn all_before(&self, offset: usize) -> Result<ChainedSegment<'_, I>> {
+        self.segment(self.lower_offset_limit(), offset)
+    }
+
+    /// Gets all items after the provided offset (inclusive).
+    #[inline]
+    pub fn all_after(&self, offset: usize) -> Result<ChainedSegment<'_, I>> {
+        self.segment(offset, self.upper_offset_limit())
+    }
+}
@@ -1,13 +1,20 @@
+use super::Window;
 #[cfg(feature = "async")]
 use crate::AsyncU8Source;
-use crate::{Endidness, Result, Segment, Source, U8Source};
+use crate::{
+    AccessHint, Endidness, Error, LockPolicy, Result, Segment, SegmentMut, Source, SourceOptions,
+    U8Source,
+};
 #[cfg(feature = "with-bytes")]
 use bytes::Bytes;
-use fs3::FileExt as _;
-use memmap2::{Mmap, MmapMut};
+use core::convert::TryFrom;
+use fs3::FileExt;
+use memmap2::{Mmap, MmapMut, MmapOptions};
 use std::{
-    fs::File,
+    fs::{File, OpenOptions},
+    ops::Range,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
 #[cfg(feature = "async")]
 use tokio::task::spawn_blocking;
@@ -15,12 +22,97 @@ use tokio::task::spawn_blocking;
 #[cfg(feature = "async")]
 use async_trait::async_trait;
 
+/// The default number of bytes a [`WindowedMappedFileSource`] maps at a time.
+pub const DEFAULT_MAP_WINDOW_SIZE: usize = 64 * 1024 * 1024;
+
+/// The size of the file. Since offsets are `usize`s, this fails if the file is too large for them.
+fn file_len(file: &File) -> Result<usize> {
+    let len = file.metadata()?.len();
+    usize::try_from(len).map_err(|_| Error::Other {
+        message: format!(
+            "A file that's {} bytes long is too large to be addressed on this platform.",
+            len
+        ),
+    })
+}
+
+/// Checks that the file has len bytes starting at the provided position. Mapping data past the end
+/// of a file doesn't fail, but accessing it does (and not in a way that can be recovered from).
+fn validate_file_range(file: &File, file_pos: usize, len: usize) -> Result<()> {
+    let file_len = file_len(file)?;
+    if file_pos > file_len {
+        Err(Error::OffsetTooLarge { offset: file_pos })
+    } else if len > file_len - file_pos {
+        Err(Error::NotEnoughData {
+            requested: len,
+            left: file_len - file_pos,
+            offset: file_pos,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Locks the file according to the policy.
+fn lock_file(file: &File, lock_policy: LockPolicy) -> Result<()> {
+    // Newer versions of std have their own (incompatible) locking methods on files.
+    match lock_policy {
+        LockPolicy::None => Ok(()),
+        LockPolicy::Shared => FileExt::try_lock_shared(file),
+        LockPolicy::Exclusive => FileExt::try_lock_exclusive(file),
+        LockPolicy::SharedBlocking => FileExt::lock_shared(file),
+        LockPolicy::ExclusiveBlocking => FileExt::lock_exclusive(file),
+    }?;
+    Ok(())
+}
+
+/// The [`MmapOptions`] to map a file with.
+fn mmap_options(options: &SourceOptions) -> MmapOptions {
+    let mut mmap_options = MmapOptions::new();
+    if options.populate() {
+        mmap_options.populate();
+    }
+    mmap_options
+}
+
+/// Passes the access hint for the mapped data on to the OS.
+#[cfg(unix)]
+fn advise(data: &[u8], access_hint: AccessHint) {
+    let advice = match access_hint {
+        AccessHint::Normal => return,
+        AccessHint::Sequential => libc::MADV_SEQUENTIAL,
+        AccessHint::Random => libc::MADV_RANDOM,
+        AccessHint::WillNeed => libc::MADV_WILLNEED,
+    };
+    if data.is_empty() {
+        return;
+    }
+    // Maps that don't start at the beginning of a file don't start at the beginning of a page, but
+    // madvise has to be given the address of one.
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let addr = data.as_ptr() as usize;
+    let page_addr = addr - addr % page_size;
+    // Hints are only hints, so it's fine if the OS doesn't take them.
+    unsafe {
+        libc::madvise(
+            page_addr as *mut libc::c_void,
+            data.len() + (addr - page_addr),
+            advice,
+        );
+    }
+}
+
+#[cfg(not(unix))]
+#[inline]
+fn advise(_: &[u8], _: AccessHint) {}
+
 /// A [`U8Source`] whose data is owned by a memory mapped file. This source can only use `u8`s as
 /// its item.
 ///
-/// An important note: The mapped file is locked first via an advisory lock. This is to prevent
-/// changes to the file while it is mapped. However, this is just an **advisory** lock, and other
-/// processes may choose to ignore it. So, it's best not to alter it while it's mapped.
+/// An important note: The mapped file is locked first via an advisory lock (a shared one, unless
+/// another [`LockPolicy`] is provided via [`SourceOptions`]). This is to prevent changes to the file
+/// while it is mapped. However, this is just an **advisory** lock, and other processes may choose
+/// to ignore it. So, it's best not to alter it while it's mapped.
 pub struct MappedFileSource {
     initial_offset: usize,
     data: Mmap,
@@ -50,6 +142,52 @@ impl MappedFileSource {
     pub fn path(&self) -> Option<&Path> {
         self.maybe_path.as_ref().map(|p| p.as_ref())
     }
+
+    /// Creates a new source that only maps len bytes of the file, starting at the provided
+    /// position in it. The source's initial offset is that position, so its offsets are the same
+    /// as positions in the file.
+    #[inline]
+    pub fn from_file_range<P: AsRef<Path>>(
+        path: P,
+        file_pos: usize,
+        len: usize,
+        endidness: Endidness,
+    ) -> Result<Self> {
+        Self::from_file_range_with_options(
+            path,
+            file_pos,
+            len,
+            &SourceOptions::new().with_endidness(endidness),
+        )
+    }
+
+    /// Like [`MappedFileSource::from_file_range`], but uses the provided [`SourceOptions`]. Since
+    /// the source's initial offset is always the position in the file, the options' initial offset
+    /// isn't used.
+    pub fn from_file_range_with_options<P: AsRef<Path>>(
+        path: P,
+        file_pos: usize,
+        len: usize,
+        options: &SourceOptions,
+    ) -> Result<Self> {
+        let file = File::open(&path)?;
+        lock_file(&file, options.lock_policy().unwrap_or(LockPolicy::Shared))?;
+        validate_file_range(&file, file_pos, len)?;
+        let mmap = unsafe {
+            mmap_options(options)
+                .offset(file_pos as u64)
+                .len(len)
+                .map(&file)?
+        };
+        advise(&mmap, options.access_hint());
+        Ok(Self::new(
+            file_pos,
+            mmap,
+            options.endidness(),
+            Some(file),
+            Some(path.as_ref().to_path_buf()),
+        ))
+    }
 }
 
 impl Source for MappedFileSource {
@@ -59,19 +197,17 @@ impl Source for MappedFileSource {
 
 impl U8Source for MappedFileSource {
     impl_endidness_items! {}
+    impl_from_file_with_offset! {}
 
-    fn from_file_with_offset<P: AsRef<Path>>(
-        path: P,
-        initial_offset: usize,
-        endidness: Endidness,
-    ) -> Result<Self> {
+    fn from_file_with_options<P: AsRef<Path>>(path: P, options: &SourceOptions) -> Result<Self> {
         let file = File::open(&path)?;
-        file.try_lock_shared()?;
-        let mmap = unsafe { Mmap::map(&file)? };
+        lock_file(&file, options.lock_policy().unwrap_or(LockPolicy::Shared))?;
+        let mmap = unsafe { mmap_options(options).map(&file)? };
+        advise(&mmap, options.access_hint());
         Ok(Self::new(
-            initial_offset,
+            options.initial_offset(),
             mmap,
-            endidness,
+            options.endidness(),
             Some(file),
             Some(path.as_ref().to_path_buf()),
         ))
@@ -112,18 +248,30 @@ impl U8Source for MappedFileSource {
 #[cfg(feature = "async")]
 #[async_trait]
 impl AsyncU8Source for MappedFileSource {
-    #[cfg(feature = "async")]
     #[inline]
     async fn from_file_with_offset_async<P>(
         path: P,
         initial_offset: usize,
         endidness: Endidness,
     ) -> Result<Self>
+    where
+        P: AsRef<Path> + Sync + Send,
+    {
+        let options = SourceOptions::new()
+            .with_initial_offset(initial_offset)
+            .with_endidness(endidness);
+        Self::from_file_with_options_async(path, &options).await
+    }
+
+    #[cfg(feature = "async")]
+    #[inline]
+    async fn from_file_with_options_async<P>(path: P, options: &SourceOptions) -> Result<Self>
     where
         P: AsRef<Path> + Sync + Send,
     {
         let path = path.as_ref().to_path_buf();
-        spawn_blocking(move || Self::from_file_with_offset(path, initial_offset, endidness))
+        let options = options.clone();
+        spawn_blocking(move || Self::from_file_with_options(path, &options))
             .await
             .unwrap()
     }
@@ -132,7 +280,517 @@ impl AsyncU8Source for MappedFileSource {
 impl Drop for MappedFileSource {
     fn drop(&mut self) {
         if let Some(file) = &self.maybe_mapped_file {
-            file.unlock().unwrap();
+            FileExt::unlock(file).unwrap();
         }
     }
 }
+
+/// A [`U8Source`] whose data is owned by a memory mapped file that can be changed in place (e.g.
+/// to patch a firmware image). Changes are made using the [`SegmentMut`]s it creates and are
+/// written back to the file when the source is flushed (or whenever the OS decides to).
+///
+/// The file is opened for both reading and writing and is locked via an exclusive advisory lock
+/// (unless another [`LockPolicy`] is provided via [`SourceOptions`]), so it can't be mapped by
+/// another [`MappedFileSourceMut`] or [`MappedFileSource`] at the same time. As with a
+/// [`MappedFileSource`], this is just an **advisory** lock, so other processes may choose to ignore
+/// it.
+pub struct MappedFileSourceMut {
+    initial_offset: usize,
+    data: MmapMut,
+    endidness: Endidness,
+    maybe_mapped_file: Option<File>,
+    maybe_path: Option<PathBuf>,
+}
+
+impl MappedFileSourceMut {
+    #[inline]
+    fn new(
+        initial_offset: usize,
+        data: MmapMut,
+        endidness: Endidness,
+        maybe_mapped_file: Option<File>,
+        maybe_path: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            initial_offset,
+            data,
+            endidness,
+            maybe_mapped_file,
+            maybe_path,
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.maybe_path.as_ref().map(|p| p.as_ref())
+    }
+
+    /// Validates both offsets and makes sure that the end offset isn't before the start offset.
+    fn validate_range(&self, start: usize, end: usize) -> Result<()> {
+        self.validate_offset(start)?;
+        self.validate_offset(end)?;
+        if end < start {
+            Err(Error::OffsetTooSmall { offset: end })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Creates a new source that only maps len bytes of the file, starting at the provided
+    /// position in it. The source's initial offset is that position, so its offsets are the same
+    /// as positions in the file.
+    #[inline]
+    pub fn from_file_range<P: AsRef<Path>>(
+        path: P,
+        file_pos: usize,
+        len: usize,
+        endidness: Endidness,
+    ) -> Result<Self> {
+        Self::from_file_range_with_options(
+            path,
+            file_pos,
+            len,
+            &SourceOptions::new().with_endidness(endidness),
+        )
+    }
+
+    /// Like [`MappedFileSourceMut::from_file_range`], but uses the provided [`SourceOptions`].
+    /// Since the source's initial offset is always the position in the file, the options' initial
+    /// offset isn't used.
+    pub fn from_file_range_with_options<P: AsRef<Path>>(
+        path: P,
+        file_pos: usize,
+        len: usize,
+        options: &SourceOptions,
+    ) -> Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(&path)?;
+        lock_file(
+            &file,
+            options.lock_policy().unwrap_or(LockPolicy::Exclusive),
+        )?;
+        validate_file_range(&file, file_pos, len)?;
+        let mmap = unsafe {
+            mmap_options(options)
+                .offset(file_pos as u64)
+                .len(len)
+                .map_mut(&file)?
+        };
+        advise(&mmap, options.access_hint());
+        Ok(Self::new(
+            file_pos,
+            mmap,
+            options.endidness(),
+            Some(file),
+            Some(path.as_ref().to_path_buf()),
+        ))
+    }
+
+    /// Creates a [`SegmentMut`] from the start offset (inclusive) to the end offset (exclusive).
+    /// Like the segments created by [`Source::segment`], it has the same endidness as the source.
+    pub fn segment_mut(&mut self, start: usize, end: usize) -> Result<SegmentMut<u8>> {
+        self.validate_range(start, end)?;
+        Ok(SegmentMut::with_offset_and_endidness(
+            &mut self.data[start - self.initial_offset..end - self.initial_offset],
+            start,
+            self.endidness,
+        ))
+    }
+
+    /// Returns a single [`SegmentMut`] containing all data in the source.
+    #[inline]
+    pub fn all_mut(&mut self) -> Result<SegmentMut<u8>> {
+        self.segment_mut(self.lower_offset_limit(), self.upper_offset_limit())
+    }
+
+    /// Writes any changes back to the file, blocking until they've been written.
+    #[inline]
+    pub fn flush(&self) -> Result<()> {
+        Ok(self.data.flush()?)
+    }
+
+    /// Writes any changes from the start offset (inclusive) to the end offset (exclusive) back to
+    /// the file, blocking until they've been written.
+    pub fn flush_range(&self, start: usize, end: usize) -> Result<()> {
+        self.validate_range(start, end)?;
+        Ok(self
+            .data
+            .flush_range(start - self.initial_offset, end - start)?)
+    }
+
+    /// An async version of [`MappedFileSourceMut::flush`].
+    #[cfg(feature = "async")]
+    #[inline]
+    pub async fn flush_async(&self) -> Result<()> {
+        self.flush_range_async(self.lower_offset_limit(), self.upper_offset_limit())
+            .await
+    }
+
+    /// An async version of [`MappedFileSourceMut::flush_range`].
+    #[cfg(feature = "async")]
+    pub async fn flush_range_async(&self, start: usize, end: usize) -> Result<()> {
+        self.validate_range(start, end)?;
+        // This only starts writing the changes, so the file has to be synced to wait for them.
+        self.data
+            .flush_async_range(start - self.initial_offset, end - start)?;
+        if let Some(file) = &self.maybe_mapped_file {
+            let file = file.try_clone()?;
+            spawn_blocking(move || file.sync_data()).await.unwrap()?;
+        }
+        Ok(())
+    }
+}
+
+impl Source for MappedFileSourceMut {
+    type Item = u8;
+    add_basic_source_items! {@add_u8_items}
+}
+
+impl U8Source for MappedFileSourceMut {
+    impl_endidness_items! {}
+    impl_from_file_with_offset! {}
+
+    fn from_file_with_options<P: AsRef<Path>>(path: P, options: &SourceOptions) -> Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(&path)?;
+        lock_file(
+            &file,
+            options.lock_policy().unwrap_or(LockPolicy::Exclusive),
+        )?;
+        let mmap = unsafe { mmap_options(options).map_mut(&file)? };
+        advise(&mmap, options.access_hint());
+        Ok(Self::new(
+            options.initial_offset(),
+            mmap,
+            options.endidness(),
+            Some(file),
+            Some(path.as_ref().to_path_buf()),
+        ))
+    }
+
+    #[cfg(feature = "with-bytes")]
+    #[inline]
+    fn from_bytes_with_offset(
+        bytes: Bytes,
+        initial_offset: usize,
+        endidness: Endidness,
+    ) -> Result<Self> {
+        Self::from_u8_slice_with_offset(&bytes, initial_offset, endidness)
+    }
+
+    fn from_u8_slice_with_offset(
+        bytes: &[u8],
+        initial_offset: usize,
+        endidness: Endidness,
+    ) -> Result<Self> {
+        let mut mmap_mut = MmapMut::map_anon(bytes.len())?;
+        mmap_mut.copy_from_slice(bytes);
+        Ok(Self::new(initial_offset, mmap_mut, endidness, None, None))
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait]
+impl AsyncU8Source for MappedFileSourceMut {
+    #[inline]
+    async fn from_file_with_offset_async<P>(
+        path: P,
+        initial_offset: usize,
+        endidness: Endidness,
+    ) -> Result<Self>
+    where
+        P: AsRef<Path> + Sync + Send,
+    {
+        let options = SourceOptions::new()
+            .with_initial_offset(initial_offset)
+            .with_endidness(endidness);
+        Self::from_file_with_options_async(path, &options).await
+    }
+
+    #[inline]
+    async fn from_file_with_options_async<P>(path: P, options: &SourceOptions) -> Result<Self>
+    where
+        P: AsRef<Path> + Sync + Send,
+    {
+        let path = path.as_ref().to_path_buf();
+        let options = options.clone();
+        spawn_blocking(move || Self::from_file_with_options(path, &options))
+            .await
+            .unwrap()
+    }
+}
+
+impl Drop for MappedFileSourceMut {
+    fn drop(&mut self) {
+        if let Some(file) = &self.maybe_mapped_file {
+            FileExt::unlock(file).unwrap();
+        }
+    }
+}
+
+/// The part of a [`WindowedMappedFileSource`]'s file that's currently mapped.
+struct MappedPart {
+    start: u64,
+    map: Arc<Mmap>,
+}
+
+impl MappedPart {
+    #[inline]
+    fn end(&self) -> u64 {
+        self.start + self.map.len() as u64
+    }
+
+    #[inline]
+    fn contains(&self, start: u64, end: u64) -> bool {
+        self.start <= start && end <= self.end()
+    }
+}
+
+/// Narrows a position in a file so that it can be used in memory.
+fn narrow(value: u64) -> Result<usize> {
+    usize::try_from(value).map_err(|_| Error::Other {
+        message: format!("{} is too large to be addressed on this platform.", value),
+    })
+}
+
+/// The offset that's reported in errors about a position in a file. Positions that are too large
+/// to be addressed on this platform are reported as `usize::MAX`.
+#[inline]
+fn error_offset(value: u64) -> usize {
+    usize::try_from(value).unwrap_or(usize::MAX)
+}
+
+/// A source whose data is a memory mapped file that's only mapped a part at a time. This allows
+/// files that are too large to be mapped all at once (e.g. on 32-bit targets) to be read.
+///
+/// Like a [`crate::StreamSource`], it hands out [`Window`]s instead of implementing [`Source`].
+/// When a window is requested that isn't in the part of the file that's currently mapped, the
+/// part of the file around it is mapped instead. Windows that have already been created keep their
+/// part of the file mapped until they're dropped. A window is never copied, so it can be larger
+/// than the window size (in which case, all of it is mapped).
+///
+/// Offsets are always the same as positions in the file, so they're `u64`s. Only the windows
+/// themselves need to fit in memory, so a window can start anywhere in the file as long as its
+/// length fits in a `usize`. See [`Window::segment`] for how the offsets of a window that starts
+/// past `usize::MAX` are handled. The file is locked the same way as a [`MappedFileSource`]'s is.
+pub struct WindowedMappedFileSource {
+    file: File,
+    path: PathBuf,
+    current: Mutex<Option<MappedPart>>,
+    window_size: usize,
+    size: u64,
+    endidness: Endidness,
+    access_hint: AccessHint,
+    populate: bool,
+}
+
+impl WindowedMappedFileSource {
+    /// Creates a new source that maps [`DEFAULT_MAP_WINDOW_SIZE`] bytes of the file at a time.
+    #[inline]
+    pub fn new<P: AsRef<Path>>(path: P, endidness: Endidness) -> Result<Self> {
+        Self::with_options(path, &SourceOptions::new().with_endidness(endidness))
+    }
+
+    /// Creates a new source that maps the provided number of bytes of the file at a time. Each
+    /// part of the file that's mapped starts at a multiple of the window size.
+    ///
+    /// Returns an error if the window size is zero.
+    #[inline]
+    pub fn with_window_size<P: AsRef<Path>>(
+        path: P,
+        endidness: Endidness,
+        window_size: usize,
+    ) -> Result<Self> {
+        Self::open(
+            path,
+            &SourceOptions::new().with_endidness(endidness),
+            window_size,
+        )
+    }
+
+    /// Creates a new source using the provided [`SourceOptions`]. The window size is the options'
+    /// buffer size (or [`DEFAULT_MAP_WINDOW_SIZE`]), and the access hint is given for each part of
+    /// the file as it's mapped. Since offsets are always positions in the file, the options'
+    /// initial offset isn't used.
+    #[inline]
+    pub fn with_options<P: AsRef<Path>>(path: P, options: &SourceOptions) -> Result<Self> {
+        Self::open(
+            path,
+            options,
+            options.buffer_size().unwrap_or(DEFAULT_MAP_WINDOW_SIZE),
+        )
+    }
+
+    fn open<P: AsRef<Path>>(path: P, options: &SourceOptions, window_size: usize) -> Result<Self> {
+        if window_size == 0 {
+            return Err(Error::Other {
+                message: "The window size must be greater than zero.".into(),
+            });
+        }
+        let file = File::open(&path)?;
+        lock_file(&file, options.lock_policy().unwrap_or(LockPolicy::Shared))?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            file,
+            path: path.as_ref().to_path_buf(),
+            current: Mutex::new(None),
+            window_size,
+            size,
+            endidness: options.endidness(),
+            access_hint: options.access_hint(),
+            populate: options.populate(),
+        })
+    }
+
+    /// Creates a [`Window`] from the start offset (inclusive) to the end offset (exclusive),
+    /// remapping the file if needed.
+    pub fn window(&self, start: u64, end: u64) -> Result<Window> {
+        self.validate_offset(start)?;
+        self.validate_offset(end)?;
+        if end < start {
+            return Err(Error::OffsetTooSmall {
+                offset: error_offset(end),
+            });
+        }
+        let len = narrow(end - start)?;
+        if len == 0 {
+            return Ok(Window::from_pages(
+                Vec::new(),
+                self.window_size,
+                0,
+                0,
+                start,
+                self.endidness,
+            ));
+        }
+        let mut current = self.current.lock().unwrap();
+        let part = match current.take() {
+            Some(part) if part.contains(start, end) => part,
+            _ => {
+                let window_size = self.window_size as u64;
+                let map_start = start - start % window_size;
+                let map_len = (end - map_start)
+                    .max(window_size)
+                    .min(self.size - map_start);
+                let mut mmap_options = MmapOptions::new();
+                if self.populate {
+                    mmap_options.populate();
+                }
+                let map = unsafe {
+                    mmap_options
+                        .offset(map_start)
+                        .len(narrow(map_len)?)
+                        .map(&self.file)?
+                };
+                advise(&map, self.access_hint);
+                MappedPart {
+                    start: map_start,
+                    map: Arc::new(map),
+                }
+            }
+        };
+        // The window is inside of the mapped part, so its position in it fits in a usize.
+        let start_pos = (start - part.start) as usize;
+        let window = Window::from_map(
+            Arc::clone(&part.map),
+            start_pos,
+            start_pos + len,
+            start,
+            self.endidness,
+        );
+        *current = Some(part);
+        Ok(window)
+    }
+
+    /// Gets a [`Window`] of n items, starting at the given offset.
+    #[inline]
+    pub fn get_n(&self, offset: u64, num_items: usize) -> Result<Window> {
+        let end = offset
+            .checked_add(num_items as u64)
+            .ok_or(Error::OffsetTooLarge { offset: usize::MAX })?;
+        self.window(offset, end)
+    }
+
+    /// Gets a [`Window`] containing all data in the source. This maps the entire file.
+    #[inline]
+    pub fn all(&self) -> Result<Window> {
+        self.window(self.lower_offset_limit(), self.upper_offset_limit())
+    }
+
+    /// Gets a [`Window`] of all items in the source before the provided offset (exclusive).
+    #[inline]
+    pub fn all_before(&self, offset: u64) -> Result<Window> {
+        self.window(self.lower_offset_limit(), offset)
+    }
+
+    /// Gets a [`Window`] of all items in the source after the provided offset (inclusive).
+    #[inline]
+    pub fn all_after(&self, offset: u64) -> Result<Window> {
+        self.window(offset, self.upper_offset_limit())
+    }
+
+    /// Checks to make sure that the provided offset is valid. If it is, then an `Ok(())` will be
+    /// returned. Otherwise, the appropriate error will be returned.
+    pub fn validate_offset(&self, offset: u64) -> Result<()> {
+        if offset > self.upper_offset_limit() {
+            Err(Error::OffsetTooLarge {
+                offset: error_offset(offset),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The offsets of the part of the file that's currently mapped (if any).
+    pub fn mapped_range(&self) -> Option<Range<u64>> {
+        self.current
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|part| part.start..part.end())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The size of the file.
+    #[inline]
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The number of bytes that are mapped at a time.
+    #[inline]
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// The endidness of the source.
+    #[inline]
+    pub fn endidness(&self) -> Endidness {
+        self.endidness
+    }
+
+    /// Changes the default endidness. This does **not** change the endidness for any [`Window`]s
+    /// that have already been created.
+    #[inline]
+    pub fn change_endidness(&mut self, endidness: Endidness) {
+        self.endidness = endidness
+    }
+
+    /// The lowest valid offset that can be requested, which is always zero.
+    #[inline]
+    pub fn lower_offset_limit(&self) -> u64 {
+        0
+    }
+
+    /// The highest valid offset that can be requested.
+    #[inline]
+    pub fn upper_offset_limit(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Drop for WindowedMappedFileSource {
+    fn drop(&mut self) {
+        FileExt::unlock(&self.file).unwrap();
+    }
+}
@@ -72,6 +72,27 @@ macro_rules! add_basic_source_items {
     };
 }
 
+/// Implements [`U8Source::from_file_with_offset`] for sources that implement
+/// [`U8Source::from_file_with_options`].
+macro_rules! impl_from_file_with_offset {
+    () => {
+        #[cfg(feature = "std")]
+        #[inline]
+        fn from_file_with_offset<P: AsRef<Path>>(
+            path: P,
+            initial_offset: usize,
+            endidness: Endidness,
+        ) -> Result<Self> {
+            Self::from_file_with_options(
+                path,
+                &SourceOptions::new()
+                    .with_initial_offset(initial_offset)
+                    .with_endidness(endidness),
+            )
+        }
+    };
+}
+
 macro_rules! impl_endidness_items {
     () => {
         #[inline]
@@ -86,11 +107,36 @@ macro_rules! impl_endidness_items {
     };
 }
 
+/// A [`Source`] whose concrete type has been erased so that sources of different types can be
+/// stored together (e.g. in a [`ChainedSource`]).
+trait ErasedSource<I>: Send + Sync {
+    fn items(&self) -> Result<&[I]>;
+}
+
+impl<S> ErasedSource<S::Item> for S
+where
+    S: Source + Send + Sync,
+{
+    #[inline]
+    fn items(&self) -> Result<&[S::Item]> {
+        Ok(self.all()?.data())
+    }
+}
+
+mod options;
+pub use options::{AccessHint, LockPolicy, SourceOptions};
+
 mod vec_source;
 pub use vec_source::VecSource;
 
 mod segment_like;
 pub use segment_like::*;
+
+mod chained;
+pub use chained::{ChainedSegment, ChainedSource};
+
+mod sparse;
+pub use sparse::{Region, SparseSource};
 #[cfg(feature = "with-bytes")]
 mod bytes_source;
 
@@ -100,7 +146,19 @@ pub use bytes_source::BytesSource;
 #[cfg(feature = "memmap")]
 mod mmap;
 #[cfg(feature = "memmap")]
-pub use mmap::MappedFileSource;
+pub use mmap::{
+    MappedFileSource, MappedFileSourceMut, WindowedMappedFileSource, DEFAULT_MAP_WINDOW_SIZE,
+};
+
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "std")]
+pub use stream::{StreamSource, Window, DEFAULT_MAX_PAGES, DEFAULT_PAGE_SIZE};
+
+#[cfg(feature = "async")]
+mod async_stream;
+#[cfg(feature = "async")]
+pub use async_stream::AsyncStreamSource;
 
 /// Sources own their own data and are used to generate [`Segment`]s. The following sources are
 /// included with segsource (although others can be implemented):
@@ -110,7 +168,19 @@ pub use mmap::MappedFileSource;
 /// 2. [`BytesSource`]: A source that uses a `Bytes` object from the wonderful `bytes` crate to
 ///    store its data. This source can only use `u8`s as its item. Requires the `bytes` feature.
 /// 3. [`MappedFileSource`]: A source that stores its data using a memory mapped file. This source
-///    can only use `u8`s as its item. Requires the `mmap` feature.
+///    can only use `u8`s as its item. Requires the `mmap` feature. A [`MappedFileSourceMut`] can
+///    be used instead to change the file in place.
+///
+/// Several sources can also be joined into one contiguous offset space using a [`ChainedSource`],
+/// or mapped at arbitrary addresses (with holes between them) using a [`SparseSource`].
+///
+/// Data that can't be loaded up front can instead be read lazily using a [`StreamSource`], which
+/// hands out [`Window`]s rather than implementing this trait (or, with the `async` feature, an
+/// [`AsyncStreamSource`]). Likewise, files that are too large to map all at once can be mapped a
+/// part at a time using a [`WindowedMappedFileSource`].
+///
+/// File based sources are configured using [`SourceOptions`] (e.g. to change how a memory mapped
+/// file is locked), which the other constructors are shorthand for.
 ///
 /// When a [`Source`] creates a new [`Segment`], that segment will have the same initial offset and
 /// (if applicable) the same endidness as the source.
@@ -271,6 +341,15 @@ pub trait U8Source: Source<Item = u8> {
         endidness: Endidness,
     ) -> Result<Self>;
 
+    #[cfg(feature = "std")]
+    /// Creates a new source using the the provided file and [`SourceOptions`]. By default, only
+    /// the options' initial offset and endidness are used; sources that support the other options
+    /// override this.
+    #[inline]
+    fn from_file_with_options<P: AsRef<Path>>(path: P, options: &SourceOptions) -> Result<Self> {
+        Self::from_file_with_offset(path, options.initial_offset(), options.endidness())
+    }
+
     #[cfg(feature = "with-bytes")]
     /// Creates a new source using the the provided Bytes and [`Endidness`].
     #[inline]
@@ -308,4 +387,13 @@ pub trait AsyncU8Source: U8Source {
     ) -> Result<Self>
     where
         P: AsRef<Path> + Sync + Send;
+
+    /// An async version of [`U8Source::from_file_with_options`].
+    #[inline]
+    async fn from_file_with_options_async<P>(path: P, options: &SourceOptions) -> Result<Self>
+    where
+        P: AsRef<Path> + Sync + Send,
+    {
+        Self::from_file_with_offset_async(path, options.initial_offset(), options.endidness()).await
+    }
 }
@@ -0,0 +1,174 @@
+use crate::Endidness;
+#[cfg(feature = "std")]
+use crate::{Result, U8Source};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+/// How a file is locked while a source is using it. Like the locks themselves (which are only
+/// **advisory**), this is only used by sources that keep their file open (i.e. the memory mapped
+/// ones).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockPolicy {
+    /// The file isn't locked.
+    None,
+    /// A shared lock. If another process holds an exclusive lock, an error is returned.
+    Shared,
+    /// An exclusive lock. If another process holds any lock, an error is returned.
+    Exclusive,
+    /// A shared lock, waiting for any exclusive lock held by another process to be released.
+    SharedBlocking,
+    /// An exclusive lock, waiting for any lock held by another process to be released.
+    ExclusiveBlocking,
+}
+
+/// A hint about how a memory mapped file will be accessed, which is passed on to the OS (via
+/// `madvise`) so that it can read ahead (or not) accordingly. Hints are only given on Unix-like
+/// platforms, and since they're only hints, failing to give one isn't an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessHint {
+    /// No hint is given.
+    Normal,
+    /// The data will be read from start to end.
+    Sequential,
+    /// The data will be read in no particular order, so reading ahead isn't useful.
+    Random,
+    /// All of the data will be needed soon, so it should be read ahead.
+    WillNeed,
+}
+
+impl Default for AccessHint {
+    #[inline]
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// Options for creating a source, built up via its `with_*` methods. Options that don't apply to a
+/// source are ignored by it.
+///
+/// ```
+/// # use segsource::{Endidness, Source as _, SourceOptions, VecSource};
+/// # let path = std::env::temp_dir().join(format!("segsource-doc-{}", std::process::id()));
+/// # std::fs::write(&path, [1, 2, 3, 4]).unwrap();
+/// let source: VecSource<u8> = SourceOptions::new()
+///     .with_initial_offset(0x100)
+///     .with_endidness(Endidness::Big)
+///     .open(&path)
+///     .unwrap();
+/// assert_eq!(source.all().unwrap().u16_at(0x102).unwrap(), 0x0304);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SourceOptions {
+    initial_offset: usize,
+    endidness: Endidness,
+    lock_policy: Option<LockPolicy>,
+    access_hint: AccessHint,
+    populate: bool,
+    buffer_size: Option<usize>,
+}
+
+impl SourceOptions {
+    /// Creates options with an initial offset of zero, the default endidness, and each source's
+    /// default behavior for everything else.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the initial offset of the source.
+    #[inline]
+    pub fn with_initial_offset(mut self, initial_offset: usize) -> Self {
+        self.initial_offset = initial_offset;
+        self
+    }
+
+    /// Sets the endidness of the source.
+    #[inline]
+    pub fn with_endidness(mut self, endidness: Endidness) -> Self {
+        self.endidness = endidness;
+        self
+    }
+
+    /// Sets how the file is locked. See [`LockPolicy`] for more details.
+    #[inline]
+    pub fn with_lock_policy(mut self, lock_policy: LockPolicy) -> Self {
+        self.lock_policy = Some(lock_policy);
+        self
+    }
+
+    /// Sets the hint given about how a memory mapped file will be accessed. See [`AccessHint`] for
+    /// more details.
+    #[inline]
+    pub fn with_access_hint(mut self, access_hint: AccessHint) -> Self {
+        self.access_hint = access_hint;
+        self
+    }
+
+    /// Sets whether or not a memory mapped file is read into memory (i.e. prefaulted) when it's
+    /// mapped instead of as it's accessed. This is only supported on Linux.
+    #[inline]
+    pub fn with_populate(mut self, populate: bool) -> Self {
+        self.populate = populate;
+        self
+    }
+
+    /// Sets how many bytes are read (or mapped) at a time. This is the capacity of the buffer a
+    /// [`crate::BytesSource`] reads its file with, the page size of a [`crate::StreamSource`] (or
+    /// a [`crate::AsyncStreamSource`]), and the window size of a
+    /// [`crate::WindowedMappedFileSource`].
+    ///
+    /// Each of those sources returns an error when it's created if the size is zero.
+    #[inline]
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// The initial offset of the source.
+    #[inline]
+    pub fn initial_offset(&self) -> usize {
+        self.initial_offset
+    }
+
+    /// The endidness of the source.
+    #[inline]
+    pub fn endidness(&self) -> Endidness {
+        self.endidness
+    }
+
+    /// How the file is locked, or `None` if the source should use its default (a
+    /// [`LockPolicy::Shared`] lock for a [`crate::MappedFileSource`] and a
+    /// [`LockPolicy::Exclusive`] one for a [`crate::MappedFileSourceMut`]).
+    #[inline]
+    pub fn lock_policy(&self) -> Option<LockPolicy> {
+        self.lock_policy
+    }
+
+    /// The hint given about how a memory mapped file will be accessed.
+    #[inline]
+    pub fn access_hint(&self) -> AccessHint {
+        self.access_hint
+    }
+
+    /// Whether or not a memory mapped file is read into memory when it's mapped.
+    #[inline]
+    pub fn populate(&self) -> bool {
+        self.populate
+    }
+
+    /// How many bytes are read (or mapped) at a time, or `None` if the source should use its
+    /// default.
+    #[inline]
+    pub fn buffer_size(&self) -> Option<usize> {
+        self.buffer_size
+    }
+
+    /// Creates a new source from the provided file using these options. This is the same as
+    /// calling [`U8Source::from_file_with_options`].
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn open<S: U8Source, P: AsRef<Path>>(&self, path: P) -> Result<S> {
+        S::from_file_with_options(path, self)
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "std")]
+use crate::SourceOptions;
 use crate::{Endidness, Result, Segment, Source, U8Source};
 #[cfg(not(feature = "std"))]
 use alloc::{boxed::Box, vec::Vec};
@@ -162,4 +164,10 @@ where
     ) -> Result<Self> {
         Self::new(S::from_file_with_offset(path, initial_offset, endidness)?)
     }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn from_file_with_options<P: AsRef<Path>>(path: P, options: &SourceOptions) -> Result<Self> {
+        Self::new(S::from_file_with_options(path, options)?)
+    }
 }
@@ -0,0 +1,273 @@
+use super::{ChainedSegment, ErasedSource};
+use crate::{
+    error::{Error, Result},
+    Endidness, Source,
+};
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, format, vec::Vec};
+
+/// Information about one of the regions in a [`SparseSource`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+    index: usize,
+    base: usize,
+    size: usize,
+}
+
+impl Region {
+    /// The region's index in the [`SparseSource`] (regions are ordered by their base address).
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The address of the region's first item.
+    #[inline]
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// The number of items in the region.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The address right after the region's last item.
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.base + self.size
+    }
+
+    /// Whether or not the provided address is in the region.
+    #[inline]
+    pub fn contains(&self, address: usize) -> bool {
+        self.base <= address && address < self.end()
+    }
+}
+
+struct MappedRegion<I> {
+    base: usize,
+    size: usize,
+    source: Box<dyn ErasedSource<I>>,
+}
+
+/// An address space made up of several [`Source`]s (which don't need to be the same type), each
+/// mapped at its own base address, with unmapped holes between them. This is useful for emulators
+/// and for things like ELF and PE loaders.
+///
+/// Where an initial offset lets a single source start at an arbitrary address, a [`SparseSource`]
+/// can have any number of regions. Each source's own initial offset is ignored in favor of the
+/// base address it's mapped at. Reading any address that isn't in a region returns an
+/// [`Error::Unmapped`]. Reads that are entirely within one region are borrowed from it, while reads
+/// that run from one region into another one right after it are stitched together (see
+/// [`ChainedSegment`]).
+///
+/// ```
+/// # use segsource::{Endidness, Error, SparseSource, Source as _, U8Source as _, VecSource};
+/// let mut memory = SparseSource::new(Endidness::Little);
+/// memory.map(0x1000, VecSource::from_u8_slice(&[1, 0, 0, 0], Endidness::Big).unwrap()).unwrap();
+/// memory.map(0x4000, VecSource::from_u8_slice(&[2, 0], Endidness::Big).unwrap()).unwrap();
+/// assert_eq!(memory.get_n(0x1000, 4).unwrap().segment().next_u32().unwrap(), 1);
+/// assert_eq!(memory.region_at(0x4001).unwrap().base(), 0x4000);
+/// assert!(matches!(memory.get_n(0x2000, 1), Err(Error::Unmapped { offset: 0x2000 })));
+/// assert!(matches!(memory.get_n(0x4000, 4), Err(Error::Unmapped { offset: 0x4002 })));
+/// ```
+pub struct SparseSource<I> {
+    // Always sorted by base address and never overlapping.
+    regions: Vec<MappedRegion<I>>,
+    endidness: Endidness,
+}
+
+impl<I> SparseSource<I> {
+    /// Creates a new [`SparseSource`] with nothing mapped.
+    pub fn new(endidness: Endidness) -> Self {
+        Self {
+            regions: Vec::new(),
+            endidness,
+        }
+    }
+
+    /// Maps the provided source at the provided base address. Returns an error if it would
+    /// overlap with a region that's already mapped, or if it would end past the highest address.
+    pub fn map<S>(&mut self, base: usize, source: S) -> Result<()>
+    where
+        S: Source<Item = I> + Send + Sync + 'static,
+    {
+        let size = source.items()?.len();
+        let end = base
+            .checked_add(size)
+            .ok_or(Error::OffsetTooLarge { offset: usize::MAX })?;
+        let idx = self.regions.partition_point(|region| region.base < base);
+        let overlaps_prev = self.regions[..idx]
+            .iter()
+            .rev()
+            .find(|region| region.size > 0)
+            .is_some_and(|region| region.base + region.size > base);
+        let overlaps_next = self.regions[idx..]
+            .iter()
+            .find(|region| region.size > 0)
+            .is_some_and(|region| region.base < end);
+        if size > 0 && (overlaps_prev || overlaps_next) {
+            return Err(Error::Other {
+                message: format!(
+                    "A region from 0x{:x} to 0x{:x} would overlap an existing region.",
+                    base, end
+                ),
+            });
+        }
+        self.regions.insert(
+            idx,
+            MappedRegion {
+                base,
+                size,
+                source: Box::new(source),
+            },
+        );
+        Ok(())
+    }
+
+    /// Removes the region with the provided index, returning its information.
+    pub fn unmap(&mut self, index: usize) -> Option<Region> {
+        if index < self.regions.len() {
+            let region = self.region(index);
+            self.regions.remove(index);
+            Some(region)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn region(&self, index: usize) -> Region {
+        let MappedRegion { base, size, .. } = self.regions[index];
+        Region { index, base, size }
+    }
+
+    /// Finds the region the provided address is in (if any).
+    pub fn region_at(&self, address: usize) -> Option<Region> {
+        let end = self
+            .regions
+            .partition_point(|region| region.base <= address);
+        // Empty regions are skipped, since they don't contain anything.
+        let idx = self.regions[..end]
+            .iter()
+            .rposition(|region| region.size > 0)?;
+        Some(self.region(idx)).filter(|region| region.contains(address))
+    }
+
+    /// An iterator over all of the regions, ordered by their base addresses.
+    pub fn regions(&self) -> impl Iterator<Item = Region> + '_ {
+        (0..self.regions.len()).map(move |idx| self.region(idx))
+    }
+
+    /// The number of regions.
+    #[inline]
+    pub fn num_regions(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Whether or not the provided address is in a region.
+    #[inline]
+    pub fn is_mapped(&self, address: usize) -> bool {
+        self.region_at(address).is_some()
+    }
+
+    /// Checks to make sure that the provided address is mapped. If it is, then an `Ok(())` will be
+    /// returned. Otherwise, an [`Error::Unmapped`] will be returned.
+    pub fn validate_offset(&self, address: usize) -> Result<()> {
+        if self.is_mapped(address) {
+            Ok(())
+        } else {
+            Err(Error::Unmapped { offset: address })
+        }
+    }
+
+    /// The total number of mapped items.
+    pub fn size(&self) -> usize {
+        self.regions.iter().map(|region| region.size).sum()
+    }
+
+    /// The lowest mapped address (if anything is mapped).
+    pub fn lower_offset_limit(&self) -> Option<usize> {
+        self.regions()
+            .find(|region| region.size > 0)
+            .map(|r| r.base)
+    }
+
+    /// The address right after the highest mapped address (if anything is mapped).
+    pub fn upper_offset_limit(&self) -> Option<usize> {
+        self.regions()
+            .filter(|region| region.size > 0)
+            .last()
+            .map(|r| r.end())
+    }
+
+    /// The endidness used for all [`crate::Segment`]s created from the source, regardless of the
+    /// endidness of the individual regions.
+    #[inline]
+    pub fn endidness(&self) -> Endidness {
+        self.endidness
+    }
+
+    /// Changes the endidness. This does **not** change the endidness for any
+    /// [`ChainedSegment`]s that have already been created.
+    #[inline]
+    pub fn change_endidness(&mut self, endidness: Endidness) {
+        self.endidness = endidness
+    }
+}
+
+impl<I: Clone> SparseSource<I> {
+    /// Gets the data from the start address (inclusive) to the end address (exclusive). Every
+    /// address in between must be mapped.
+    pub fn segment(&self, start: usize, end: usize) -> Result<ChainedSegment<'_, I>> {
+        if end < start {
+            return Err(Error::OffsetTooSmall { offset: end });
+        } else if start == end {
+            return Ok(ChainedSegment::borrowed(&[], start, self.endidness));
+        }
+        let mut region = self
+            .region_at(start)
+            .ok_or(Error::Unmapped { offset: start })?;
+        let items = self.regions[region.index].source.items()?;
+        if end <= region.end() {
+            return Ok(ChainedSegment::borrowed(
+                &items[start - region.base..end - region.base],
+                start,
+                self.endidness,
+            ));
+        }
+        let mut buffer = Vec::with_capacity(end - start);
+        buffer.extend_from_slice(&items[start - region.base..]);
+        while region.end() < end {
+            let next_address = region.end();
+            region = self.region_at(next_address).ok_or(Error::Unmapped {
+                offset: next_address,
+            })?;
+            let items = self.regions[region.index].source.items()?;
+            buffer.extend_from_slice(&items[..end.min(region.end()) - region.base]);
+        }
+        Ok(ChainedSegment::stitched(buffer, start, self.endidness))
+    }
+
+    /// Gets n items, starting at the given address.
+    #[inline]
+    pub fn get_n(&self, address: usize, num_items: usize) -> Result<ChainedSegment<'_, I>> {
+        let end = address
+            .checked_add(num_items)
+            .ok_or(Error::OffsetTooLarge { offset: usize::MAX })?;
+        self.segment(address, end)
+    }
+
+    /// Gets all of the data in the region with the provided index.
+    pub fn region_data(&self, index: usize) -> Option<Result<ChainedSegment<'_, I>>> {
+        let region = self.regions.get(index)?;
+        Some(
+            region
+                .source
+                .items()
+                .map(|items| ChainedSegment::borrowed(items, region.base, self.endidness)),
+        )
+    }
+}
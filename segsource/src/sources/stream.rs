@@ -0,0 +1,524 @@
+use crate::{
+    error::{Error, Result},
+    Endidness, Segment, SourceOptions,
+};
+use core::convert::TryFrom;
+#[cfg(feature = "memmap")]
+use memmap2::Mmap;
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, Read, Seek, SeekFrom},
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+/// The default number of bytes in each page read by a [`StreamSource`].
+pub const DEFAULT_PAGE_SIZE: usize = 4096;
+/// The default maximum number of pages a [`StreamSource`] keeps cached.
+pub const DEFAULT_MAX_PAGES: usize = 64;
+
+/// An LRU cache of fixed-size pages. This doesn't do any reading itself so that it can be shared
+/// by the sync and async streaming sources.
+pub(super) struct PageCache {
+    pages: HashMap<usize, Arc<[u8]>>,
+    // Page numbers from the least to the most recently used.
+    recency: VecDeque<usize>,
+    page_size: usize,
+    max_pages: usize,
+}
+
+impl PageCache {
+    pub(super) fn new(page_size: usize, max_pages: usize) -> Result<Self> {
+        if page_size == 0 {
+            return Err(Error::Other {
+                message: "The page size must be greater than zero.".into(),
+            });
+        }
+        if max_pages == 0 {
+            return Err(Error::Other {
+                message: "At least one page must be cacheable.".into(),
+            });
+        }
+        Ok(Self {
+            pages: HashMap::new(),
+            recency: VecDeque::new(),
+            page_size,
+            max_pages,
+        })
+    }
+
+    fn touch(&mut self, page_num: usize) {
+        if let Some(idx) = self.recency.iter().position(|&num| num == page_num) {
+            self.recency.remove(idx);
+        }
+        self.recency.push_back(page_num);
+    }
+
+    /// Gets a cached page, marking it as the most recently used.
+    pub(super) fn get(&mut self, page_num: usize) -> Option<Arc<[u8]>> {
+        let page = self.pages.get(&page_num).cloned()?;
+        self.touch(page_num);
+        Some(page)
+    }
+
+    /// Caches a page, evicting the least recently used one if the cache is full.
+    pub(super) fn insert(&mut self, page_num: usize, page: Arc<[u8]>) {
+        if self.pages.len() >= self.max_pages {
+            if let Some(evicted) = self.recency.pop_front() {
+                self.pages.remove(&evicted);
+            }
+        }
+        self.pages.insert(page_num, page);
+        self.touch(page_num);
+    }
+
+    /// The position and length of the provided page in data of the provided size.
+    #[inline]
+    pub(super) fn page_span(&self, page_num: usize, size: usize) -> (usize, usize) {
+        let start = page_num * self.page_size;
+        (start, self.page_size.min(size - start))
+    }
+
+    /// The numbers of the pages needed for the data from start_pos to end_pos.
+    pub(super) fn page_nums(&self, start_pos: usize, end_pos: usize) -> Range<usize> {
+        if start_pos == end_pos {
+            0..0
+        } else {
+            start_pos / self.page_size..(end_pos - 1) / self.page_size + 1
+        }
+    }
+
+    #[inline]
+    pub(super) fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    #[inline]
+    pub(super) fn max_pages(&self) -> usize {
+        self.max_pages
+    }
+
+    #[inline]
+    pub(super) fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.pages.clear();
+        self.recency.clear();
+    }
+}
+
+/// Fills the buffer from the reader, returning how many bytes were read (which is only less than
+/// the buffer's length if the reader ran out of data).
+pub(super) fn fill_from<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(filled)
+}
+
+/// Turns a page that was supposed to be read into the page itself, or an error if the reader ran
+/// out of data.
+pub(super) fn finish_page(buffer: Vec<u8>, filled: usize, start: usize) -> Result<Arc<[u8]>> {
+    if filled < buffer.len() {
+        Err(Error::NotEnoughData {
+            requested: buffer.len(),
+            left: filled,
+            offset: start,
+        })
+    } else {
+        Ok(buffer.into())
+    }
+}
+
+/// A [`Window`] is either part of a single cached page, a copy of data that spanned several
+/// pages, or part of a memory mapped file.
+#[derive(Clone)]
+enum WindowData {
+    Page {
+        page: Arc<[u8]>,
+        start: usize,
+        end: usize,
+    },
+    Owned(Arc<[u8]>),
+    #[cfg(feature = "memmap")]
+    Mapped {
+        map: Arc<Mmap>,
+        start: usize,
+        end: usize,
+    },
+}
+
+/// Data handed out by a [`StreamSource`] (or a [`crate::WindowedMappedFileSource`]). A window keeps
+/// its data alive even after the page it came from has been evicted from the source's cache (or
+/// the file has been remapped), and [`Segment`]s are created from it via [`Window::segment`].
+#[derive(Clone)]
+pub struct Window {
+    data: WindowData,
+    initial_offset: u64,
+    endidness: Endidness,
+}
+
+impl Window {
+    /// Creates a [`Segment`] over the window's data. The segment has the same initial offset and
+    /// endidness as the window.
+    ///
+    /// Since a [`Segment`]'s offsets are `usize`s, a window from a
+    /// [`crate::WindowedMappedFileSource`] that starts past `usize::MAX` creates a segment whose
+    /// offsets are relative to the start of the window instead (see [`Window::relative_segment`]).
+    #[inline]
+    pub fn segment(&self) -> Segment<'_, u8> {
+        match usize::try_from(self.initial_offset) {
+            Ok(initial_offset) => {
+                Segment::with_offset_and_endidness(self.as_ref(), initial_offset, self.endidness)
+            }
+            Err(_) => self.relative_segment(),
+        }
+    }
+
+    /// Creates a [`Segment`] over the window's data whose offsets are relative to the start of the
+    /// window (i.e. its initial offset is 0). The segment has the same endidness as the window.
+    #[inline]
+    pub fn relative_segment(&self) -> Segment<'_, u8> {
+        Segment::with_offset_and_endidness(self.as_ref(), 0, self.endidness)
+    }
+
+    /// The offset of the first byte in the window.
+    #[inline]
+    pub fn initial_offset(&self) -> u64 {
+        self.initial_offset
+    }
+
+    /// The endidness of the window.
+    #[inline]
+    pub fn endidness(&self) -> Endidness {
+        self.endidness
+    }
+
+    /// The number of bytes in the window.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.as_ref().len()
+    }
+
+    /// Whether or not the window is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether or not the window's data had to be copied (because it spanned more than one page)
+    /// instead of sharing a cached page.
+    #[inline]
+    pub fn is_copy(&self) -> bool {
+        matches!(self.data, WindowData::Owned(_))
+    }
+
+    /// Creates a window from the data from start_pos to end_pos of a memory mapped file's data.
+    #[cfg(feature = "memmap")]
+    pub(super) fn from_map(
+        map: Arc<Mmap>,
+        start_pos: usize,
+        end_pos: usize,
+        initial_offset: u64,
+        endidness: Endidness,
+    ) -> Self {
+        Self {
+            data: WindowData::Mapped {
+                map,
+                start: start_pos,
+                end: end_pos,
+            },
+            initial_offset,
+            endidness,
+        }
+    }
+
+    /// Creates a window from the data from start_pos to end_pos, using the provided pages (which
+    /// must be every page that data is in).
+    pub(super) fn from_pages(
+        pages: Vec<Arc<[u8]>>,
+        page_size: usize,
+        start_pos: usize,
+        end_pos: usize,
+        initial_offset: u64,
+        endidness: Endidness,
+    ) -> Self {
+        let first_page_start = start_pos - start_pos % page_size;
+        let data = match pages.len() {
+            0 => WindowData::Owned(Arc::from(&[][..])),
+            1 => WindowData::Page {
+                page: pages.into_iter().next().unwrap(),
+                start: start_pos - first_page_start,
+                end: end_pos - first_page_start,
+            },
+            _ => {
+                let mut buffer = Vec::with_capacity(end_pos - start_pos);
+                let mut page_start = first_page_start;
+                for page in pages {
+                    let from = start_pos.max(page_start) - page_start;
+                    let to = (end_pos - page_start).min(page.len());
+                    buffer.extend_from_slice(&page[from..to]);
+                    page_start += page_size;
+                }
+                WindowData::Owned(buffer.into())
+            }
+        };
+        Self {
+            data,
+            initial_offset,
+            endidness,
+        }
+    }
+}
+
+impl AsRef<[u8]> for Window {
+    fn as_ref(&self) -> &[u8] {
+        match &self.data {
+            WindowData::Page { page, start, end } => &page[*start..*end],
+            WindowData::Owned(data) => data,
+            #[cfg(feature = "memmap")]
+            WindowData::Mapped { map, start, end } => &map[*start..*end],
+        }
+    }
+}
+
+/// A source that lazily reads its data from anything that implements `Read` and `Seek`.
+///
+/// Unlike the other sources, a [`StreamSource`] doesn't load its data up front. Instead, it reads
+/// fixed-size pages as they're needed and keeps the most recently used ones in a bounded cache.
+/// Because of this, it hands out [`Window`]s (which [`Segment`]s can then be created from) instead
+/// of implementing [`crate::Source`]. A window that fits in a single page shares that page with the
+/// cache; one that spans several pages is copied into its own buffer.
+///
+/// ```
+/// # use segsource::{Endidness, StreamSource};
+/// # use std::io::Cursor;
+/// let data: Vec<u8> = (0..=255).collect();
+/// let source = StreamSource::with_cache(Cursor::new(data), 0x100, Endidness::Big, 16, 4).unwrap();
+/// let window = source.get_n(0x110, 4).unwrap();
+/// assert!(!window.is_copy());
+/// let segment = window.segment();
+/// assert_eq!(segment.u32_at(0x110).unwrap(), 0x10111213);
+/// let window = source.window(0x11e, 0x122).unwrap();
+/// assert!(window.is_copy());
+/// assert_eq!(window.as_ref(), [0x1e, 0x1f, 0x20, 0x21]);
+/// ```
+pub struct StreamSource<R> {
+    reader: Mutex<R>,
+    cache: Mutex<PageCache>,
+    initial_offset: usize,
+    size: usize,
+    endidness: Endidness,
+}
+
+impl<R: Read + Seek> StreamSource<R> {
+    /// Creates a new source that uses [`DEFAULT_PAGE_SIZE`] byte pages and caches at most
+    /// [`DEFAULT_MAX_PAGES`] of them.
+    #[inline]
+    pub fn new(reader: R, initial_offset: usize, endidness: Endidness) -> Result<Self> {
+        Self::with_options(
+            reader,
+            &SourceOptions::new()
+                .with_initial_offset(initial_offset)
+                .with_endidness(endidness),
+        )
+    }
+
+    /// Creates a new source using the provided [`SourceOptions`]. The page size is the options'
+    /// buffer size (or [`DEFAULT_PAGE_SIZE`]), and at most [`DEFAULT_MAX_PAGES`] pages are cached.
+    #[inline]
+    pub fn with_options(reader: R, options: &SourceOptions) -> Result<Self> {
+        Self::with_cache(
+            reader,
+            options.initial_offset(),
+            options.endidness(),
+            options.buffer_size().unwrap_or(DEFAULT_PAGE_SIZE),
+            DEFAULT_MAX_PAGES,
+        )
+    }
+
+    /// Creates a new source that uses pages of the provided size and caches at most the provided
+    /// number of them. The size of the data is found by seeking to the end of the reader.
+    ///
+    /// Returns an error if either the page size or the maximum number of pages is zero.
+    pub fn with_cache(
+        mut reader: R,
+        initial_offset: usize,
+        endidness: Endidness,
+        page_size: usize,
+        max_pages: usize,
+    ) -> Result<Self> {
+        let cache = PageCache::new(page_size, max_pages)?;
+        let size = reader.seek(SeekFrom::End(0))? as usize;
+        Ok(Self {
+            reader: Mutex::new(reader),
+            cache: Mutex::new(cache),
+            initial_offset,
+            size,
+            endidness,
+        })
+    }
+
+    /// Gets the page with the provided number, reading it if it's not already cached.
+    fn page(&self, cache: &mut PageCache, page_num: usize) -> Result<Arc<[u8]>> {
+        if let Some(page) = cache.get(page_num) {
+            return Ok(page);
+        }
+        let (start, len) = cache.page_span(page_num, self.size);
+        let mut buffer = vec![0; len];
+        let filled = {
+            let mut reader = self.reader.lock().unwrap();
+            reader.seek(SeekFrom::Start(start as u64))?;
+            fill_from(&mut *reader, &mut buffer)?
+        };
+        let page = finish_page(buffer, filled, start)?;
+        cache.insert(page_num, Arc::clone(&page));
+        Ok(page)
+    }
+
+    /// Creates a [`Window`] from the start offset (inclusive) to the end offset (exclusive).
+    pub fn window(&self, start: usize, end: usize) -> Result<Window> {
+        self.validate_offset(start)?;
+        self.validate_offset(end)?;
+        if end < start {
+            return Err(Error::OffsetTooSmall { offset: end });
+        }
+        let (start_pos, end_pos) = (start - self.initial_offset, end - self.initial_offset);
+        let mut cache = self.cache.lock().unwrap();
+        let pages = cache
+            .page_nums(start_pos, end_pos)
+            .map(|page_num| self.page(&mut cache, page_num))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Window::from_pages(
+            pages,
+            cache.page_size(),
+            start_pos,
+            end_pos,
+            start as u64,
+            self.endidness,
+        ))
+    }
+
+    /// Gets a [`Window`] of n items, starting at the given offset.
+    #[inline]
+    pub fn get_n(&self, offset: usize, num_items: usize) -> Result<Window> {
+        let end = offset
+            .checked_add(num_items)
+            .ok_or(Error::OffsetTooLarge { offset: usize::MAX })?;
+        self.window(offset, end)
+    }
+
+    /// Gets a [`Window`] containing all data in the source. Unless all of the data fits in a
+    /// single page, this will copy all of it.
+    #[inline]
+    pub fn all(&self) -> Result<Window> {
+        self.window(self.lower_offset_limit(), self.upper_offset_limit())
+    }
+
+    /// Gets a [`Window`] of all items in the source before the provided offset (exclusive).
+    #[inline]
+    pub fn all_before(&self, offset: usize) -> Result<Window> {
+        self.window(self.lower_offset_limit(), offset)
+    }
+
+    /// Gets a [`Window`] of all items in the source after the provided offset (inclusive).
+    #[inline]
+    pub fn all_after(&self, offset: usize) -> Result<Window> {
+        self.window(offset, self.upper_offset_limit())
+    }
+
+    /// Checks to make sure that the provided offset is valid. If it is, then an `Ok(())` will be
+    /// returned. Otherwise, the appropriate error will be returned.
+    pub fn validate_offset(&self, offset: usize) -> Result<()> {
+        if offset < self.lower_offset_limit() {
+            Err(Error::OffsetTooSmall { offset })
+        } else if offset > self.upper_offset_limit() {
+            Err(Error::OffsetTooLarge { offset })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The number of pages currently cached.
+    #[inline]
+    pub fn cached_pages(&self) -> usize {
+        self.cache.lock().unwrap().len()
+    }
+
+    /// Removes all pages from the cache. Any [`Window`]s that have already been created are
+    /// unaffected.
+    pub fn clear_cache(&self) {
+        self.cache.lock().unwrap().clear()
+    }
+
+    /// Consumes the source, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner().unwrap()
+    }
+}
+
+impl<R> StreamSource<R> {
+    /// The amount of data in the source.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of bytes in each page.
+    #[inline]
+    pub fn page_size(&self) -> usize {
+        self.cache.lock().unwrap().page_size()
+    }
+
+    /// The maximum number of pages that will be cached.
+    #[inline]
+    pub fn max_pages(&self) -> usize {
+        self.cache.lock().unwrap().max_pages()
+    }
+
+    /// The initial offset of the source.
+    #[inline]
+    pub fn initial_offset(&self) -> usize {
+        self.initial_offset
+    }
+
+    /// Changes the initial offset. This does **not** change the initial offset for any
+    /// [`Window`]s that have already been created.
+    #[inline]
+    pub fn change_initial_offset(&mut self, offset: usize) {
+        self.initial_offset = offset
+    }
+
+    /// The endidness of the source.
+    #[inline]
+    pub fn endidness(&self) -> Endidness {
+        self.endidness
+    }
+
+    /// Changes the default endidness. This does **not** change the endidness for any [`Window`]s
+    /// that have already been created.
+    #[inline]
+    pub fn change_endidness(&mut self, endidness: Endidness) {
+        self.endidness = endidness
+    }
+
+    /// The lowest valid offset that can be requested.
+    #[inline]
+    pub fn lower_offset_limit(&self) -> usize {
+        self.initial_offset
+    }
+
+    /// The highest valid offset that can be requested.
+    #[inline]
+    pub fn upper_offset_limit(&self) -> usize {
+        self.size + self.initial_offset
+    }
+}
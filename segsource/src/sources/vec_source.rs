@@ -2,11 +2,14 @@
 use crate::sync::async_u8_vec_from_file;
 #[cfg(feature = "async")]
 use crate::AsyncU8Source;
-use crate::{Endidness, Result, Segment, Source, U8Source};
+#[cfg(feature = "std")]
+use crate::SourceOptions;
+use crate::{Endidness, Error, Result, Segment, SegmentMut, Source, U8Source};
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 #[cfg(feature = "with-bytes")]
 use bytes::Bytes;
+use core::ops::Range;
 #[cfg(feature = "std")]
 use std::{fs, io::Read as _, path::Path};
 
@@ -18,16 +21,60 @@ pub struct VecSource<I: Sync + Send> {
     initial_offset: usize,
     data: Vec<I>,
     endidness: Endidness,
+    compressed_range: Option<Range<usize>>,
 }
 
 impl<I: Sync + Send> VecSource<I> {
     #[inline]
-    fn new(data: Vec<I>, initial_offset: usize, endidness: Endidness) -> Self {
+    pub(crate) fn new(data: Vec<I>, initial_offset: usize, endidness: Endidness) -> Self {
         Self {
             initial_offset,
             data,
             endidness,
+            compressed_range: None,
+        }
+    }
+
+    #[cfg(any(feature = "deflate", feature = "lz4", feature = "zstd"))]
+    #[inline]
+    pub(crate) fn decompressed(
+        data: Vec<I>,
+        endidness: Endidness,
+        compressed_range: Range<usize>,
+    ) -> Self {
+        Self {
+            compressed_range: Some(compressed_range),
+            ..Self::new(data, 0, endidness)
+        }
+    }
+
+    /// If the source was created by decompressing a [`Segment`] (e.g. via
+    /// [`Segment::decompress_zlib`]), the offsets of the compressed data it came from. This can be
+    /// used to report where errors in the decompressed data came from (see
+    /// [`crate::Error::in_compressed`]).
+    #[inline]
+    pub fn compressed_range(&self) -> Option<Range<usize>> {
+        self.compressed_range.clone()
+    }
+
+    /// Creates a [`SegmentMut`] from the start offset (inclusive) to the end offset (exclusive).
+    pub fn segment_mut(&mut self, start: usize, end: usize) -> Result<SegmentMut<'_, I>> {
+        self.validate_offset(start)?;
+        self.validate_offset(end)?;
+        if end < start {
+            return Err(Error::OffsetTooSmall { offset: end });
         }
+        Ok(SegmentMut::new_full(
+            &mut self.data[start - self.initial_offset..end - self.initial_offset],
+            start,
+            0,
+            self.endidness,
+        ))
+    }
+
+    /// Returns a single [`SegmentMut`] containing all data in the source.
+    pub fn all_mut(&mut self) -> Result<SegmentMut<'_, I>> {
+        self.segment_mut(self.lower_offset_limit(), self.upper_offset_limit())
     }
 }
 
@@ -45,11 +92,7 @@ impl<I: Sync + Send> Source for VecSource<I> {
 
     #[inline]
     fn from_vec_with_offset(items: Vec<Self::Item>, initial_offset: usize) -> Result<Self> {
-        Ok(Self {
-            initial_offset,
-            data: items,
-            endidness: Endidness::default(),
-        })
+        Ok(Self::new(items, initial_offset, Endidness::default()))
     }
 
     fn segment(&self, start: usize, end: usize) -> Result<Segment<I>> {
@@ -67,21 +110,21 @@ impl<I: Sync + Send> Source for VecSource<I> {
 
 impl U8Source for VecSource<u8> {
     impl_endidness_items! {}
+    impl_from_file_with_offset! {}
 
     #[cfg(feature = "std")]
-    #[inline]
-    fn from_file_with_offset<P: AsRef<Path>>(
-        path: P,
-        initial_offset: usize,
-        endidness: Endidness,
-    ) -> Result<Self> {
+    fn from_file_with_options<P: AsRef<Path>>(path: P, options: &SourceOptions) -> Result<Self> {
         let md = path.as_ref().metadata()?;
         let mut data = Vec::with_capacity(md.len() as usize);
         {
             let mut file = fs::File::open(path)?;
             file.read_to_end(&mut data)?;
         }
-        Ok(Self::new(data, initial_offset, endidness))
+        Ok(Self::new(
+            data,
+            options.initial_offset(),
+            options.endidness(),
+        ))
     }
 
     #[inline]
@@ -130,19 +173,30 @@ impl U8Source for VecSource<u8> {
 #[cfg(feature = "async")]
 #[async_trait]
 impl AsyncU8Source for VecSource<u8> {
-    #[cfg(feature = "async")]
+    #[inline]
     async fn from_file_with_offset_async<P>(
         path: P,
         initial_offset: usize,
         endidness: Endidness,
     ) -> Result<Self>
+    where
+        P: AsRef<Path> + Sync + Send,
+    {
+        let options = SourceOptions::new()
+            .with_initial_offset(initial_offset)
+            .with_endidness(endidness);
+        Self::from_file_with_options_async(path, &options).await
+    }
+
+    #[cfg(feature = "async")]
+    async fn from_file_with_options_async<P>(path: P, options: &SourceOptions) -> Result<Self>
     where
         P: AsRef<Path> + Sync + Send,
     {
         Ok(Self::new(
             async_u8_vec_from_file(path).await?,
-            initial_offset,
-            endidness,
+            options.initial_offset(),
+            options.endidness(),
         ))
     }
 }
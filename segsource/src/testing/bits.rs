@@ -0,0 +1,100 @@
+use crate::{BitOrder, Error, Result, Segment};
+
+const TEST_BIT_DATA: [u8; 4] = [0b1011_0010, 0b0111_1100, 0b1000_0001, 0xff];
+
+#[test]
+fn msb_first_test() -> Result<()> {
+    let segment = Segment::with_offset(&TEST_BIT_DATA, 10);
+    let bits = segment.bits(BitOrder::MsbFirst);
+    assert_eq!(bits.position(), (10, 0));
+    assert!(bits.next_bool()?);
+    assert_eq!(bits.peek_bits(3)?, 0b011);
+    assert_eq!(bits.next_bits(3)?, 0b011);
+    assert_eq!(bits.position(), (10, 4));
+    assert_eq!(bits.next_bits(8)?, 0b0010_0111);
+    assert_eq!(bits.position(), (11, 4));
+    assert_eq!(bits.next_signed_bits(4)?, -4);
+    assert_eq!(bits.next_bits(16)?, 0b1000_0001_1111_1111);
+    assert!(!bits.has_more());
+    assert_eq!(segment.current_offset(), 10);
+    Ok(())
+}
+
+#[test]
+fn lsb_first_test() -> Result<()> {
+    let segment = Segment::new(&TEST_BIT_DATA);
+    let bits = segment.bits(BitOrder::LsbFirst);
+    assert!(!bits.next_bool()?);
+    assert_eq!(bits.next_bits(3)?, 0b001);
+    assert_eq!(bits.next_bits(8)?, 0b1100_1011);
+    assert_eq!(bits.next_signed_bits(4)?, 0b0111);
+    assert_eq!(bits.peek_signed_bits(2)?, 1);
+    assert_eq!(bits.next_bits(16)?, 0xff81);
+    Ok(())
+}
+
+#[test]
+fn align_test() -> Result<()> {
+    let segment = Segment::with_offset(&TEST_BIT_DATA, 3);
+    segment.move_to(4)?;
+    let bits = segment.bits(BitOrder::MsbFirst);
+    assert_eq!(bits.position(), (4, 0));
+    bits.align_to_byte();
+    assert_eq!(bits.position(), (4, 0));
+    bits.next_bits(1)?;
+    assert!(!bits.is_aligned());
+    bits.align_to_byte();
+    assert_eq!(bits.position(), (5, 0));
+    bits.skip_bits(3)?;
+    let segment = bits.into_segment();
+    assert_eq!(segment.current_offset(), 6);
+    assert_eq!(segment.next_u8()?, 0xff);
+    Ok(())
+}
+
+#[test]
+fn not_enough_data_test() -> Result<()> {
+    let segment = Segment::new(&TEST_BIT_DATA);
+    let bits = segment.bits(BitOrder::MsbFirst);
+    bits.skip_bits(21)?;
+    assert!(matches!(
+        bits.next_bits(12),
+        Err(Error::NotEnoughData {
+            requested: 3,
+            left: 2,
+            offset: 2
+        })
+    ));
+    assert!(matches!(
+        bits.peek_bits(64),
+        Err(Error::NotEnoughData {
+            requested: 9,
+            left: 2,
+            offset: 2
+        })
+    ));
+    assert_eq!(bits.position(), (2, 5));
+    assert_eq!(bits.next_bits(11)?, 0b001_1111_1111);
+    assert!(matches!(bits.next_bool(), Err(Error::NotEnoughData { .. })));
+    Ok(())
+}
+
+#[test]
+fn too_many_bits_test() -> Result<()> {
+    let data = [0; 16];
+    let segment = Segment::new(&data);
+    let bits = segment.bits(BitOrder::MsbFirst);
+    assert!(matches!(bits.next_bits(65), Err(Error::Other { .. })));
+    assert!(matches!(
+        bits.peek_signed_bits(65),
+        Err(Error::Other { .. })
+    ));
+    assert_eq!(bits.next_bits(64)?, 0);
+    bits.skip_bits(3)?;
+    assert!(matches!(
+        bits.skip_bits(usize::MAX),
+        Err(Error::OffsetTooLarge { offset: 8 })
+    ));
+    assert_eq!(bits.position(), (8, 3));
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use crate::{
+    checksum::{
+        Adler32, Checksum, Crc16Arc, Crc16CcittFalse, Crc16Modbus, Crc16Xmodem, Crc32, Xxh32, Xxh64,
+    },
+    Endidness, Error, Result, Segment,
+};
+
+const CHECK: &[u8] = b"123456789";
+const SPAM: &[u8] = b"Nobody inspects the spammish repetition";
+
+#[test]
+fn known_values() {
+    assert_eq!(Crc32::checksum(CHECK), 0xcbf43926);
+    assert_eq!(Crc32::checksum(b""), 0);
+    assert_eq!(Adler32::checksum(b"Wikipedia"), 0x11e60398);
+    assert_eq!(Adler32::checksum(b""), 1);
+    assert_eq!(Crc16Arc::checksum(CHECK), 0xbb3d);
+    assert_eq!(Crc16Modbus::checksum(CHECK), 0x4b37);
+    assert_eq!(Crc16CcittFalse::checksum(CHECK), 0x29b1);
+    assert_eq!(Crc16Xmodem::checksum(CHECK), 0x31c3);
+    assert_eq!(Xxh32::checksum(b""), 0x02cc5d05);
+    assert_eq!(Xxh32::checksum(b"abc"), 0x32d153ff);
+    assert_eq!(Xxh32::checksum(SPAM), 0xe2293b2f);
+    assert_eq!(Xxh64::checksum(b""), 0xef46db3751d8e999);
+    assert_eq!(Xxh64::checksum(b"abc"), 0x44bc2cf5ad770999);
+    assert_eq!(Xxh64::checksum(SPAM), 0xfbcea83c8a378bf1);
+}
+
+#[test]
+fn xxhash_seeds() {
+    let mut hasher = Xxh32::with_seed(1);
+    hasher.update(SPAM);
+    assert_ne!(hasher.finish(), Xxh32::checksum(SPAM));
+    assert_eq!(Xxh32::with_seed(0), Xxh32::default());
+    assert_eq!(Xxh64::with_seed(0), Xxh64::default());
+}
+
+fn assert_streams<C: Checksum>(data: &[u8]) {
+    let expected = C::checksum(data);
+    for split in 0..data.len() {
+        let mut checksum = C::default();
+        for chunk in data[..split].chunks(7) {
+            checksum.update(chunk);
+        }
+        checksum.update(&data[split..]);
+        assert_eq!(checksum.finish(), expected);
+    }
+}
+
+#[test]
+fn streaming() {
+    let data: Vec<u8> = (0..200_u32).map(|i| (i * 31 % 251) as u8).collect();
+    assert_streams::<Crc32>(&data);
+    assert_streams::<Adler32>(&data);
+    assert_streams::<Crc16Arc>(&data);
+    assert_streams::<Crc16CcittFalse>(&data);
+    assert_streams::<Xxh32>(&data);
+    assert_streams::<Xxh64>(&data);
+}
+
+#[test]
+fn adler32_long_runs() {
+    // Long enough that the sums have to be reduced part way through.
+    let data = [0xff; 20_000];
+    let (mut a, mut b) = (1_u64, 0_u64);
+    for byte in data.iter() {
+        a = (a + *byte as u64) % 65521;
+        b = (b + a) % 65521;
+    }
+    assert_eq!(Adler32::checksum(&data) as u64, (b << 16) | a);
+}
+
+#[test]
+fn segment_checksums() -> Result<()> {
+    let mut data = CHECK.to_vec();
+    data.extend_from_slice(&0xcbf43926_u32.to_le_bytes());
+    let segment = Segment::with_offset_and_endidness(&data, 0x100, Endidness::Little);
+    segment.move_to(0x104)?;
+    assert_eq!(segment.checksum_range::<Crc32>(0x100, 0x109)?, 0xcbf43926);
+    assert_eq!(segment.checksum_range::<Crc32>(0x104, 0x104)?, 0);
+    assert_eq!(segment.current_offset(), 0x104);
+    assert_eq!(segment.checksum::<Crc32>(), Crc32::checksum(&data));
+    let stored = segment.u32_at(0x109)?;
+    segment.verify_checksum::<Crc32>(0x100, 0x109, stored, 0x109)?;
+    Ok(())
+}
+
+#[test]
+fn segment_checksum_errors() {
+    let segment = Segment::with_offset_and_endidness(CHECK, 0x100, Endidness::Little);
+    assert!(matches!(
+        segment.checksum_range::<Crc32>(0xff, 0x104),
+        Err(Error::OffsetTooSmall { offset: 0xff })
+    ));
+    assert!(matches!(
+        segment.checksum_range::<Crc32>(0x104, 0x10a),
+        Err(Error::NotEnoughData { offset: 0x104, .. })
+    ));
+    assert!(matches!(
+        segment.checksum_range::<Crc32>(0x104, 0x103),
+        Err(Error::OffsetTooSmall { offset: 0x103 })
+    ));
+    let error = segment
+        .verify_checksum::<Crc16Xmodem>(0x100, 0x109, 0x1234, 0x42)
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        Error::ChecksumMismatch {
+            expected: 0x1234,
+            actual: 0x31c3,
+            offset: 0x42
+        }
+    ));
+    assert_eq!(error.offset(), Some(0x42));
+}
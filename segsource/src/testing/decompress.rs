@@ -0,0 +1,121 @@
+use crate::{Endidness, Error, Result, Segment, Source as _, VecSource};
+#[cfg(any(feature = "deflate", feature = "lz4"))]
+use std::io::Write as _;
+
+const DATA: &[u8] = b"\x00\x00\x00\x2asome data that is repeated, some data that is repeated";
+
+fn check_decompressed(source: &VecSource<u8>, start: usize, end: usize) -> Result<()> {
+    assert_eq!(source.compressed_range(), Some(start..end));
+    assert_eq!(source.initial_offset(), 0);
+    let segment = source.all()?;
+    assert_eq!(segment.as_ref(), DATA);
+    assert_eq!(segment.endidness(), Endidness::Big);
+    assert_eq!(segment.next_u32()?, 0x2a);
+    Ok(())
+}
+
+fn check_invalid(result: Result<VecSource<u8>>) {
+    assert!(matches!(
+        result,
+        Err(Error::Decompression { offset: 0x10, .. })
+    ));
+}
+
+#[test]
+fn not_decompressed() -> Result<()> {
+    assert_eq!(VecSource::from_vec(DATA.to_vec())?.compressed_range(), None);
+    Ok(())
+}
+
+#[test]
+fn in_compressed() {
+    let error = Error::OffsetTooLarge { offset: 4 }.in_compressed(0x10, 0x20);
+    assert!(matches!(
+        error.root_cause(),
+        Error::OffsetTooLarge { offset: 4 }
+    ));
+    assert_eq!(error.offset(), Some(4));
+    assert_eq!(
+        error.to_string(),
+        "An offset of 0x4 is too large. (in data decompressed from 0x10 to 0x20)"
+    );
+}
+
+#[cfg(feature = "deflate")]
+#[test]
+fn deflate() -> Result<()> {
+    use flate2::{
+        write::{DeflateEncoder, GzEncoder, ZlibEncoder},
+        Compression,
+    };
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(DATA)?;
+    let deflate = encoder.finish()?;
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(DATA)?;
+    let zlib = encoder.finish()?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(DATA)?;
+    let gzip = encoder.finish()?;
+
+    let segment = Segment::with_offset_and_endidness(&deflate, 0x10, Endidness::Big);
+    // The current offset doesn't matter.
+    segment.move_by(2)?;
+    let source = segment.decompress_deflate()?;
+    check_decompressed(&source, 0x10, 0x10 + deflate.len())?;
+    let segment = Segment::with_offset_and_endidness(&zlib, 0x10, Endidness::Big);
+    check_decompressed(&segment.decompress_zlib()?, 0x10, 0x10 + zlib.len())?;
+    check_invalid(segment.decompress_gzip());
+    let segment = Segment::with_offset_and_endidness(&gzip, 0x10, Endidness::Big);
+    check_decompressed(&segment.decompress_gzip()?, 0x10, 0x10 + gzip.len())?;
+    check_invalid(segment.decompress_zlib());
+    let source = segment.decompress_gzip_with_max_size(DATA.len())?;
+    check_decompressed(&source, 0x10, 0x10 + gzip.len())?;
+    check_invalid(segment.decompress_gzip_with_max_size(DATA.len() - 1));
+    let segment = Segment::with_offset_and_endidness(&deflate, 0x10, Endidness::Big);
+    check_invalid(segment.decompress_deflate_with_max_size(0));
+    let segment = Segment::with_offset_and_endidness(&zlib, 0x10, Endidness::Big);
+    check_invalid(segment.decompress_zlib_with_max_size(4));
+    Ok(())
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn lz4() -> Result<()> {
+    let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+    encoder.write_all(DATA)?;
+    let frame = encoder.finish().unwrap();
+    let block = lz4_flex::block::compress(DATA);
+
+    let segment = Segment::with_offset_and_endidness(&frame, 0x10, Endidness::Big);
+    check_decompressed(&segment.decompress_lz4()?, 0x10, 0x10 + frame.len())?;
+    let segment = Segment::with_offset_and_endidness(&block, 0x10, Endidness::Big);
+    let source = segment.decompress_lz4_block(DATA.len())?;
+    check_decompressed(&source, 0x10, 0x10 + block.len())?;
+    check_invalid(segment.decompress_lz4());
+    check_invalid(segment.decompress_lz4_block(1));
+    let segment = Segment::with_offset_and_endidness(&frame, 0x10, Endidness::Big);
+    check_decompressed(
+        &segment.decompress_lz4_with_max_size(DATA.len())?,
+        0x10,
+        0x10 + frame.len(),
+    )?;
+    check_invalid(segment.decompress_lz4_with_max_size(DATA.len() - 1));
+    Ok(())
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd() -> Result<()> {
+    // A single frame containing one raw (i.e. uncompressed) block.
+    let mut frame = vec![0x28, 0xb5, 0x2f, 0xfd, 0x20, DATA.len() as u8];
+    frame.extend_from_slice(&((DATA.len() as u32) << 3 | 1).to_le_bytes()[..3]);
+    frame.extend_from_slice(DATA);
+
+    let segment = Segment::with_offset_and_endidness(&frame, 0x10, Endidness::Big);
+    check_decompressed(&segment.decompress_zstd()?, 0x10, 0x10 + frame.len())?;
+    check_invalid(segment.decompress_zstd_with_max_size(DATA.len() - 1));
+    let segment = Segment::with_offset_and_endidness(DATA, 0x10, Endidness::Big);
+    check_invalid(segment.decompress_zstd());
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use crate::{Endidness, Result, Segment, ToSegment as _};
+
+#[test]
+fn middle_endian_reads() -> Result<()> {
+    let data = [
+        0x0b, 0x0a, 0x0d, 0x0c, 0x04, 0x03, 0x02, 0x01, 0x08, 0x07, 0x06, 0x05,
+    ];
+    let segment = Segment::with_endidness(&data, Endidness::Middle);
+    assert_eq!(segment.u8_at(0)?, 0x0b);
+    assert_eq!(segment.u16_at(0)?, 0x0a0b);
+    assert_eq!(segment.next_u32()?, 0x0a0b0c0d);
+    assert_eq!(segment.peek_u64()?, 0x0102030405060708);
+    assert_eq!(segment.i32_at(0)?, 0x0a0b0c0d);
+    assert_eq!(segment.next_f64()?.to_bits(), 0x0102030405060708);
+    Ok(())
+}
+
+#[test]
+fn middle_endian_writes() -> Result<()> {
+    assert_eq!(
+        0x0a0b0c0d_u32.to_bytes_with_endidness(Endidness::Middle)?,
+        [0x0b, 0x0a, 0x0d, 0x0c]
+    );
+    assert_eq!(
+        0x0a0b_u16.to_bytes_with_endidness(Endidness::Middle)?,
+        [0x0b, 0x0a]
+    );
+    let value = f64::from_bits(0x0102030405060708);
+    let bytes = value.to_bytes_with_endidness(Endidness::Middle)?;
+    assert_eq!(bytes, [0x04, 0x03, 0x02, 0x01, 0x08, 0x07, 0x06, 0x05]);
+    let segment = Segment::with_endidness(&bytes, Endidness::Middle);
+    assert_eq!(segment.next_f64()?.to_bits(), value.to_bits());
+    Ok(())
+}
+
+#[test]
+fn set_endidness() -> Result<()> {
+    let data = [0x01, 0x02, 0x01, 0x02, 0x01, 0x02, 0x03, 0x04];
+    let segment = Segment::with_endidness(&data, Endidness::Little);
+    assert_eq!(segment.next_u16()?, 0x0201);
+    segment.set_endidness(Endidness::Big);
+    assert_eq!(segment.endidness(), Endidness::Big);
+    assert_eq!(segment.next_u16()?, 0x0102);
+    let child = segment.clone();
+    segment.set_endidness(Endidness::Little);
+    assert_eq!(child.endidness(), Endidness::Big);
+    assert_eq!(child.u32_at(4)?, 0x01020304);
+    assert_eq!(segment.u32_at(4)?, 0x04030201);
+    assert_eq!(segment.next_n(4)?.endidness(), Endidness::Little);
+    Ok(())
+}
+
+#[test]
+fn per_read_endidness() -> Result<()> {
+    let data = [0x01, 0x02, 0x01, 0x02, 0x3f, 0x80, 0x00, 0x00];
+    let segment = Segment::with_endidness(&data, Endidness::Little);
+    assert_eq!(
+        segment.int_at_with_endidness::<u16>(0, Endidness::Big)?,
+        0x0102
+    );
+    assert_eq!(
+        segment.next_int_with_endidness::<u16>(Endidness::Big)?,
+        0x0102
+    );
+    assert_eq!(
+        segment.next_int_with_endidness::<u16>(Endidness::Little)?,
+        0x0201
+    );
+    assert_eq!(
+        segment.float_at_with_endidness::<f32>(4, Endidness::Big)?,
+        1.0
+    );
+    assert_eq!(
+        segment.next_float_with_endidness::<f32>(Endidness::Big)?,
+        1.0
+    );
+    assert_eq!(segment.current_offset(), 8);
+    assert_eq!(segment.endidness(), Endidness::Little);
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::{Error, PathSegment};
+
+fn not_enough_data() -> Error {
+    Error::NotEnoughData {
+        requested: 4,
+        left: 2,
+        offset: 0x10,
+    }
+}
+
+#[test]
+fn no_context() {
+    let error = not_enough_data();
+    assert!(error.path().is_none());
+    assert!(error.segment_range().is_none());
+    assert!(matches!(error.root_cause(), Error::NotEnoughData { .. }));
+    assert_eq!(error.offset(), Some(0x10));
+    assert_eq!(
+        error.to_string(),
+        "Requested 4 bytes at offset 0x10, but only 2 bytes left."
+    );
+}
+
+#[test]
+fn nested_context() {
+    let error = not_enough_data()
+        .in_field("Entry", "size", 0x0c, 0x12)
+        .at_index(2, 0, 0x20)
+        .in_field("Table", "entries", 0, 0x20);
+    assert_eq!(
+        error.path().unwrap().segments(),
+        [
+            PathSegment::Type("Table"),
+            PathSegment::Field("entries"),
+            PathSegment::Index(2),
+            PathSegment::Type("Entry"),
+            PathSegment::Field("size"),
+        ]
+    );
+    assert_eq!(error.path().unwrap().to_string(), "Table.entries[2].size");
+    assert_eq!(error.segment_range(), Some(0x0c..0x12));
+    assert!(matches!(
+        error.root_cause(),
+        Error::NotEnoughData { offset: 0x10, .. }
+    ));
+    assert_eq!(error.offset(), Some(0x10));
+    assert_eq!(
+        error.to_string(),
+        "Requested 4 bytes at offset 0x10, but only 2 bytes left. (in Table.entries[2].size, \
+         parsed from 0xc to 0x12)"
+    );
+}
+
+#[test]
+fn no_offset() {
+    let error = Error::Other {
+        message: "bad".into(),
+    }
+    .in_field("Thing", "0", 4, 8);
+    assert_eq!(error.offset(), None);
+    assert_eq!(
+        error.to_string(),
+        "bad (in Thing.0, parsed from 0x4 to 0x8)"
+    );
+}
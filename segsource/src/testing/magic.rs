@@ -0,0 +1,65 @@
+use crate::{Error, Result, Segment};
+
+#[test]
+fn next_magic() -> Result<()> {
+    let segment = Segment::with_offset(b"\x7fELF\x02\x01", 0x10);
+    segment.next_magic(b"\x7fELF")?;
+    assert_eq!(segment.current_offset(), 0x14);
+    segment.next_magic(b"")?;
+    assert_eq!(segment.current_offset(), 0x14);
+    match segment.next_magic(b"\x01\x01") {
+        Err(Error::BadMagic {
+            expected,
+            found,
+            offset,
+        }) => {
+            assert_eq!(expected, b"\x01\x01");
+            assert_eq!(found, b"\x02\x01");
+            assert_eq!(offset, 0x14);
+        }
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    assert_eq!(segment.current_offset(), 0x14);
+    Ok(())
+}
+
+#[test]
+fn peek_magic() -> Result<()> {
+    let segment = Segment::new(b"PK\x03\x04");
+    segment.peek_magic(b"PK")?;
+    assert_eq!(segment.current_offset(), 0);
+    segment.magic_at(2, b"\x03\x04")?;
+    assert!(matches!(
+        segment.peek_magic(b"MZ"),
+        Err(Error::BadMagic { offset: 0, .. })
+    ));
+    assert_eq!(segment.current_offset(), 0);
+    Ok(())
+}
+
+#[test]
+fn magic_past_end() {
+    let segment = Segment::new(b"\x89PN");
+    assert!(matches!(
+        segment.peek_magic(b"\x89PNG"),
+        Err(Error::NotEnoughData {
+            requested: 4,
+            left: 3,
+            offset: 0
+        })
+    ));
+    assert!(matches!(
+        segment.magic_at(3, b"G"),
+        Err(Error::NoMoreData { offset: 3 })
+    ));
+    assert!(matches!(
+        segment.magic_at(4, b"G"),
+        Err(Error::NoMoreData { offset: 4 })
+    ));
+    segment.next_n(2).unwrap();
+    assert!(matches!(
+        segment.next_magic(b"NG"),
+        Err(Error::NotEnoughData { .. })
+    ));
+    assert_eq!(segment.current_offset(), 2);
+}
@@ -2,5 +2,19 @@
 fn init_tests() {
     color_backtrace::install();
 }
+pub(crate) mod bits;
+pub(crate) mod checksum;
+#[cfg(any(feature = "deflate", feature = "lz4", feature = "zstd"))]
+pub(crate) mod decompress;
+pub(crate) mod endidness;
+pub(crate) mod error;
+pub(crate) mod magic;
+pub(crate) mod padding;
+pub(crate) mod parse_iter;
+pub(crate) mod search;
 pub(crate) mod segment;
+pub(crate) mod sinks;
 pub(crate) mod sources;
+pub(crate) mod text;
+pub(crate) mod var_int;
+pub(crate) mod view;
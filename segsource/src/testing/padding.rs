@@ -0,0 +1,81 @@
+use crate::{Endidness, Error, Result, Segment, U8Sink as _, VecSink};
+
+#[test]
+fn align() -> Result<()> {
+    let segment = Segment::with_offset(&[0; 30], 0x102);
+    assert_eq!(segment.padding_to_align(4, segment.initial_offset()), 0);
+    segment.align(4)?;
+    assert_eq!(segment.current_offset(), 0x102);
+    segment.move_by(1)?;
+    assert_eq!(segment.padding_to_align(4, segment.initial_offset()), 3);
+    assert_eq!(segment.padding_to_align(4, 0), 1);
+    assert_eq!(segment.padding_to_align(4, 0x110), 1);
+    assert_eq!(segment.padding_to_align(0, 0), 0);
+    segment.align(8)?;
+    assert_eq!(segment.current_offset(), 0x10a);
+    segment.move_to(0x11f)?;
+    assert!(matches!(
+        segment.align(4),
+        Err(Error::OffsetTooLarge { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn padding_to() -> Result<()> {
+    let segment = Segment::with_offset(&[0; 8], 0x10);
+    segment.move_by(3)?;
+    assert_eq!(segment.padding_to(0x10, 8)?, 5);
+    assert_eq!(segment.padding_to(0x11, 2)?, 0);
+    match segment.padding_to(0x10, 2) {
+        Err(Error::ExceedsPadding {
+            size,
+            padded_size,
+            offset,
+        }) => {
+            assert_eq!(size, 3);
+            assert_eq!(padded_size, 2);
+            assert_eq!(offset, 0x10);
+        }
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn next_zero_padding() -> Result<()> {
+    let segment = Segment::with_offset(&[0, 0, 0, 1, 0], 0x20);
+    segment.next_zero_padding(2)?;
+    assert_eq!(segment.current_offset(), 0x22);
+    assert!(matches!(
+        segment.next_zero_padding(2),
+        Err(Error::NonZeroPadding { offset: 0x23 })
+    ));
+    assert_eq!(segment.current_offset(), 0x22);
+    assert!(matches!(
+        segment.next_zero_padding(4),
+        Err(Error::NotEnoughData { .. })
+    ));
+    segment.next_zero_padding(0)?;
+    Ok(())
+}
+
+#[test]
+fn sink_padding() -> Result<()> {
+    let mut sink = VecSink::with_offset_and_endidness(0x31, Endidness::Big);
+    sink.put_u8(0xff)?;
+    sink.align(4)?;
+    assert_eq!(sink.current_offset(), 0x35);
+    assert_eq!(sink.padding_to_align(8, 0), 3);
+    sink.put_zeros(100)?;
+    assert_eq!(sink.padding_to(0x31, 110)?, 6);
+    assert!(matches!(
+        sink.padding_to(0x31, 100),
+        Err(Error::ExceedsPadding { size: 104, .. })
+    ));
+    let data = sink.into_vec();
+    assert_eq!(data.len(), 104);
+    assert_eq!(data[0], 0xff);
+    assert!(data[1..].iter().all(|b| *b == 0));
+    Ok(())
+}
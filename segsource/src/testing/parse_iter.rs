@@ -0,0 +1,73 @@
+use crate::{Endidness, Error, Result, Segment};
+use core::convert::TryFrom;
+
+#[test]
+fn parse_iter() -> Result<()> {
+    let segment = Segment::with_offset_and_endidness(&[0, 1, 0, 2, 0, 3], 0x20, Endidness::Big);
+    let items = segment.parse_iter::<u16>().collect::<Result<Vec<_>>>()?;
+    assert_eq!(items, [1, 2, 3]);
+    assert_eq!(segment.current_offset(), 0x26);
+    assert!(segment.parse_iter::<u16>().next().is_none());
+    Ok(())
+}
+
+#[test]
+fn parse_n() -> Result<()> {
+    let segment = Segment::with_endidness(&[0, 1, 0, 2, 0, 3], Endidness::Big);
+    let mut iter = segment.parse_n::<u16>(2).with_offsets();
+    assert_eq!(iter.size_hint(), (0, Some(2)));
+    assert_eq!(iter.next().unwrap()?, (1, 0..2));
+    assert_eq!(iter.next().unwrap()?, (2, 2..4));
+    assert!(iter.next().is_none());
+    assert_eq!(segment.current_offset(), 4);
+    assert_eq!(segment.parse_n::<u8>(0).count(), 0);
+    Ok(())
+}
+
+#[test]
+fn parse_until() -> Result<()> {
+    let segment = Segment::new(&[3, 2, 0, 1, 0]);
+    let items = segment
+        .parse_until::<u8, _>(|item| *item == 0)
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(items, [3, 2]);
+    assert_eq!(segment.current_offset(), 3);
+    let items = segment
+        .parse_until::<u8, _>(|item| *item == 0)
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(items, [1]);
+    // There's no sentinel left, so the missing data is an error.
+    let mut iter = segment.parse_until::<u8, _>(|item| *item == 0);
+    assert!(matches!(iter.next(), Some(Err(_))));
+    assert_eq!(segment.current_offset(), 5);
+    assert!(iter.next().is_none());
+    Ok(())
+}
+
+#[test]
+fn parse_iter_errors() {
+    let segment = Segment::with_offset(&[1, 0, 0, 0, 2, 0], 0x10);
+    let mut iter = segment.parse_iter::<u32>().with_offsets();
+    assert_eq!(iter.next().unwrap().unwrap(), (1, 0x10..0x14));
+    assert!(matches!(iter.next(), Some(Err(_))));
+    assert!(iter.next().is_none());
+    assert_eq!(segment.current_offset(), 0x14);
+}
+
+/// Doesn't consume anything, so iterating until exhausted would otherwise never end.
+struct Empty;
+
+impl<'s> TryFrom<&Segment<'s, u8>> for Empty {
+    type Error = Error;
+
+    fn try_from(_: &Segment<'s, u8>) -> Result<Self> {
+        Ok(Self)
+    }
+}
+
+#[test]
+fn parse_iter_empty_items() {
+    let segment = Segment::new(&[1]);
+    assert_eq!(segment.parse_iter::<Empty>().count(), 1);
+    assert_eq!(segment.parse_n::<Empty>(3).count(), 3);
+}
@@ -0,0 +1,120 @@
+use crate::{Error, Result, Segment};
+
+#[test]
+fn find_test() -> Result<()> {
+    let data = b"abcabdabcabcabd";
+    let segment = Segment::with_offset(data, 100);
+    assert_eq!(segment.find(b"abcabd"), Some(100));
+    assert_eq!(segment.find_from(101, b"abcabd")?, Some(109));
+    assert_eq!(segment.find(b"abd"), Some(103));
+    assert_eq!(segment.find(b"abe"), None);
+    assert_eq!(segment.find(b""), Some(100));
+    assert_eq!(segment.find(b"abcabdabcabcabdx"), None);
+    segment.move_to(110)?;
+    assert_eq!(segment.find(b"abcabd"), None);
+    assert_eq!(segment.find(b"cabd"), Some(111));
+    assert_eq!(segment.current_offset(), 110);
+    assert!(matches!(
+        segment.find_from(99, b"a"),
+        Err(Error::OffsetTooSmall { offset: 99 })
+    ));
+    assert_eq!(segment.find_from(115, b"a")?, None);
+    Ok(())
+}
+
+#[test]
+fn rfind_test() -> Result<()> {
+    let data = b"aabaabaaab";
+    let segment = Segment::with_offset(data, 10);
+    assert_eq!(segment.rfind(b"aab"), Some(17));
+    assert_eq!(segment.rfind(b"aaba"), Some(13));
+    assert_eq!(segment.rfind(b"b"), Some(19));
+    assert_eq!(segment.rfind(b"c"), None);
+    assert_eq!(segment.rfind(b""), Some(20));
+    segment.move_to(15)?;
+    assert_eq!(segment.rfind(b"aaba"), None);
+    assert_eq!(segment.rfind(b"ba"), Some(15));
+    Ok(())
+}
+
+#[test]
+fn find_all_test() -> Result<()> {
+    let data = [1u16, 2, 1, 2, 1, 2, 3, 1, 2];
+    let segment = Segment::with_offset(&data, 4);
+    assert_eq!(
+        segment.find_all(&[1, 2]).collect::<Vec<_>>(),
+        vec![4, 6, 8, 11]
+    );
+    // Matches don't overlap
+    assert_eq!(segment.find_all(&[1, 2, 1]).collect::<Vec<_>>(), vec![4]);
+    segment.move_by(1)?;
+    assert_eq!(
+        segment.find_all(&[1, 2]).collect::<Vec<_>>(),
+        vec![6, 8, 11]
+    );
+    assert_eq!(segment.find_all(&[4]).next(), None);
+    assert_eq!(segment.find_all(&[3]).collect::<Vec<_>>(), vec![10]);
+    assert_eq!(
+        segment.find_all(&[2]).collect::<Vec<_>>(),
+        vec![5, 7, 9, 12]
+    );
+    Ok(())
+}
+
+#[test]
+fn split_on_test() -> Result<()> {
+    let data = b"GET / HTTP/1.1\r\nHost: x\r\n\r\nbody";
+    let segment = Segment::with_offset(data, 0x20);
+    let lines: Vec<Segment<u8>> = segment.split_on(b"\r\n").collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0].as_ref(), b"GET / HTTP/1.1");
+    assert_eq!(lines[0].initial_offset(), 0x20);
+    assert_eq!(lines[1].as_ref(), b"Host: x");
+    assert_eq!(lines[1].initial_offset(), 0x30);
+    assert!(lines[2].is_empty());
+    assert_eq!(lines[3].as_ref(), b"body");
+    assert_eq!(lines[3].initial_offset(), 0x3b);
+    assert_eq!(lines[3].next_u8()?, b'b');
+
+    segment.move_to(0x30)?;
+    assert_eq!(segment.split_on(b"\r\n").count(), 3);
+    segment.move_to(0x3f)?;
+    let rest: Vec<Segment<u8>> = segment.split_on(b"\r\n").collect();
+    assert_eq!(rest.len(), 1);
+    assert!(rest[0].is_empty());
+    assert_eq!(rest[0].initial_offset(), 0x3f);
+
+    let segment = Segment::with_offset(b"ab", 4);
+    let pieces: Vec<_> = segment
+        .split_on(b"")
+        .map(|piece| (piece.initial_offset(), piece.as_ref().to_vec()))
+        .collect();
+    assert_eq!(
+        pieces,
+        vec![
+            (4, vec![]),
+            (4, b"a".to_vec()),
+            (5, b"b".to_vec()),
+            (6, vec![])
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn long_pattern_test() -> Result<()> {
+    // Long enough that its table can't be built on the stack.
+    let mut data = vec![0u8; 100];
+    data.extend((0..40).map(|i| i as u8));
+    data.extend((0..40).map(|i| i as u8));
+    let pattern: Vec<u8> = (0..40).collect();
+    let segment = Segment::new(&data);
+    assert_eq!(segment.find(&pattern), Some(100));
+    assert_eq!(segment.find_from(101, &pattern)?, Some(140));
+    assert_eq!(segment.rfind(&pattern), Some(140));
+    assert_eq!(
+        segment.find_all(&pattern).collect::<Vec<_>>(),
+        vec![100, 140]
+    );
+    Ok(())
+}
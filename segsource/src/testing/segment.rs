@@ -1,5 +1,5 @@
 #![allow(clippy::needless_range_loop)]
-use crate::{marker::Integer, Endidness, Result, Segment};
+use crate::{marker::Integer, Endidness, Error, Result, Segment};
 use core::fmt::Debug;
 use paste::paste;
 
@@ -119,6 +119,90 @@ make_basic_test_fn! {
     ]
 }
 
+#[test]
+fn peek_int_test() -> Result<()> {
+    let segment = Segment::with_offset_and_endidness(&TEST_U8_DATA, 10, Endidness::Big);
+    segment.move_by(2)?;
+    assert_eq!(segment.peek_u16()?, 0x0203);
+    assert_eq!(segment.peek_i32()?, 0x02030405);
+    assert_eq!(segment.current_offset(), 12);
+    assert_eq!(segment.next_u8()?, 0x02);
+    Ok(())
+}
+
+#[test]
+fn larger_than_segment_test() -> Result<()> {
+    let segment = Segment::with_offset(&TEST_U8_DATA[..4], 10);
+    segment.move_by(1)?;
+    assert!(matches!(
+        segment.next_str(5),
+        Err(Error::NotEnoughData {
+            requested: 5,
+            left: 3,
+            offset: 11
+        })
+    ));
+    assert!(matches!(
+        segment.validate_offset(12, 8),
+        Err(Error::NotEnoughData {
+            requested: 8,
+            left: 2,
+            offset: 12
+        })
+    ));
+    assert_eq!(segment.current_offset(), 11);
+    Ok(())
+}
+
+pub fn float_cmp(segment: &Segment<'_, u8>, as_u32: &[u32], as_u64: &[u64]) -> Result<()> {
+    let initial_offset = segment.initial_offset();
+    for (i, num) in as_u32.iter().enumerate() {
+        let offset = initial_offset + i * 4;
+        assert_eq!(segment.f32_at(offset)?.to_bits(), *num);
+        assert_eq!(segment.current_f32()?.to_bits(), *num);
+        assert_eq!(segment.peek_f32()?.to_bits(), *num);
+        assert_eq!(segment.current_offset(), offset);
+        assert_eq!(segment.next_f32()?.to_bits(), *num);
+    }
+    segment.move_to(initial_offset)?;
+    for (i, num) in as_u64.iter().enumerate() {
+        let offset = initial_offset + i * 8;
+        assert_eq!(segment.f64_at(offset)?.to_bits(), *num);
+        assert_eq!(segment.peek_f64()?.to_bits(), *num);
+        assert_eq!(segment.current_offset(), offset);
+        assert_eq!(segment.next_f64()?.to_bits(), *num);
+    }
+    assert!(segment.next_f32().is_err());
+    Ok(())
+}
+
+make_basic_test_fn! {
+    @cmp_func = float_cmp,
+    float_test,
+    [
+        @Endidness::Little, 0, (&LE_U32_U8_DATA, &LE_U64_U8_DATA);
+        @Endidness::Little, 5, (&LE_U32_U8_DATA, &LE_U64_U8_DATA);
+        @Endidness::Big, 0, (&BE_U32_U8_DATA, &BE_U64_U8_DATA);
+        @Endidness::Big, 100, (&BE_U32_U8_DATA, &BE_U64_U8_DATA);
+    ]
+}
+
+#[test]
+fn float_try_from_test() -> Result<()> {
+    use core::convert::TryFrom as _;
+    let segment = Segment::with_endidness(&TEST_U8_DATA, Endidness::Big);
+    assert_eq!(f32::try_from(&segment)?.to_bits(), BE_U32_U8_DATA[0]);
+    let array = <[f32; 3]>::try_from(&segment)?;
+    for i in 0..3 {
+        assert_eq!(array[i].to_bits(), BE_U32_U8_DATA[i + 1]);
+    }
+    segment.move_to(0)?;
+    let array = <[f64; 2]>::try_from(&segment)?;
+    assert_eq!(array[0].to_bits(), BE_U64_U8_DATA[0]);
+    assert_eq!(array[1].to_bits(), BE_U64_U8_DATA[1]);
+    Ok(())
+}
+
 pub fn basic_test_1_impl(segment: &Segment<'_, u8>) -> Result<()> {
     let initial_offset = segment.initial_offset();
     assert_eq!(segment.current_offset(), initial_offset);
@@ -0,0 +1,224 @@
+use super::segment::{
+    BE_U16_U8_DATA, BE_U32_U8_DATA, BE_U64_U8_DATA, LE_U16_U8_DATA, LE_U32_U8_DATA, LE_U64_U8_DATA,
+    TEST_U8_DATA,
+};
+use crate::{
+    Endidness, Error, Result, SegmentMut, Source as _, U8Sink, U8Source as _, VecSink, VecSource,
+};
+
+fn put_ints<S: U8Sink>(sink: &mut S, as_u16: &[u16], as_u32: &[u32], as_u64: &[u64]) -> Result<()> {
+    let initial_offset = sink.initial_offset();
+    for num in as_u16.iter() {
+        sink.put_u16(*num)?;
+    }
+    assert_eq!(sink.current_offset(), initial_offset + 16);
+    sink.move_to(initial_offset)?;
+    for num in as_u32.iter() {
+        sink.put_u32(*num)?;
+    }
+    sink.move_to(initial_offset)?;
+    for (i, num) in as_u64.iter().enumerate() {
+        sink.put_u64_at(initial_offset + i * 8, *num)?;
+    }
+    assert_eq!(sink.current_offset(), initial_offset);
+    Ok(())
+}
+
+#[test]
+fn segment_mut_put_test() -> Result<()> {
+    for &offset in [0, 1, 100].iter() {
+        let mut data = [0; 16];
+        let mut segment = SegmentMut::with_offset_and_endidness(&mut data, offset, Endidness::Big);
+        put_ints(
+            &mut segment,
+            &BE_U16_U8_DATA,
+            &BE_U32_U8_DATA,
+            &BE_U64_U8_DATA,
+        )?;
+        assert_eq!(data, TEST_U8_DATA);
+
+        let mut data = [0; 16];
+        let mut segment =
+            SegmentMut::with_offset_and_endidness(&mut data, offset, Endidness::Little);
+        put_ints(
+            &mut segment,
+            &LE_U16_U8_DATA,
+            &LE_U32_U8_DATA,
+            &LE_U64_U8_DATA,
+        )?;
+        assert_eq!(data, TEST_U8_DATA);
+    }
+    Ok(())
+}
+
+#[test]
+fn segment_mut_bounds_test() -> Result<()> {
+    let mut data = [0; 4];
+    let mut segment = SegmentMut::with_offset(&mut data, 10);
+    assert!(matches!(
+        segment.put_u8_at(9, 0),
+        Err(Error::OffsetTooSmall { offset: 9 })
+    ));
+    assert!(matches!(
+        segment.move_by(-1),
+        Err(Error::OffsetTooSmall { offset: 9 })
+    ));
+    assert!(matches!(
+        segment.move_by(i128::MIN),
+        Err(Error::OffsetTooSmall { offset: 0 })
+    ));
+    assert!(matches!(
+        segment.segment_mut(12, 11),
+        Err(Error::OffsetTooSmall { offset: 11 })
+    ));
+    assert!(matches!(
+        segment.put_u8_at(15, 0),
+        Err(Error::OffsetTooLarge { offset: 15 })
+    ));
+    assert!(matches!(
+        segment.put_u32_at(11, 0),
+        Err(Error::NotEnoughData {
+            requested: 4,
+            left: 3,
+            offset: 11
+        })
+    ));
+    assert!(matches!(
+        segment.move_to(15),
+        Err(Error::OffsetTooLarge { offset: 15 })
+    ));
+    segment.put_u16(0xffff)?;
+    segment.put_u16(0xffff)?;
+    assert!(segment.is_empty());
+    assert!(matches!(
+        segment.put_u8(0),
+        Err(Error::NotEnoughData { .. })
+    ));
+    assert_eq!(segment.current_offset(), 14);
+    assert_eq!(data, [0xff; 4]);
+    Ok(())
+}
+
+#[test]
+fn segment_mut_as_segment_test() -> Result<()> {
+    let mut data = [0; 16];
+    let mut segment = SegmentMut::with_offset_and_endidness(&mut data, 5, Endidness::Little);
+    segment.put_items(&TEST_U8_DATA[..8])?;
+    segment.put_f64(f64::from_bits(LE_U64_U8_DATA[1]))?;
+    let reader = segment.as_segment();
+    assert_eq!(reader.current_offset(), 21);
+    reader.move_to(5)?;
+    for num in LE_U32_U8_DATA.iter() {
+        assert_eq!(reader.next_u32()?, *num);
+    }
+    Ok(())
+}
+
+#[test]
+fn vec_sink_put_test() -> Result<()> {
+    for &offset in [0, 1, 100].iter() {
+        let mut sink = VecSink::with_offset_and_endidness(offset, Endidness::Big);
+        put_ints(&mut sink, &BE_U16_U8_DATA, &BE_U32_U8_DATA, &BE_U64_U8_DATA)?;
+        assert_eq!(sink.as_ref(), &TEST_U8_DATA);
+
+        let mut sink = VecSink::with_offset_and_endidness(offset, Endidness::Little);
+        put_ints(&mut sink, &LE_U16_U8_DATA, &LE_U32_U8_DATA, &LE_U64_U8_DATA)?;
+        assert_eq!(sink.into_vec(), TEST_U8_DATA);
+    }
+    Ok(())
+}
+
+#[test]
+fn vec_sink_bounds_test() -> Result<()> {
+    let mut sink = VecSink::new();
+    assert!(matches!(
+        sink.move_by(-1),
+        Err(Error::OffsetTooSmall { offset: 0 })
+    ));
+    assert!(matches!(
+        sink.move_by(i128::MAX),
+        Err(Error::OffsetTooLarge { .. })
+    ));
+    assert_eq!(sink.current_offset(), 0);
+    assert!(matches!(
+        sink.put_u8_at(usize::MAX, 1),
+        Err(Error::OffsetTooLarge { .. })
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn vec_sink_seek_test() -> Result<()> {
+    use std::io::{ErrorKind, Seek as _, SeekFrom};
+    let mut sink = VecSink::with_offset_and_endidness(4, Endidness::Big);
+    sink.put_u32(0x01020304)?;
+    assert_eq!(sink.seek(SeekFrom::End(-2))?, 6);
+    assert_eq!(sink.seek(SeekFrom::Current(-2))?, 4);
+    for pos in [SeekFrom::Current(-1), SeekFrom::Start(3), SeekFrom::End(-5)].iter() {
+        assert_eq!(sink.seek(*pos).unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(sink.current_offset(), 4);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn segment_mut_seek_test() -> Result<()> {
+    use std::io::{ErrorKind, Seek as _, SeekFrom};
+    let mut data = [0; 4];
+    let mut segment = SegmentMut::with_offset(&mut data, 4);
+    assert_eq!(segment.seek(SeekFrom::End(-2))?, 6);
+    assert_eq!(segment.seek(SeekFrom::Current(-2))?, 4);
+    for pos in [
+        SeekFrom::Current(-1),
+        SeekFrom::Start(3),
+        SeekFrom::End(-5),
+        SeekFrom::End(1),
+        SeekFrom::Start(u64::MAX),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            segment.seek(*pos).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+        assert_eq!(segment.current_offset(), 4);
+    }
+    Ok(())
+}
+
+#[test]
+fn vec_source_segment_mut_test() -> Result<()> {
+    let mut source = VecSource::from_u8_slice_with_offset(&[0; 8], 2, Endidness::Big)?;
+    source.segment_mut(4, 6)?.put_u16(0x0102)?;
+    assert_eq!(source.all()?.u16_at(4)?, 0x0102);
+    assert!(matches!(
+        source.segment_mut(5, 3),
+        Err(Error::OffsetTooSmall { offset: 3 })
+    ));
+    Ok(())
+}
+
+#[test]
+fn vec_sink_grow_test() -> Result<()> {
+    let mut sink = VecSink::with_offset_and_endidness(10, Endidness::Big);
+    assert!(matches!(
+        sink.put_u8_at(9, 0),
+        Err(Error::OffsetTooSmall { offset: 9 })
+    ));
+    sink.move_to(14)?;
+    assert_eq!(sink.size(), 0);
+    sink.put_u16(0x0405)?;
+    assert_eq!(sink.as_ref(), &[0, 0, 0, 0, 4, 5]);
+    sink.put_u8_at(10, 1)?;
+    sink.put_f32_at(20, f32::from_bits(0x01020304))?;
+    assert_eq!(sink.upper_offset_limit(), 24);
+    let source = sink.into_source();
+    let segment = source.all()?;
+    assert_eq!(segment.initial_offset(), 10);
+    assert_eq!(segment.endidness(), Endidness::Big);
+    assert_eq!(segment.next_u16()?, 0x0100);
+    assert_eq!(segment.u32_at(20)?, 0x01020304);
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use crate::{AsyncStreamSource, Endidness, Error, Result};
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io::{self, Cursor, SeekFrom};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+/// A reader that's only ready every other time it's polled and only reads a few bytes at a time.
+struct SlowReader {
+    inner: Cursor<Vec<u8>>,
+    ready: bool,
+}
+
+impl AsyncRead for SlowReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.ready = !self.ready;
+        if !self.ready {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        let mut limited = buf.take(3);
+        let result = Pin::new(&mut self.inner).poll_read(cx, &mut limited);
+        let filled = limited.filled().len();
+        buf.advance(filled);
+        result
+    }
+}
+
+impl AsyncSeek for SlowReader {
+    fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        Pin::new(&mut self.inner).start_seek(position)
+    }
+
+    fn poll_complete(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Pin::new(&mut self.inner).poll_complete(cx)
+    }
+}
+
+fn test_data() -> Vec<u8> {
+    (0..=0x63).collect()
+}
+
+fn block_on<F: core::future::Future<Output = Result<()>>>(future: F) -> Result<()> {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+async fn slow_source(initial_offset: usize) -> Result<AsyncStreamSource<SlowReader>> {
+    let reader = SlowReader {
+        inner: Cursor::new(test_data()),
+        ready: false,
+    };
+    AsyncStreamSource::with_cache(reader, initial_offset, Endidness::Big, 16, 2).await
+}
+
+#[test]
+fn windows() -> Result<()> {
+    block_on(async {
+        let mut source = slow_source(10).await?;
+        assert_eq!(source.size(), 100);
+        assert_eq!(source.current_offset(), 10);
+        let window = source.window_async(12, 20).await?;
+        assert!(!window.is_copy());
+        assert_eq!(window.as_ref(), [2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(window.segment().u16_at(18)?, 0x0809);
+        let window = source.get_n_async(20, 40).await?;
+        assert!(window.is_copy());
+        assert_eq!(window.initial_offset(), 20);
+        assert_eq!(window.as_ref(), &test_data()[10..50]);
+        assert!(source.cached_pages() <= 2);
+        assert!(matches!(
+            source.window_async(9, 12).await,
+            Err(Error::OffsetTooSmall { offset: 9 })
+        ));
+        assert!(matches!(
+            source.get_n_async(100, 11).await,
+            Err(Error::OffsetTooLarge { offset: 111 })
+        ));
+        assert!(matches!(
+            source.get_n_async(100, usize::MAX).await,
+            Err(Error::OffsetTooLarge { offset: usize::MAX })
+        ));
+        Ok(())
+    })
+}
+
+#[test]
+fn cursor() -> Result<()> {
+    block_on(async {
+        let mut source = slow_source(0x100).await?;
+        assert_eq!(source.next_u8_async().await?, 0);
+        assert_eq!(source.peek_u16_async().await?, 0x0102);
+        assert_eq!(source.next_u32_async().await?, 0x01020304);
+        assert_eq!(source.current_offset(), 0x105);
+        let window = source.next_n_async(20).await?;
+        assert_eq!(window.initial_offset(), 0x105);
+        assert_eq!(window.as_ref(), &test_data()[5..25]);
+        assert_eq!(source.current_offset(), 0x119);
+        assert_eq!(source.remaining(), 75);
+        assert_eq!(source.u64_at_async(0x108).await?, 0x08090a0b0c0d0e0f);
+        source.change_endidness(Endidness::Little);
+        assert_eq!(source.next_i16_async().await?, 0x1a19);
+        source.move_by(-3)?;
+        assert_eq!(source.peek_n_async(3).await?.as_ref(), [0x18, 0x19, 0x1a]);
+        source.move_to(0x162)?;
+        assert_eq!(source.next_u16_async().await?, 0x6362);
+        assert!(matches!(
+            source.next_u8_async().await,
+            Err(Error::OffsetTooLarge { offset: 0x165 })
+        ));
+        assert_eq!(source.current_offset(), 0x164);
+        assert!(source.move_to(0xff).is_err());
+        Ok(())
+    })
+}
+
+#[test]
+fn default_cache() -> Result<()> {
+    block_on(async {
+        let mut source =
+            AsyncStreamSource::new(Cursor::new(test_data()), 0, Endidness::Big).await?;
+        assert_eq!(source.page_size(), crate::DEFAULT_PAGE_SIZE);
+        assert_eq!(source.max_pages(), crate::DEFAULT_MAX_PAGES);
+        assert_eq!(
+            source.next_n_async(100).await?.as_ref(),
+            test_data().as_slice()
+        );
+        assert_eq!(source.cached_pages(), 1);
+        source.clear_cache();
+        assert_eq!(source.cached_pages(), 0);
+        Ok(())
+    })
+}
+
+#[test]
+fn zero_sized_cache() -> Result<()> {
+    block_on(async {
+        let options = crate::SourceOptions::new().with_buffer_size(0);
+        assert!(matches!(
+            AsyncStreamSource::with_options(Cursor::new(test_data()), &options).await,
+            Err(Error::Other { .. })
+        ));
+        assert!(matches!(
+            AsyncStreamSource::with_cache(Cursor::new(test_data()), 0, Endidness::Big, 16, 0).await,
+            Err(Error::Other { .. })
+        ));
+        Ok(())
+    })
+}
@@ -0,0 +1,99 @@
+use crate::{
+    testing::segment, ChainedSource, Endidness, Error, Result, Source as _, U8Source as _,
+    VecSource,
+};
+
+fn u8_source(data: &[u8], initial_offset: usize) -> Result<VecSource<u8>> {
+    VecSource::from_u8_slice_with_offset(data, initial_offset, Endidness::Little)
+}
+
+/// Splits the standard test data up into several differently sized (and offset) sources.
+fn test_chain(initial_offset: usize) -> Result<ChainedSource<u8>> {
+    let data = &segment::TEST_U8_DATA;
+    let mut source = ChainedSource::new(initial_offset, Endidness::Big);
+    source.push(u8_source(&data[..3], 0)?)?;
+    source.push(u8_source(&[], 50)?)?;
+    source.push(u8_source(&data[3..4], 1000)?)?;
+    source.push(u8_source(&data[4..11], 7)?)?;
+    source.push(u8_source(&data[11..], 0)?)?;
+    Ok(source)
+}
+
+#[test]
+fn offsets() -> Result<()> {
+    let source = test_chain(100)?;
+    assert_eq!(source.size(), 16);
+    assert_eq!(source.num_parts(), 5);
+    assert_eq!(source.lower_offset_limit(), 100);
+    assert_eq!(source.upper_offset_limit(), 116);
+    assert_eq!(source.part_offset(1), Some(103));
+    assert_eq!(source.part_offset(3), Some(104));
+    assert_eq!(source.part_offset(5), None);
+    assert_eq!(source.part_index_at(100)?, 0);
+    assert_eq!(source.part_index_at(103)?, 2);
+    assert_eq!(source.part_index_at(110)?, 3);
+    assert_eq!(source.part_index_at(115)?, 4);
+    assert!(matches!(
+        source.part_index_at(116),
+        Err(Error::OffsetTooLarge { offset: 116 })
+    ));
+    assert!(source.validate_offset(116).is_ok());
+    assert!(matches!(
+        source.validate_offset(99),
+        Err(Error::OffsetTooSmall { offset: 99 })
+    ));
+    assert!(matches!(
+        source.get_n(110, 7),
+        Err(Error::OffsetTooLarge { offset: 117 })
+    ));
+    assert!(matches!(
+        source.get_n(110, usize::MAX),
+        Err(Error::OffsetTooLarge { offset: usize::MAX })
+    ));
+    Ok(())
+}
+
+#[test]
+fn borrowed_and_stitched() -> Result<()> {
+    let source = test_chain(100)?;
+    let data = source.get_n(105, 5)?;
+    assert!(!data.is_stitched());
+    assert_eq!(data.initial_offset(), 105);
+    assert_eq!(data.as_ref(), [5, 6, 7, 8, 9]);
+
+    let data = source.segment(102, 112)?;
+    assert!(data.is_stitched());
+    assert_eq!(data.as_ref(), &segment::TEST_U8_DATA[2..12]);
+    let segment = data.segment();
+    assert_eq!(segment.initial_offset(), 102);
+    assert_eq!(segment.endidness(), Endidness::Big);
+    assert_eq!(segment.u32_at(102)?, 0x02030405);
+
+    assert!(source.segment(103, 103)?.is_empty());
+    assert_eq!(source.all_before(103)?.as_ref(), [0, 1, 2]);
+    assert_eq!(source.all_after(111)?.as_ref(), [11, 12, 13, 14, 15]);
+    assert!(!source.all_after(111)?.is_stitched());
+    Ok(())
+}
+
+#[test]
+fn segment_tests() -> Result<()> {
+    for initial_offset in &[0, 7, 100] {
+        let source = test_chain(*initial_offset)?;
+        segment::basic_test_1_impl(&source.all()?.segment())?;
+        segment::move_by_test_impl(&source.all()?.segment())?;
+        segment::indexing_test_impl(&source.all()?.segment())?;
+    }
+    Ok(())
+}
+
+#[test]
+fn non_u8_items() -> Result<()> {
+    let mut source = ChainedSource::new(10, Endidness::default());
+    source.push(VecSource::from_vec(vec!["a", "b"])?)?;
+    source.push(VecSource::from_vec(vec!["c"])?)?;
+    let segment = source.all()?;
+    assert_eq!(segment.as_ref(), ["a", "b", "c"]);
+    assert_eq!(segment.segment().item_at(12)?, "c");
+    Ok(())
+}
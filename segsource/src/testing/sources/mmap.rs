@@ -0,0 +1,242 @@
+use crate::{
+    AccessHint, Endidness, Error, LockPolicy, MappedFileSource, MappedFileSourceMut, Result,
+    Source as _, SourceOptions, U8Sink as _, U8Source as _, WindowedMappedFileSource,
+};
+use std::{fs, path::PathBuf};
+
+/// A file in the temp directory that's removed once the test is done with it.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str, data: &[u8]) -> Self {
+        let path = std::env::temp_dir().join(format!("segsource-{}-{}", std::process::id(), name));
+        fs::write(&path, data).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn patch_in_place() -> Result<()> {
+    let file = TempFile::new("patch", &[0; 8]);
+    let mut source = MappedFileSourceMut::from_file_with_offset(&file.0, 0x100, Endidness::Big)?;
+    {
+        let mut segment = source.all_mut()?;
+        segment.put_u16(0x0102)?;
+        segment.put_u32_at(0x104, 0x03040506)?;
+    }
+    {
+        let mut segment = source.segment_mut(0x102, 0x104)?;
+        segment.change_endidness(Endidness::Little);
+        segment.put_u16(0x0708)?;
+        assert!(segment.put_u8(0).is_err());
+    }
+    assert_eq!(source.all()?.u32_at(0x100)?, 0x01020807);
+    source.flush()?;
+    source.flush_range(0x104, 0x108)?;
+    assert!(source.flush_range(0x104, 0x109).is_err());
+    assert!(matches!(
+        source.flush_range(0x104, 0x102),
+        Err(Error::OffsetTooSmall { offset: 0x102 })
+    ));
+    assert!(matches!(
+        source.segment_mut(0x104, 0x102),
+        Err(Error::OffsetTooSmall { offset: 0x102 })
+    ));
+    drop(source);
+    assert_eq!(fs::read(&file.0)?, [1, 2, 8, 7, 3, 4, 5, 6]);
+    Ok(())
+}
+
+#[test]
+fn anonymous() -> Result<()> {
+    let mut source = MappedFileSourceMut::from_u8_slice(&[1, 2, 3], Endidness::Little)?;
+    source.all_mut()?.put_u8_at(1, 9)?;
+    source.flush()?;
+    assert_eq!(source.all()?.as_ref(), [1, 9, 3]);
+    assert!(source.path().is_none());
+    Ok(())
+}
+
+#[test]
+fn file_range() -> Result<()> {
+    let data: Vec<u8> = (0..32).collect();
+    let file = TempFile::new("file_range", &data);
+    let source = MappedFileSource::from_file_range(&file.0, 0x11, 8, Endidness::Big)?;
+    assert_eq!(source.lower_offset_limit(), 0x11);
+    assert_eq!(source.upper_offset_limit(), 0x19);
+    assert_eq!(source.all()?.u16_at(0x12)?, 0x1213);
+    assert_eq!(source.all()?.as_ref(), &data[0x11..0x19]);
+    assert!(source.segment(0x10, 0x12).is_err());
+    drop(source);
+    assert!(matches!(
+        MappedFileSource::from_file_range(&file.0, 0x21, 1, Endidness::Big),
+        Err(Error::OffsetTooLarge { offset: 0x21 })
+    ));
+    assert!(matches!(
+        MappedFileSource::from_file_range(&file.0, 0x1c, 8, Endidness::Big),
+        Err(Error::NotEnoughData {
+            requested: 8,
+            left: 4,
+            offset: 0x1c
+        })
+    ));
+    Ok(())
+}
+
+#[test]
+fn patch_file_range() -> Result<()> {
+    let file = TempFile::new("patch_file_range", &[0; 8]);
+    let mut source = MappedFileSourceMut::from_file_range(&file.0, 3, 2, Endidness::Little)?;
+    source.segment_mut(3, 5)?.put_u16(0x0102)?;
+    assert!(source.segment_mut(5, 6).is_err());
+    source.flush()?;
+    drop(source);
+    assert_eq!(fs::read(&file.0)?, [0, 0, 0, 2, 1, 0, 0, 0]);
+    Ok(())
+}
+
+#[test]
+fn file_range_with_options() -> Result<()> {
+    let data: Vec<u8> = (0..16).collect();
+    let file = TempFile::new("file_range_with_options", &data);
+    let options = SourceOptions::new()
+        .with_initial_offset(0x100)
+        .with_endidness(Endidness::Little)
+        .with_lock_policy(LockPolicy::None)
+        .with_access_hint(AccessHint::Random);
+    let mut patch = MappedFileSourceMut::from_file_range_with_options(&file.0, 4, 4, &options)?;
+    // Without a lock, the file can be mapped while it's being changed.
+    let source = MappedFileSource::from_file_range_with_options(&file.0, 2, 8, &options)?;
+    assert_eq!(source.lower_offset_limit(), 2);
+    assert_eq!(source.endidness(), Endidness::Little);
+    patch.segment_mut(4, 6)?.put_u16(0x0102)?;
+    assert_eq!(source.all()?.u16_at(4)?, 0x0102);
+    assert_eq!(patch.upper_offset_limit(), 8);
+    Ok(())
+}
+
+#[test]
+fn windowed() -> Result<()> {
+    let data: Vec<u8> = (0..40).collect();
+    let file = TempFile::new("windowed", &data);
+    let source = WindowedMappedFileSource::with_window_size(&file.0, Endidness::Big, 16)?;
+    assert_eq!(source.size(), 40);
+    assert!(source.mapped_range().is_none());
+
+    let first = source.get_n(2, 4)?;
+    assert_eq!(source.mapped_range(), Some(0..16));
+    assert_eq!(first.segment().u16_at(4)?, 0x0405);
+    let second = source.window(10, 14)?;
+    assert_eq!(source.mapped_range(), Some(0..16));
+    assert_eq!(second.initial_offset(), 10);
+    assert_eq!(second.relative_segment().u16_at(0)?, 0x0a0b);
+
+    // Windows keep their part of the file mapped after it's been remapped.
+    let third = source.window(20, 24)?;
+    assert_eq!(source.mapped_range(), Some(16..32));
+    assert_eq!(first.as_ref(), &data[2..6]);
+    assert_eq!(third.as_ref(), &data[20..24]);
+
+    // Windows can span more than the window size, and mappings are clipped to the file's end.
+    let spanning = source.window(14, 35)?;
+    assert_eq!(source.mapped_range(), Some(0..35));
+    assert_eq!(spanning.as_ref(), &data[14..35]);
+    assert!(!spanning.is_copy());
+    source.all_after(36)?;
+    assert_eq!(source.mapped_range(), Some(32..40));
+
+    assert_eq!(source.all()?.as_ref(), &data[..]);
+    assert!(source.all_before(0)?.is_empty());
+    assert!(source.window(39, 41).is_err());
+    assert!(matches!(
+        source.window(5, 4),
+        Err(Error::OffsetTooSmall { offset: 4 })
+    ));
+    assert!(matches!(
+        source.get_n(u64::MAX, 1),
+        Err(Error::OffsetTooLarge { offset: usize::MAX })
+    ));
+    assert!(matches!(
+        WindowedMappedFileSource::with_window_size(&file.0, Endidness::Big, 0),
+        Err(Error::Other { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn lock_policies() -> Result<()> {
+    let file = TempFile::new("lock_policies", &[1, 2, 3, 4]);
+    let locked = MappedFileSourceMut::from_file(&file.0, Endidness::Big)?;
+    assert!(MappedFileSource::from_file(&file.0, Endidness::Big).is_err());
+    let unlocked: MappedFileSource = SourceOptions::new()
+        .with_lock_policy(LockPolicy::None)
+        .open(&file.0)?;
+    assert_eq!(unlocked.all()?.as_ref(), [1, 2, 3, 4]);
+    drop(locked);
+    let _shared: MappedFileSource = SourceOptions::new()
+        .with_lock_policy(LockPolicy::SharedBlocking)
+        .open(&file.0)?;
+    assert!(SourceOptions::new()
+        .with_lock_policy(LockPolicy::Exclusive)
+        .open::<MappedFileSourceMut, _>(&file.0)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn populate_and_access_hints() -> Result<()> {
+    let data: Vec<u8> = (0..64).collect();
+    let file = TempFile::new("populate_and_access_hints", &data);
+    for access_hint in [
+        AccessHint::Normal,
+        AccessHint::Sequential,
+        AccessHint::Random,
+        AccessHint::WillNeed,
+    ]
+    .iter()
+    {
+        let options = SourceOptions::new()
+            .with_initial_offset(0x40)
+            .with_endidness(Endidness::Big)
+            .with_access_hint(*access_hint)
+            .with_populate(true);
+        let source: MappedFileSource = options.open(&file.0)?;
+        assert_eq!(source.all()?.u16_at(0x42)?, 0x0203);
+        let source =
+            WindowedMappedFileSource::with_options(&file.0, &options.with_buffer_size(16))?;
+        assert_eq!(source.window_size(), 16);
+        assert_eq!(source.window(20, 22)?.segment().u16_at(20)?, 0x1415);
+        assert_eq!(source.mapped_range(), Some(16..32));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn flush_async() -> Result<()> {
+    use crate::AsyncU8Source as _;
+    let file = TempFile::new("flush_async", &[0; 4]);
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let mut source =
+                MappedFileSourceMut::from_file_async(&file.0, Endidness::Little).await?;
+            source.all_mut()?.put_u32(0x01020304)?;
+            source.flush_async().await?;
+            source.flush_range_async(1, 3).await?;
+            assert!(matches!(
+                source.flush_range_async(3, 1).await,
+                Err(Error::OffsetTooSmall { offset: 1 })
+            ));
+            Ok::<_, crate::Error>(())
+        })?;
+    assert_eq!(fs::read(&file.0)?, [4, 3, 2, 1]);
+    Ok(())
+}
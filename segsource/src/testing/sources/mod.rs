@@ -294,12 +294,12 @@ macro_rules! add_imports {
     () => {
         #[cfg(feature = "with-bytes")]
         use crate::BytesSource;
-        #[cfg(feature = "memmap")]
-        use crate::MappedFileSource;
         use crate::{
             testing::{segment, sources::U8VecSource},
             Endidness, Result, SegmentLikeSource, Source as _, U8Source as _,
         };
+        #[cfg(feature = "memmap")]
+        use crate::{MappedFileSource, MappedFileSourceMut};
         #[cfg(not(feature = "std"))]
         use alloc::vec::Vec;
         #[cfg(feature = "with-bytes")]
@@ -428,5 +428,25 @@ make_source_tests! {U8VecSource, vec}
 make_source_tests! {BytesSource, bytes}
 #[cfg(feature = "memmap")]
 make_source_tests! {MappedFileSource, memmap}
+#[cfg(feature = "memmap")]
+make_source_tests! {MappedFileSourceMut, memmap_mut}
 
+#[cfg(feature = "with-bytes")]
+#[test]
+fn bytes_source_zero_buffer_size() {
+    let options = crate::SourceOptions::new().with_buffer_size(0);
+    assert!(matches!(
+        crate::BytesSource::from_file_with_options("missing", &options),
+        Err(crate::Error::Other { .. })
+    ));
+}
+
+#[cfg(feature = "async")]
+mod async_stream;
+mod chained;
+#[cfg(feature = "memmap")]
+mod mmap;
 mod segment_like;
+mod sparse;
+#[cfg(feature = "std")]
+mod stream;
@@ -0,0 +1,119 @@
+use crate::{
+    testing::segment, Endidness, Error, Region, Result, SparseSource, U8Source as _, VecSource,
+};
+
+fn u8_source(data: &[u8]) -> Result<VecSource<u8>> {
+    VecSource::from_u8_slice_with_offset(data, 3, Endidness::Big)
+}
+
+fn test_memory() -> Result<SparseSource<u8>> {
+    let data = &segment::TEST_U8_DATA;
+    let mut memory = SparseSource::new(Endidness::Little);
+    memory.map(0x400, u8_source(&data[..8])?)?;
+    memory.map(0x100, u8_source(&data[8..12])?)?;
+    memory.map(0x104, u8_source(&data[12..])?)?;
+    memory.map(0x200, u8_source(&[])?)?;
+    Ok(memory)
+}
+
+#[test]
+fn regions() -> Result<()> {
+    let memory = test_memory()?;
+    assert_eq!(memory.num_regions(), 4);
+    assert_eq!(memory.size(), 16);
+    assert_eq!(memory.lower_offset_limit(), Some(0x100));
+    assert_eq!(memory.upper_offset_limit(), Some(0x408));
+    let bases: Vec<_> = memory.regions().map(|region| region.base()).collect();
+    assert_eq!(bases, [0x100, 0x104, 0x200, 0x400]);
+
+    let region = memory.region_at(0x405).unwrap();
+    assert_eq!(region.index(), 3);
+    assert_eq!(region.base(), 0x400);
+    assert_eq!(region.size(), 8);
+    assert_eq!(region.end(), 0x408);
+    assert!(region.contains(0x407));
+    assert!(!region.contains(0x408));
+    assert_eq!(memory.region_at(0x104).unwrap().index(), 1);
+    assert_eq!(memory.region_at(0x200), None);
+    assert_eq!(memory.region_at(0xff), None);
+    assert_eq!(memory.region_at(0x408), None);
+    assert!(memory.is_mapped(0x103));
+    assert!(!memory.is_mapped(0x108));
+    assert!(matches!(
+        memory.validate_offset(0x300),
+        Err(Error::Unmapped { offset: 0x300 })
+    ));
+    Ok(())
+}
+
+#[test]
+fn reads() -> Result<()> {
+    let memory = test_memory()?;
+    let data = memory.get_n(0x402, 4)?;
+    assert!(!data.is_stitched());
+    assert_eq!(data.initial_offset(), 0x402);
+    let segment = data.segment();
+    assert_eq!(segment.endidness(), Endidness::Little);
+    assert_eq!(segment.next_u32()?, 0x05040302);
+
+    let data = memory.segment(0x102, 0x106)?;
+    assert!(data.is_stitched());
+    assert_eq!(data.as_ref(), [10, 11, 12, 13]);
+    assert_eq!(data.segment().u16_at(0x103)?, 0x0c0b);
+
+    assert!(memory.get_n(0x300, 0)?.is_empty());
+    assert!(matches!(
+        memory.get_n(0xfe, 4),
+        Err(Error::Unmapped { offset: 0xfe })
+    ));
+    assert!(matches!(
+        memory.segment(0x106, 0x109),
+        Err(Error::Unmapped { offset: 0x108 })
+    ));
+    let error = memory.get_n(0x406, 4).err().unwrap();
+    assert!(matches!(error, Error::Unmapped { offset: 0x408 }));
+    assert!(matches!(
+        memory.get_n(0x402, usize::MAX),
+        Err(Error::OffsetTooLarge { offset: usize::MAX })
+    ));
+    assert_eq!(error.offset(), Some(0x408));
+    assert_eq!(error.to_string(), "Address 0x408 is not mapped.");
+
+    assert_eq!(memory.region_data(1).unwrap()?.as_ref(), [12, 13, 14, 15]);
+    assert!(memory.region_data(4).is_none());
+    Ok(())
+}
+
+#[test]
+fn map_and_unmap() -> Result<()> {
+    let mut memory = test_memory()?;
+    assert!(matches!(
+        memory.map(0xfe, u8_source(&[0, 0, 0])?),
+        Err(Error::Other { .. })
+    ));
+    assert!(matches!(
+        memory.map(0x407, u8_source(&[0])?),
+        Err(Error::Other { .. })
+    ));
+    // Empty regions don't hide the regions around them.
+    memory.map(0x402, u8_source(&[])?)?;
+    assert!(matches!(
+        memory.map(0x404, u8_source(&[0])?),
+        Err(Error::Other { .. })
+    ));
+    assert!(matches!(
+        memory.map(usize::MAX - 1, u8_source(&[0, 0, 0])?),
+        Err(Error::OffsetTooLarge { offset: usize::MAX })
+    ));
+    assert_eq!(memory.num_regions(), 5);
+    memory.map(0x408, u8_source(&[0xff])?)?;
+    assert_eq!(memory.get_n(0x406, 3)?.as_ref(), [6, 7, 0xff]);
+    let region = memory.unmap(0).unwrap();
+    assert_eq!(
+        (region.index(), region.base(), region.size()),
+        (0, 0x100, 4)
+    );
+    assert!(!memory.is_mapped(0x100));
+    assert_eq!(memory.unmap(10), None::<Region>);
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use crate::{testing::segment, Endidness, Error, Result, SourceOptions, StreamSource};
+use std::{
+    io::{self, Cursor, Read, Seek, SeekFrom},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// A reader that counts how many times it's been read from and only reads a few bytes at a time.
+struct CountingReader {
+    inner: Cursor<Vec<u8>>,
+    reads: Arc<AtomicUsize>,
+}
+
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        let len = buf.len().min(3);
+        self.inner.read(&mut buf[..len])
+    }
+}
+
+impl Seek for CountingReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+fn test_data() -> Vec<u8> {
+    (0..=0x63).collect()
+}
+
+fn counting_source(
+    page_size: usize,
+    max_pages: usize,
+) -> Result<(StreamSource<CountingReader>, Arc<AtomicUsize>)> {
+    let reads = Arc::new(AtomicUsize::new(0));
+    let reader = CountingReader {
+        inner: Cursor::new(test_data()),
+        reads: Arc::clone(&reads),
+    };
+    let source = StreamSource::with_cache(reader, 0, Endidness::Big, page_size, max_pages)?;
+    Ok((source, reads))
+}
+
+#[test]
+fn windows() -> Result<()> {
+    let source = StreamSource::with_cache(Cursor::new(test_data()), 10, Endidness::Big, 16, 2)?;
+    assert_eq!(source.size(), 100);
+    assert_eq!(source.lower_offset_limit(), 10);
+    assert_eq!(source.upper_offset_limit(), 110);
+
+    let window = source.window(12, 20)?;
+    assert!(!window.is_copy());
+    assert_eq!(window.initial_offset(), 12);
+    assert_eq!(window.as_ref(), [2, 3, 4, 5, 6, 7, 8, 9]);
+    let segment = window.segment();
+    assert_eq!(segment.current_offset(), 12);
+    assert_eq!(segment.endidness(), Endidness::Big);
+    assert_eq!(segment.u16_at(18)?, 0x0809);
+
+    let window = source.window(20, 60)?;
+    assert!(window.is_copy());
+    assert_eq!(window.as_ref(), &test_data()[10..50]);
+
+    let window = source.all_after(106)?;
+    assert!(!window.is_copy());
+    assert_eq!(window.as_ref(), &test_data()[96..]);
+    assert_eq!(source.all()?.as_ref(), test_data().as_slice());
+    assert!(source.window(110, 110)?.is_empty());
+
+    assert!(matches!(
+        source.window(9, 12),
+        Err(Error::OffsetTooSmall { offset: 9 })
+    ));
+    assert!(matches!(
+        source.get_n(100, 11),
+        Err(Error::OffsetTooLarge { offset: 111 })
+    ));
+    assert!(matches!(
+        source.get_n(100, usize::MAX),
+        Err(Error::OffsetTooLarge { offset: usize::MAX })
+    ));
+    Ok(())
+}
+
+#[test]
+fn page_cache() -> Result<()> {
+    let (source, reads) = counting_source(16, 2)?;
+    let first = source.get_n(0, 4)?;
+    let reads_per_page = reads.load(Ordering::SeqCst);
+    assert!(reads_per_page > 1);
+    // Reads from the same page shouldn't touch the reader.
+    source.get_n(4, 8)?;
+    source.get_n(0, 16)?;
+    assert_eq!(reads.load(Ordering::SeqCst), reads_per_page);
+    assert_eq!(source.cached_pages(), 1);
+
+    source.get_n(16, 4)?;
+    source.get_n(0, 4)?;
+    assert_eq!(source.cached_pages(), 2);
+    let before_eviction = reads.load(Ordering::SeqCst);
+    // The page at 16 is now the least recently used, so it's evicted.
+    source.get_n(32, 4)?;
+    assert_eq!(source.cached_pages(), 2);
+    source.get_n(0, 4)?;
+    assert_eq!(
+        reads.load(Ordering::SeqCst),
+        before_eviction + reads_per_page
+    );
+    source.get_n(16, 4)?;
+    assert_eq!(
+        reads.load(Ordering::SeqCst),
+        before_eviction + reads_per_page * 2
+    );
+
+    // Windows keep their data after it's been evicted.
+    source.clear_cache();
+    assert_eq!(source.cached_pages(), 0);
+    assert_eq!(first.as_ref(), [0, 1, 2, 3]);
+    Ok(())
+}
+
+#[test]
+fn change_offset_and_endidness() -> Result<()> {
+    let mut source = StreamSource::new(Cursor::new(test_data()), 0, Endidness::Big)?;
+    assert_eq!(source.page_size(), crate::DEFAULT_PAGE_SIZE);
+    assert_eq!(source.max_pages(), crate::DEFAULT_MAX_PAGES);
+    source.change_initial_offset(100);
+    source.change_endidness(Endidness::Little);
+    let window = source.get_n(101, 2)?;
+    assert_eq!(window.endidness(), Endidness::Little);
+    assert_eq!(window.segment().next_u16()?, 0x0201);
+    Ok(())
+}
+
+#[test]
+fn with_options() -> Result<()> {
+    let options = SourceOptions::new()
+        .with_initial_offset(0x100)
+        .with_endidness(Endidness::Big)
+        .with_buffer_size(16);
+    let source = StreamSource::with_options(Cursor::new(test_data()), &options)?;
+    assert_eq!(source.page_size(), 16);
+    assert_eq!(source.max_pages(), crate::DEFAULT_MAX_PAGES);
+    assert_eq!(source.lower_offset_limit(), 0x100);
+    assert_eq!(source.get_n(0x10e, 4)?.segment().u32_at(0x10e)?, 0x0e0f1011);
+    Ok(())
+}
+
+#[test]
+fn zero_sized_cache() {
+    for (page_size, max_pages) in [(0, 2), (16, 0)] {
+        assert!(matches!(
+            StreamSource::with_cache(
+                Cursor::new(test_data()),
+                0,
+                Endidness::Big,
+                page_size,
+                max_pages
+            ),
+            Err(Error::Other { .. })
+        ));
+    }
+    let options = SourceOptions::new().with_buffer_size(0);
+    assert!(matches!(
+        StreamSource::with_options(Cursor::new(test_data()), &options),
+        Err(Error::Other { .. })
+    ));
+}
+
+#[test]
+fn stream_segment_tests() -> Result<()> {
+    let data = segment::TEST_U8_DATA.to_vec();
+    let source = StreamSource::with_cache(Cursor::new(data), 7, Endidness::Big, 4, 2)?;
+    segment::basic_test_1_impl(&source.all()?.segment())?;
+    segment::move_by_test_impl(&source.all()?.segment())?;
+    segment::next_n_test_impl(&source.all()?.segment(), 1, 3)?;
+    Ok(())
+}
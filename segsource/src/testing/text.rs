@@ -0,0 +1,195 @@
+use crate::{Endidness, Error, Result, Segment, SegmentMut, U8Sink as _, VecSink};
+
+#[test]
+fn cstr_test() -> Result<()> {
+    let data = b"abc\0\0def\0xyz";
+    let segment = Segment::with_offset(data, 0x10);
+    assert_eq!(segment.peek_cstr()?, "abc");
+    assert_eq!(segment.current_offset(), 0x10);
+    assert_eq!(segment.next_cstr()?, "abc");
+    assert_eq!(segment.next_cstr_bytes()?, b"");
+    assert_eq!(segment.next_cstr()?, "def");
+    assert_eq!(segment.current_offset(), 0x19);
+    assert_eq!(segment.cstr_at(0x15)?, "def");
+    assert!(matches!(
+        segment.next_cstr(),
+        Err(Error::NotEnoughData {
+            requested: 4,
+            left: 3,
+            offset: 0x19
+        })
+    ));
+    assert_eq!(segment.current_offset(), 0x19);
+    Ok(())
+}
+
+#[test]
+fn str_test() -> Result<()> {
+    let data = "héllo wörld".as_bytes();
+    let segment = Segment::new(data);
+    assert_eq!(segment.peek_str(6)?, "héllo");
+    assert_eq!(segment.next_str(6)?, "héllo");
+    assert_eq!(segment.str_at(7, 6)?, "wörld");
+    assert!(matches!(
+        segment.str_at(0, 2),
+        Err(Error::InvalidUtf8 { offset: 1 })
+    ));
+    assert!(matches!(
+        segment.next_str(100),
+        Err(Error::NotEnoughData {
+            requested: 100,
+            left: 7,
+            offset: 6
+        })
+    ));
+    Ok(())
+}
+
+#[test]
+fn invalid_utf8_offset_test() {
+    let data = b"\x00\x00ab\xffcd\0";
+    let segment = Segment::with_offset(data, 0x100);
+    segment.move_to(0x102).unwrap();
+    assert!(matches!(
+        segment.next_cstr(),
+        Err(Error::InvalidUtf8 { offset: 0x104 })
+    ));
+    assert_eq!(segment.current_offset(), 0x102);
+    assert_eq!(segment.next_cstr_bytes().unwrap(), b"ab\xffcd");
+}
+
+#[test]
+fn prefixed_str_test() -> Result<()> {
+    let data = b"\x03abc\x00\x02hi\x00\x00\x00\x01!";
+    let segment = Segment::with_endidness(data, Endidness::Big);
+    assert_eq!(segment.next_prefixed_str::<u8>()?, "abc");
+    assert_eq!(segment.peek_prefixed_str::<u16>()?, "hi");
+    assert_eq!(segment.next_prefixed_bytes::<u16>()?, b"hi");
+    assert_eq!(segment.next_prefixed_str::<u32>()?, "!");
+    assert!(!segment.has_more());
+
+    let segment = Segment::with_endidness(b"\x02\x00hi", Endidness::Little);
+    assert_eq!(segment.prefixed_str_at::<u16>(0)?, "hi");
+
+    let segment = Segment::new(b"\x05ab");
+    assert!(matches!(
+        segment.next_prefixed_str::<u8>(),
+        Err(Error::NotEnoughData {
+            requested: 5,
+            left: 2,
+            offset: 1
+        })
+    ));
+    assert_eq!(segment.current_offset(), 0);
+
+    let segment = Segment::new(b"\x01\xff");
+    assert!(matches!(
+        segment.next_prefixed_str::<u8>(),
+        Err(Error::InvalidUtf8 { offset: 1 })
+    ));
+    Ok(())
+}
+
+#[test]
+fn padded_str_test() -> Result<()> {
+    let data = b"abc\0\0\0xyz   \0junk!";
+    let segment = Segment::new(data);
+    assert_eq!(segment.next_padded_str(6, 0)?, "abc");
+    assert_eq!(segment.current_offset(), 6);
+    assert_eq!(segment.peek_padded_str(6, b' ')?, "xyz");
+    assert_eq!(segment.next_padded_str(11, b' ')?, "xyz");
+    assert_eq!(segment.padded_str_at(0, 2, 0)?, "ab");
+    Ok(())
+}
+
+#[test]
+fn utf16_test() -> Result<()> {
+    let data = [0x00, 0x68, 0x00, 0x69, 0xd8, 0x3d, 0xde, 0x00, 0x00, 0x00];
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    assert_eq!(segment.peek_utf16(2)?, "hi");
+    assert_eq!(segment.next_utf16_cstr()?, "hi\u{1f600}");
+    assert!(!segment.has_more());
+    assert_eq!(segment.utf16_at(4, 2)?, "\u{1f600}");
+
+    let data = [0x68, 0x00, 0x69, 0x00, 0x00, 0x00];
+    let segment = Segment::with_endidness(&data, Endidness::Little);
+    assert_eq!(segment.utf16_cstr_at(0)?, "hi");
+    assert_eq!(segment.next_utf16(2)?, "hi");
+    assert_eq!(segment.current_offset(), 4);
+
+    // An unpaired surrogate
+    let data = [0x68, 0x00, 0x3d, 0xd8, 0x69, 0x00];
+    let segment = Segment::with_offset_and_endidness(&data, 0x10, Endidness::Little);
+    assert!(matches!(
+        segment.next_utf16(3),
+        Err(Error::InvalidUtf16 { offset: 0x12 })
+    ));
+    assert!(matches!(
+        segment.next_utf16_cstr(),
+        Err(Error::NotEnoughData {
+            requested: 8,
+            left: 6,
+            offset: 0x10
+        })
+    ));
+    Ok(())
+}
+
+#[test]
+fn put_str_test() -> Result<()> {
+    let mut sink = VecSink::with_endidness(Endidness::Big);
+    sink.put_cstr("abc")?;
+    sink.put_prefixed_str::<u16>("hi")?;
+    sink.put_padded_str("xy", 4, b' ')?;
+    sink.put_utf16_cstr("é")?;
+    sink.put_str("!")?;
+    assert_eq!(sink.current_offset(), 17);
+    assert!(matches!(
+        sink.put_padded_str("toolong", 4, 0),
+        Err(Error::NotEnoughData {
+            requested: 7,
+            left: 4,
+            offset: 17
+        })
+    ));
+    assert!(matches!(
+        sink.put_prefixed_str::<u8>(&"a".repeat(256)),
+        Err(Error::Other { .. })
+    ));
+    let data = sink.into_vec();
+    assert_eq!(data, b"abc\0\x00\x02hixy  \x00\xe9\x00\x00!");
+
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    assert_eq!(segment.next_cstr()?, "abc");
+    assert_eq!(segment.next_prefixed_str::<u16>()?, "hi");
+    assert_eq!(segment.next_padded_str(4, b' ')?, "xy");
+    assert_eq!(segment.next_utf16_cstr()?, "é");
+    assert_eq!(segment.next_str(1)?, "!");
+    Ok(())
+}
+
+#[test]
+fn put_str_too_long_test() -> Result<()> {
+    let mut data = [0xaa; 6];
+    let mut segment = SegmentMut::new(&mut data);
+    segment.move_to(2)?;
+    assert!(matches!(
+        segment.put_cstr("abcd"),
+        Err(Error::NotEnoughData { .. })
+    ));
+    assert!(matches!(
+        segment.put_padded_str("ab", 5, b' '),
+        Err(Error::NotEnoughData { .. })
+    ));
+    assert!(matches!(
+        segment.put_prefixed_str::<u16>("abc"),
+        Err(Error::NotEnoughData { .. })
+    ));
+    assert!(matches!(
+        segment.put_utf16_cstr("ab"),
+        Err(Error::NotEnoughData { .. })
+    ));
+    assert_eq!(segment.current_offset(), 2);
+    assert_eq!(data, [0xaa; 6]);
+    Ok(())
+}
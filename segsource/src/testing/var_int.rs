@@ -0,0 +1,143 @@
+use crate::{Error, Result, Segment, U8Sink as _, VecSink};
+
+const ULEB128_DATA: [(u64, &[u8]); 6] = [
+    (0, &[0x00]),
+    (127, &[0x7f]),
+    (128, &[0x80, 0x01]),
+    (624485, &[0xe5, 0x8e, 0x26]),
+    (u32::MAX as u64, &[0xff, 0xff, 0xff, 0xff, 0x0f]),
+    (
+        u64::MAX,
+        &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+    ),
+];
+
+const SLEB128_DATA: [(i64, &[u8]); 7] = [
+    (0, &[0x00]),
+    (-1, &[0x7f]),
+    (63, &[0x3f]),
+    (-64, &[0x40]),
+    (64, &[0xc0, 0x00]),
+    (-123456, &[0xc0, 0xbb, 0x78]),
+    (
+        i64::MIN,
+        &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f],
+    ),
+];
+
+const ZIGZAG_DATA: [(i64, &[u8]); 6] = [
+    (0, &[0x00]),
+    (-1, &[0x01]),
+    (1, &[0x02]),
+    (-2, &[0x03]),
+    (i32::MAX as i64, &[0xfe, 0xff, 0xff, 0xff, 0x0f]),
+    (i32::MIN as i64, &[0xff, 0xff, 0xff, 0xff, 0x0f]),
+];
+
+const VLQ_DATA: [(u64, &[u8]); 8] = [
+    (0x00, &[0x00]),
+    (0x40, &[0x40]),
+    (0x7f, &[0x7f]),
+    (0x80, &[0x81, 0x00]),
+    (0x2000, &[0xc0, 0x00]),
+    (0x3fff, &[0xff, 0x7f]),
+    (0x4000, &[0x81, 0x80, 0x00]),
+    (0x0fffffff, &[0xff, 0xff, 0xff, 0x7f]),
+];
+
+macro_rules! make_var_int_test {
+    ($test_name:ident, $data:ident, $at:ident, $peek:ident, $next:ident, $put:ident) => {
+        #[test]
+        fn $test_name() -> Result<()> {
+            for (value, bytes) in $data.iter() {
+                let segment = Segment::with_offset(bytes, 7);
+                assert_eq!(segment.$at(7)?, *value);
+                assert_eq!(segment.$peek()?, *value);
+                assert_eq!(segment.current_offset(), 7);
+                assert_eq!(segment.$next()?, *value);
+                assert!(!segment.has_more());
+                let mut sink = VecSink::new();
+                sink.$put(*value)?;
+                assert_eq!(sink.as_ref(), *bytes);
+            }
+            Ok(())
+        }
+    };
+}
+
+make_var_int_test! {uleb128_test, ULEB128_DATA, uleb128_at, peek_uleb128, next_uleb128, put_uleb128}
+make_var_int_test! {sleb128_test, SLEB128_DATA, sleb128_at, peek_sleb128, next_sleb128, put_sleb128}
+make_var_int_test! {varint_test, ULEB128_DATA, varint_at, peek_varint, next_varint, put_varint}
+make_var_int_test! {zigzag_test, ZIGZAG_DATA, zigzag_at, peek_zigzag, next_zigzag, put_zigzag}
+make_var_int_test! {vlq_test, VLQ_DATA, vlq_at, peek_vlq, next_vlq, put_vlq}
+
+#[test]
+fn invalid_var_int_test() -> Result<()> {
+    let overlong = [0x80; 11];
+    let segment = Segment::with_offset(&overlong, 2);
+    assert!(matches!(
+        segment.next_uleb128(),
+        Err(Error::InvalidVarInt { offset: 2 })
+    ));
+    assert!(matches!(
+        segment.next_vlq(),
+        Err(Error::InvalidVarInt { offset: 2 })
+    ));
+    assert_eq!(segment.current_offset(), 2);
+
+    let overflow = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+    let segment = Segment::new(&overflow);
+    assert!(matches!(
+        segment.uleb128_at(0),
+        Err(Error::InvalidVarInt { offset: 0 })
+    ));
+    assert!(matches!(
+        segment.sleb128_at(0),
+        Err(Error::InvalidVarInt { offset: 0 })
+    ));
+    assert!(matches!(
+        segment.vlq_at(0),
+        Err(Error::InvalidVarInt { offset: 0 })
+    ));
+
+    for overlong in [&[0x80, 0x00][..], &[0xff, 0x80, 0x00], &[0x80, 0x80, 0x00]].iter() {
+        let segment = Segment::new(overlong);
+        assert!(matches!(
+            segment.uleb128_at(0),
+            Err(Error::InvalidVarInt { offset: 0 })
+        ));
+        assert!(matches!(
+            segment.zigzag_at(0),
+            Err(Error::InvalidVarInt { offset: 0 })
+        ));
+    }
+    for overlong in [&[0x80, 0x00][..], &[0x80, 0x81, 0x00]].iter() {
+        let segment = Segment::new(overlong);
+        assert!(matches!(
+            segment.vlq_at(0),
+            Err(Error::InvalidVarInt { offset: 0 })
+        ));
+    }
+    assert_eq!(Segment::new(&[0x81, 0x80, 0x00]).vlq_at(0)?, 0x4000);
+    for overlong in [&[0x80, 0x00][..], &[0xff, 0x7f], &[0xc0, 0x80, 0x00]].iter() {
+        let segment = Segment::new(overlong);
+        assert!(matches!(
+            segment.sleb128_at(0),
+            Err(Error::InvalidVarInt { offset: 0 })
+        ));
+    }
+
+    let truncated = [0x01, 0x80, 0x80];
+    let segment = Segment::new(&truncated);
+    assert_eq!(segment.next_varint()?, 1);
+    assert!(matches!(
+        segment.next_varint(),
+        Err(Error::NotEnoughData {
+            requested: 3,
+            left: 2,
+            offset: 1
+        })
+    ));
+    assert_eq!(segment.current_offset(), 1);
+    Ok(())
+}
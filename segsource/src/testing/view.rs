@@ -0,0 +1,46 @@
+use crate::{Endidness, Error, FixedLayout, Result, Segment};
+
+#[test]
+fn layout_size_test() {
+    assert_eq!(u8::LAYOUT_SIZE, 1);
+    assert_eq!(i32::LAYOUT_SIZE, 4);
+    assert_eq!(f64::LAYOUT_SIZE, 8);
+    assert_eq!(u128::LAYOUT_SIZE, 16);
+    assert_eq!(<[u8; 7]>::LAYOUT_SIZE, 7);
+}
+
+#[test]
+fn view_at_test() -> Result<()> {
+    let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+    let segment = Segment::with_offset_and_endidness(&data, 0x10, Endidness::Big);
+    assert_eq!(segment.view_at::<u16>(0x11)?, 0x0203);
+    assert_eq!(segment.view_at::<[u8; 3]>(0x13)?, &[0x04, 0x05, 0x06]);
+    assert_eq!(segment.current_offset(), 0x10);
+    assert!(matches!(
+        segment.view_at::<u32>(0x13),
+        Err(Error::NotEnoughData {
+            requested: 4,
+            left: 3,
+            offset: 0x13
+        })
+    ));
+    Ok(())
+}
+
+#[test]
+fn next_view_test() -> Result<()> {
+    let data = [0x01, 0x02, 0x03, 0x04, 0x05];
+    let segment = Segment::with_endidness(&data, Endidness::Little);
+    assert_eq!(segment.next_view::<u16>()?, 0x0201);
+    assert_eq!(segment.next_view::<[u8; 2]>()?, &[0x03, 0x04]);
+    assert!(matches!(
+        segment.next_view::<u16>(),
+        Err(Error::NotEnoughData {
+            requested: 2,
+            left: 1,
+            offset: 4
+        })
+    ));
+    assert_eq!(segment.current_offset(), 4);
+    Ok(())
+}
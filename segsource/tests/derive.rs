@@ -0,0 +1,913 @@
+#![cfg(feature = "derive")]
+use core::convert::TryFrom;
+use segsource::{
+    Endidness, Error, FromSegment, PathSegment, Segment, SegmentView, ToSegment, TryFromSegment,
+    U8Sink as _, VecSink,
+};
+
+#[derive(TryFromSegment, ToSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+enum Opcode {
+    #[from_seg(use_if(value_is(u8, 0x00)))]
+    Nop,
+    #[from_seg(use_if(value_is(u8, 0x01)))]
+    Push(u16),
+    #[from_seg(use_if(value_is(u8, 0x02)))]
+    Jump { target: u32 },
+}
+
+#[derive(TryFromSegment, ToSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+enum Chunk {
+    #[from_seg(use_if(value_is(u16, 0xcafe, peek)))]
+    Tagged { tag: u16, value: u8 },
+    #[from_seg(use_if(default))]
+    Raw(u8),
+}
+
+#[test]
+fn enum_value_is() -> Result<(), Error> {
+    let data = [0x01, 0x12, 0x34, 0x00, 0x02, 0x00, 0x00, 0x01, 0x00];
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    assert_eq!(Opcode::try_from(&segment)?, Opcode::Push(0x1234));
+    assert_eq!(Opcode::try_from(&segment)?, Opcode::Nop);
+    assert_eq!(Opcode::try_from(&segment)?, Opcode::Jump { target: 0x100 });
+    assert!(!segment.has_more());
+    Ok(())
+}
+
+#[test]
+fn enum_no_matching_variant() {
+    let data = [0x03, 0x00];
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    assert!(matches!(
+        Opcode::try_from(&segment),
+        Err(Error::NoMatchingVariant {
+            name: "Opcode",
+            offset: 0
+        })
+    ));
+}
+
+#[test]
+fn enum_peek_and_default() -> Result<(), Error> {
+    let data = [0xca, 0xfe, 0x07, 0x09];
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    assert_eq!(
+        Chunk::try_from(&segment)?,
+        Chunk::Tagged {
+            tag: 0xcafe,
+            value: 0x07
+        }
+    );
+    assert_eq!(Chunk::try_from(&segment)?, Chunk::Raw(0x09));
+    Ok(())
+}
+
+fn is_long(segment: &Segment<u8>) -> bool {
+    segment.remaining() >= 4
+}
+
+#[derive(TryFromSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+enum Packet {
+    #[from_seg(use_if(value_is(u8, 0x01), predicate(is_long)))]
+    Long(u32),
+    #[from_seg(use_if(value_is(u8, 0x00, 0x10)))]
+    Trailer(u8),
+    #[from_seg(use_if(default))]
+    Short(u8, u8),
+}
+
+#[test]
+fn enum_failed_condition_restores_offset() -> Result<(), Error> {
+    let data = [0x01, 0x02];
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    assert_eq!(Packet::try_from(&segment)?, Packet::Short(0x01, 0x02));
+    let data = [0x01, 0x00, 0x00, 0x00, 0x05];
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    assert_eq!(Packet::try_from(&segment)?, Packet::Long(5));
+    Ok(())
+}
+
+#[derive(TryFromSegment, ToSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+struct Record {
+    version: u8,
+    count: u16,
+    #[from_seg(parse_each, size(count))]
+    values: Vec<u32>,
+    #[from_seg(if(version > 1))]
+    extra: Option<u16>,
+    #[from_seg(if(version > 2), default(0xff))]
+    flags: u8,
+    #[from_seg(skip)]
+    cached: u32,
+    #[from_seg(move_by(2), parse_each, size(2))]
+    opcodes: Vec<Opcode>,
+}
+
+#[test]
+fn struct_round_trip() -> Result<(), Error> {
+    let records = [
+        Record {
+            version: 1,
+            count: 2,
+            values: vec![0xdeadbeef, 0x01020304],
+            extra: None,
+            flags: 0xff,
+            cached: 0,
+            opcodes: vec![Opcode::Push(0x0506), Opcode::Jump { target: 7 }],
+        },
+        Record {
+            version: 3,
+            count: 0,
+            values: vec![],
+            extra: Some(0x1234),
+            flags: 0x07,
+            cached: 0,
+            opcodes: vec![Opcode::Nop, Opcode::Nop],
+        },
+    ];
+    for record in records.iter() {
+        for &endidness in [Endidness::Big, Endidness::Little].iter() {
+            let data = record.to_bytes_with_endidness(endidness)?;
+            let segment = Segment::with_endidness(&data, endidness);
+            assert_eq!(&Record::try_from(&segment)?, record);
+            assert!(!segment.has_more());
+        }
+    }
+    Ok(())
+}
+
+#[derive(TryFromSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+struct Cached {
+    value: u8,
+    #[from_seg(skip, default(7))]
+    hits: u8,
+}
+
+#[test]
+fn skip_with_default() -> Result<(), Error> {
+    let segment = Segment::new(&[0x02]);
+    assert_eq!(Cached::try_from(&segment)?, Cached { value: 2, hits: 7 });
+    assert!(!segment.has_more());
+    Ok(())
+}
+
+#[test]
+fn enum_write() -> Result<(), Error> {
+    let mut sink = VecSink::with_endidness(Endidness::Big);
+    Opcode::Push(0x1234).write_to(&mut sink)?;
+    Opcode::Nop.write_to(&mut sink)?;
+    Opcode::Jump { target: 0x100 }.write_to(&mut sink)?;
+    Chunk::Tagged {
+        tag: 0xcafe,
+        value: 0x07,
+    }
+    .write_to(&mut sink)?;
+    Chunk::Raw(0x09).write_to(&mut sink)?;
+    assert_eq!(sink.current_offset(), 13);
+    assert_eq!(
+        sink.into_vec(),
+        [0x01, 0x12, 0x34, 0x00, 0x02, 0x00, 0x00, 0x01, 0x00, 0xca, 0xfe, 0x07, 0x09]
+    );
+    Ok(())
+}
+
+#[derive(TryFromSegment, ToSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+struct VarInts {
+    #[from_seg(uleb128)]
+    length: u32,
+    #[from_seg(sleb128)]
+    delta: i16,
+    #[from_seg(zigzag)]
+    signed: i64,
+    #[from_seg(vlq)]
+    ticks: u64,
+    #[from_seg(varint)]
+    count: usize,
+    #[from_seg(varint, parse_each, size(count))]
+    ids: Vec<u64>,
+}
+
+#[test]
+fn var_int_round_trip() -> Result<(), Error> {
+    let value = VarInts {
+        length: 624485,
+        delta: -300,
+        signed: -2,
+        ticks: 0x4000,
+        count: 2,
+        ids: vec![1, 300],
+    };
+    let data = value.to_bytes()?;
+    assert_eq!(
+        data,
+        [0xe5, 0x8e, 0x26, 0xd4, 0x7d, 0x03, 0x81, 0x80, 0x00, 0x02, 0x01, 0xac, 0x02]
+    );
+    let segment = Segment::new(&data);
+    assert_eq!(VarInts::try_from(&segment)?, value);
+
+    let too_big = [0x80, 0x80, 0x80, 0x80, 0x10];
+    let segment = Segment::new(&too_big);
+    let error = VarInts::try_from(&segment).unwrap_err();
+    assert!(matches!(
+        error.root_cause(),
+        Error::InvalidVarInt { offset: 0 }
+    ));
+    assert_eq!(error.path().unwrap().to_string(), "VarInts.length");
+    Ok(())
+}
+
+#[derive(TryFromSegment, ToSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+struct Strings {
+    #[from_seg(cstr)]
+    name: String,
+    #[from_seg(str(len_prefix = u16))]
+    title: String,
+    #[from_seg(str(padded = 6, pad = b' '))]
+    code: String,
+    count: u8,
+    #[from_seg(str(len = count))]
+    tag: String,
+    #[from_seg(cstr, parse_each, size(2))]
+    aliases: Vec<String>,
+    #[from_seg(utf16)]
+    wide: String,
+    #[from_seg(utf16(len = count))]
+    wide_tag: String,
+}
+
+#[test]
+fn str_round_trip() -> Result<(), Error> {
+    let value = Strings {
+        name: "segsource".into(),
+        title: "héllo".into(),
+        code: "AB1".into(),
+        count: 3,
+        tag: "xyz".into(),
+        aliases: vec!["a".into(), "bc".into()],
+        wide: "é".into(),
+        wide_tag: "abc".into(),
+    };
+    let data = value.to_bytes_with_endidness(Endidness::Big)?;
+    assert_eq!(
+        data.as_slice(),
+        &b"segsource\0\x00\x06h\xc3\xa9lloAB1   \x03xyza\0bc\0\x00\xe9\x00\x00\x00a\x00b\x00c"[..]
+    );
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    assert_eq!(Strings::try_from(&segment)?, value);
+
+    let bad = b"ab\xff\0";
+    let segment = Segment::new(bad);
+    let error = Strings::try_from(&segment).unwrap_err();
+    assert!(matches!(
+        error.root_cause(),
+        Error::InvalidUtf8 { offset: 2 }
+    ));
+    assert_eq!(error.offset(), Some(2));
+    Ok(())
+}
+
+// These structs only describe the layouts, so their fields are never read directly.
+#[allow(dead_code)]
+#[derive(SegmentView)]
+struct Header {
+    magic: [u8; 4],
+    version: u16,
+}
+
+#[allow(dead_code)]
+#[derive(SegmentView)]
+struct IndexEntry {
+    header: Header,
+    key: u64,
+    weight: f32,
+    flags: i8,
+}
+
+#[test]
+fn segment_view() -> Result<(), Error> {
+    assert_eq!(HeaderView::LAYOUT_SIZE, 6);
+    assert_eq!(IndexEntryView::LAYOUT_SIZE, 19);
+    let mut sink = VecSink::with_offset_and_endidness(0x100, Endidness::Little);
+    for i in 0..3u8 {
+        sink.put_bytes(b"IDX\0")?;
+        sink.put_u16(i as u16 + 1)?;
+        sink.put_u64(0x1122_3344_5566_7700 | i as u64)?;
+        sink.put_f32(i as f32 * 0.5)?;
+        sink.put_i8(-(i as i8))?;
+    }
+    let data = sink.into_vec();
+    let segment = Segment::with_offset_and_endidness(&data, 0x100, Endidness::Little);
+    let views: Vec<IndexEntryView> = (0..3)
+        .map(|_| IndexEntryView::try_from(&segment))
+        .collect::<Result<_, _>>()?;
+    assert!(!segment.has_more());
+    for (i, view) in views.iter().enumerate() {
+        assert_eq!(view.header().magic(), b"IDX\0");
+        assert_eq!(view.header().version(), i as u16 + 1);
+        assert_eq!(view.key(), 0x1122_3344_5566_7700 | i as u64);
+        assert_eq!(view.weight(), i as f32 * 0.5);
+        assert_eq!(view.flags(), -(i as i8));
+        assert_eq!(view.segment().initial_offset(), 0x100 + i * 19);
+    }
+
+    let view = segment.view_at::<IndexEntry>(0x100 + 19)?;
+    assert_eq!(view.header().version(), 2);
+    let view = IndexEntryView::new(Segment::with_endidness(&data, Endidness::Little))?;
+    assert_eq!(view.key(), 0x1122_3344_5566_7700);
+    assert!(matches!(
+        HeaderView::new(Segment::new(&data[..5])),
+        Err(Error::NotEnoughData {
+            requested: 6,
+            left: 5,
+            offset: 0
+        })
+    ));
+    Ok(())
+}
+
+#[allow(dead_code)]
+#[derive(TryFromSegment, Debug)]
+#[from_seg(error(Error))]
+struct Section {
+    kind: u8,
+    #[from_seg(cstr)]
+    name: String,
+}
+
+#[allow(dead_code)]
+#[derive(TryFromSegment, Debug)]
+#[from_seg(error(Error))]
+struct SectionTable {
+    count: u8,
+    #[from_seg(parse_each, size(count))]
+    sections: Vec<Section>,
+}
+
+#[test]
+fn error_context() {
+    let data = b"\x04\x01a\0\x02bc\0\x03\0\x04de";
+    let segment = Segment::with_offset(data, 0x100);
+    let error = SectionTable::try_from(&segment).unwrap_err();
+    assert_eq!(
+        error.path().unwrap().to_string(),
+        "SectionTable.sections[3].name"
+    );
+    assert_eq!(
+        error.path().unwrap().segments(),
+        [
+            PathSegment::Type("SectionTable"),
+            PathSegment::Field("sections"),
+            PathSegment::Index(3),
+            PathSegment::Type("Section"),
+            PathSegment::Field("name"),
+        ]
+    );
+    assert!(matches!(
+        error.root_cause(),
+        Error::NotEnoughData {
+            requested: 3,
+            left: 2,
+            offset: 0x10b
+        }
+    ));
+    assert_eq!(error.offset(), Some(0x10b));
+    assert_eq!(error.segment_range(), Some(0x100..0x10d));
+    assert_eq!(
+        error.to_string(),
+        "Requested 3 bytes at offset 0x10b, but only 2 bytes left. (in SectionTable.sections[3].name, \
+         parsed from 0x100 to 0x10d)"
+    );
+
+    let error = Opcode::try_from(&Segment::new(&[0x02, 0x00])).unwrap_err();
+    assert_eq!(error.path().unwrap().to_string(), "Opcode::Jump.target");
+}
+
+#[derive(Debug)]
+enum CustomError {
+    Segsource(Error),
+    BadKind(u8),
+}
+
+impl From<Error> for CustomError {
+    fn from(error: Error) -> Self {
+        Self::Segsource(error)
+    }
+}
+
+fn check_kind(kind: u8) -> Result<u8, CustomError> {
+    if kind < 0x80 {
+        Ok(kind)
+    } else {
+        Err(CustomError::BadKind(kind))
+    }
+}
+
+#[allow(dead_code)]
+#[derive(TryFromSegment, Debug)]
+#[from_seg(error(CustomError))]
+struct Kind {
+    #[from_seg(parser(check_kind(segment.next_u8()?)))]
+    kind: u8,
+}
+
+#[allow(dead_code)]
+#[derive(TryFromSegment, Debug)]
+#[from_seg(error(CustomError))]
+struct Kinds {
+    first: Kind,
+    second: Kind,
+    size: u16,
+}
+
+#[test]
+fn error_context_custom_error() {
+    let segment = Segment::new(&[0x01, 0x81]);
+    assert!(matches!(
+        Kinds::try_from(&segment),
+        Err(CustomError::BadKind(0x81))
+    ));
+    let segment = Segment::new(&[0x01, 0x02, 0x03]);
+    match Kinds::try_from(&segment) {
+        Err(CustomError::Segsource(error)) => {
+            assert_eq!(error.path().unwrap().to_string(), "Kinds.size")
+        }
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[derive(TryFromSegment, ToSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+struct Framed {
+    len: u8,
+    #[from_seg(parse_each, size(len))]
+    payload: Vec<u8>,
+    #[from_seg(verify_crc32(range = 0..len as usize + 1))]
+    crc: u32,
+    #[from_seg(verify_checksum(segsource::checksum::Crc16Xmodem, range = 1..len as usize + 1))]
+    check: u16,
+}
+
+#[test]
+fn verify_checksums() -> Result<(), Error> {
+    use segsource::checksum::{Checksum as _, Crc16Xmodem, Crc32};
+    let framed = Framed {
+        len: 9,
+        payload: b"123456789".to_vec(),
+        crc: Crc32::checksum(b"\x09123456789"),
+        check: Crc16Xmodem::checksum(b"123456789"),
+    };
+    let mut data = framed.to_bytes_with_endidness(Endidness::Little)?;
+    let segment = Segment::with_endidness(&data, Endidness::Little);
+    assert_eq!(Framed::try_from(&segment)?, framed);
+    data[3] = b'0';
+    let segment = Segment::with_endidness(&data, Endidness::Little);
+    let error = Framed::try_from(&segment).unwrap_err();
+    assert_eq!(error.path().unwrap().to_string(), "Framed.crc");
+    match error.root_cause() {
+        Error::ChecksumMismatch {
+            expected,
+            actual,
+            offset,
+        } => {
+            assert_eq!(*expected, framed.crc as u64);
+            assert_eq!(*actual, Crc32::checksum(&data[..10]) as u64);
+            assert_eq!(*offset, 10);
+        }
+        other => panic!("Unexpected error: {:?}", other),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "deflate")]
+#[allow(dead_code)]
+#[derive(TryFromSegment, Debug)]
+#[from_seg(error(Error))]
+struct Compressed {
+    len: u8,
+    #[from_seg(decompress = "zlib", size(len))]
+    header: SectionTable,
+    #[from_seg(decompress = "zlib")]
+    record: Record,
+}
+
+#[cfg(feature = "deflate")]
+#[allow(dead_code)]
+#[derive(TryFromSegment, Debug)]
+#[from_seg(error(Error))]
+struct LimitedCompressed {
+    #[from_seg(decompress = "zlib", max_decompressed_size(1))]
+    header: SectionTable,
+}
+
+#[cfg(feature = "deflate")]
+#[test]
+fn decompress() -> Result<(), Error> {
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write as _;
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+    let record = Record {
+        version: 1,
+        count: 1,
+        values: vec![0xdeadbeef],
+        extra: None,
+        flags: 0xff,
+        cached: 0,
+        opcodes: vec![Opcode::Nop, Opcode::Nop],
+    };
+    let header = zlib(&[0]);
+    let mut data = vec![header.len() as u8];
+    data.extend_from_slice(&header);
+    data.extend_from_slice(&zlib(&record.to_bytes_with_endidness(Endidness::Big)?));
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    let compressed = Compressed::try_from(&segment)?;
+    assert_eq!(compressed.record, record);
+    assert!(!segment.has_more());
+
+    // The record is missing its opcodes.
+    let mut data = vec![header.len() as u8];
+    data.extend_from_slice(&header);
+    let start = data.len();
+    data.extend_from_slice(&zlib(&[1, 0, 1, 0xde, 0xad, 0xbe, 0xef]));
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    let error = Compressed::try_from(&segment).unwrap_err();
+    assert_eq!(error.path().unwrap().to_string(), "Compressed.record");
+    assert_eq!(error.segment_range(), Some(0..data.len()));
+    match error {
+        Error::Context { error, .. } => match *error {
+            Error::InCompressed {
+                start: error_start,
+                end,
+                error,
+            } => {
+                assert_eq!((error_start, end), (start, data.len()));
+                assert_eq!(error.path().unwrap().to_string(), "Record.opcodes");
+            }
+            other => panic!("Unexpected error: {:?}", other),
+        },
+        other => panic!("Unexpected error: {:?}", other),
+    }
+
+    let segment = Segment::new(&header);
+    LimitedCompressed::try_from(&segment)?;
+    let data = zlib(&[0, 0]);
+    let segment = Segment::new(&data);
+    let error = LimitedCompressed::try_from(&segment).unwrap_err();
+    assert_eq!(
+        error.path().unwrap().to_string(),
+        "LimitedCompressed.header"
+    );
+    assert!(matches!(
+        error.root_cause(),
+        Error::Decompression { offset: 0, .. }
+    ));
+    Ok(())
+}
+
+#[derive(TryFromSegment, ToSegment, Debug, PartialEq)]
+#[from_seg(error(Error), magic = b"SEGS")]
+struct Archive {
+    #[from_seg(magic = 0xfeed_u16)]
+    version: u8,
+    #[from_seg(magic(b"\x01", peek))]
+    kind: u8,
+    #[from_seg(if(kind == 1), magic = b"X")]
+    extra: Option<u8>,
+}
+
+#[test]
+fn magic() -> Result<(), Error> {
+    let archive = Archive {
+        version: 2,
+        kind: 1,
+        extra: Some(3),
+    };
+    for &endidness in [Endidness::Big, Endidness::Little].iter() {
+        let data = archive.to_bytes_with_endidness(endidness)?;
+        let mut expected = b"SEGS".to_vec();
+        expected.extend_from_slice(&0xfeed_u16.to_bytes_with_endidness(endidness)?);
+        expected.extend_from_slice(b"\x02\x01X\x03");
+        assert_eq!(data, expected);
+        let segment = Segment::with_endidness(&data, endidness);
+        assert_eq!(Archive::try_from(&segment)?, archive);
+        assert!(!segment.has_more());
+    }
+
+    let error = Archive::try_from(&Segment::new(b"SEGZ")).unwrap_err();
+    match error {
+        Error::BadMagic {
+            expected,
+            found,
+            offset,
+        } => {
+            assert_eq!(expected, b"SEGS");
+            assert_eq!(found, b"SEGZ");
+            assert_eq!(offset, 0);
+        }
+        other => panic!("Unexpected error: {:?}", other),
+    }
+
+    let data = b"SEGS\xfe\xed\x02\x02";
+    let error = Archive::try_from(&Segment::with_endidness(data, Endidness::Big)).unwrap_err();
+    assert_eq!(error.path().unwrap().to_string(), "Archive.kind");
+    assert!(matches!(
+        error.root_cause(),
+        Error::BadMagic { offset: 7, .. }
+    ));
+    Ok(())
+}
+
+#[derive(TryFromSegment, ToSegment, Debug, PartialEq)]
+#[from_seg(error(Error), align(8), pad_to(16, zeroed))]
+struct Entry {
+    kind: u8,
+    #[from_seg(align(4, zeroed))]
+    value: u32,
+    #[from_seg(pad_after(2))]
+    flags: u16,
+    #[from_seg(pad_to(3))]
+    tag: u8,
+}
+
+#[derive(TryFromSegment, ToSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+struct EntryTable {
+    count: u8,
+    #[from_seg(parse_each, size(count))]
+    entries: Vec<Entry>,
+}
+
+#[test]
+fn padding() -> Result<(), Error> {
+    let table = EntryTable {
+        count: 2,
+        entries: vec![
+            Entry {
+                kind: 1,
+                value: 0xdeadbeef,
+                flags: 0x0102,
+                tag: 7,
+            },
+            Entry {
+                kind: 2,
+                value: 3,
+                flags: 4,
+                tag: 5,
+            },
+        ],
+    };
+    let mut data = table.to_bytes_with_endidness(Endidness::Big)?;
+    assert_eq!(data.len(), 40);
+    assert_eq!(
+        &data[..24],
+        b"\x02\0\0\0\0\0\0\0\x01\0\0\0\xde\xad\xbe\xef\x01\x02\0\0\x07\0\0\0"
+    );
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    assert_eq!(EntryTable::try_from(&segment)?, table);
+    assert!(!segment.has_more());
+
+    // Padding that isn't zeroed can be anything.
+    data[18] = 0xff;
+    data[22] = 0xff;
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    assert_eq!(EntryTable::try_from(&segment)?, table);
+
+    data[10] = 0xff;
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    let error = EntryTable::try_from(&segment).unwrap_err();
+    assert_eq!(
+        error.path().unwrap().to_string(),
+        "EntryTable.entries[0].value"
+    );
+    assert!(matches!(
+        error.root_cause(),
+        Error::NonZeroPadding { offset: 10 }
+    ));
+
+    data[10] = 0;
+    data[39] = 0xff;
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    let error = EntryTable::try_from(&segment).unwrap_err();
+    assert!(matches!(
+        error.root_cause(),
+        Error::NonZeroPadding { offset: 39 }
+    ));
+    Ok(())
+}
+
+#[derive(TryFromSegment, Debug, PartialEq)]
+#[from_seg(error(Error), temp(len: u8))]
+struct Message {
+    #[from_seg(parse_each, size(len))]
+    body: Vec<u8>,
+    #[from_seg(calc = body.iter().fold(0, |total, b| total + *b as u32))]
+    total: u32,
+    #[from_seg(calc = (len as usize * 2, body.first().copied()))]
+    doubled_and_first: (usize, Option<u8>),
+    #[from_seg(if(doubled_and_first.0 > 4))]
+    extra: Option<u8>,
+}
+
+#[derive(TryFromSegment, Debug, PartialEq)]
+#[from_seg(error(Error), temp(#[from_seg(uleb128)] len: u32, tag: u8))]
+struct Tagged {
+    #[from_seg(parse_each, size(len))]
+    values: Vec<u8>,
+    #[from_seg(calc = tag)]
+    tag: u8,
+}
+
+#[derive(TryFromSegment, ToSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+struct Counted(
+    u8,
+    #[from_seg(parse_each, size(_0))] Vec<u16>,
+    #[from_seg(calc = _0 as usize + _1.len())] usize,
+);
+
+#[derive(TryFromSegment, ToSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+struct Flagged {
+    flags: u8,
+    #[from_seg(if(flags & 1 == 1))]
+    extra: Option<u16>,
+    segment: u8,
+}
+
+#[test]
+fn field_references() -> Result<(), Error> {
+    let message = Message::try_from(&Segment::new(b"\x03\x01\x02\x03\x04"))?;
+    assert_eq!(
+        message,
+        Message {
+            body: vec![1, 2, 3],
+            total: 6,
+            doubled_and_first: (6, Some(1)),
+            extra: Some(4),
+        }
+    );
+    let message = Message::try_from(&Segment::new(b"\x01\x05"))?;
+    assert_eq!(message.total, 5);
+    assert_eq!(message.extra, None);
+
+    let tagged = Tagged::try_from(&Segment::new(b"\x02\x07\x01\x02"))?;
+    assert_eq!(
+        tagged,
+        Tagged {
+            values: vec![1, 2],
+            tag: 7,
+        }
+    );
+    // Errors in temps say which one was being parsed.
+    let error = Tagged::try_from(&Segment::new(b"\x02")).unwrap_err();
+    assert_eq!(error.path().unwrap().to_string(), "Tagged.tag");
+
+    let counted = Counted(2, vec![0x0102, 0x0304], 4);
+    let data = counted.to_bytes_with_endidness(Endidness::Big)?;
+    assert_eq!(data, b"\x02\x01\x02\x03\x04");
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    assert_eq!(Counted::try_from(&segment)?, counted);
+
+    for &flags in [0, 1].iter() {
+        let flagged = Flagged {
+            flags,
+            extra: if flags == 1 { Some(0x1234) } else { None },
+            segment: 7,
+        };
+        let data = flagged.to_bytes_with_endidness(Endidness::Little)?;
+        let segment = Segment::with_endidness(&data, Endidness::Little);
+        assert_eq!(Flagged::try_from(&segment)?, flagged);
+        assert!(!segment.has_more());
+    }
+    Ok(())
+}
+
+#[test]
+fn parse_iter() -> Result<(), Error> {
+    let data = [0x01, 0x00, 0x05, 0x00, 0x02, 0x00, 0x00, 0x00, 0x10, 0x03];
+    let segment = Segment::with_endidness(&data, Endidness::Big);
+    let opcodes = segment
+        .parse_n::<Opcode>(3)
+        .with_offsets()
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        opcodes,
+        [
+            (Opcode::Push(5), 0..3),
+            (Opcode::Nop, 3..4),
+            (Opcode::Jump { target: 0x10 }, 4..9),
+        ]
+    );
+
+    // The unknown opcode at the end stops the iterator.
+    segment.move_to(0)?;
+    let mut iter = segment.parse_iter::<Opcode>();
+    assert_eq!(
+        iter.nth(2).transpose()?,
+        Some(Opcode::Jump { target: 0x10 })
+    );
+    assert!(matches!(iter.next(), Some(Err(_))));
+    assert!(iter.next().is_none());
+    assert_eq!(segment.current_offset(), 9);
+    Ok(())
+}
+
+#[derive(TryFromSegment, Debug, PartialEq)]
+#[from_seg(error(Error), ctx(version: u8, wide: bool))]
+struct Row {
+    id: u8,
+    #[from_seg(if(version >= 2))]
+    flags: Option<u8>,
+    #[from_seg(if(wide))]
+    extra: Option<u16>,
+}
+
+#[derive(TryFromSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+struct Rows {
+    version: u8,
+    count: u8,
+    #[from_seg(parse_each, size(count), ctx(version, version > 2))]
+    rows: Vec<Row>,
+}
+
+#[derive(TryFromSegment, Debug, PartialEq)]
+#[from_seg(error(Error), ctx<T: Copy + Into<u32>>(scale: T), temp(raw: u8))]
+struct Scaled {
+    #[from_seg(calc = raw as u32 * scale.into())]
+    value: u32,
+}
+
+#[derive(TryFromSegment, Debug, PartialEq)]
+#[from_seg(error(Error), also_needs(base: u32), temp(relative: u16))]
+struct Pointer {
+    #[from_seg(calc = base + relative as u32)]
+    target: u32,
+}
+
+#[derive(TryFromSegment, Debug, PartialEq)]
+#[from_seg(error(Error))]
+struct Pointers {
+    #[from_seg(ctx(0x100))]
+    first: Pointer,
+    #[from_seg(ctx(0x200))]
+    second: Pointer,
+}
+
+#[test]
+fn context() -> Result<(), Error> {
+    let segment = Segment::with_endidness(&[2, 2, 1, 0xa, 2, 0xb], Endidness::Big);
+    assert_eq!(
+        Rows::try_from(&segment)?.rows,
+        [
+            Row {
+                id: 1,
+                flags: Some(0xa),
+                extra: None,
+            },
+            Row {
+                id: 2,
+                flags: Some(0xb),
+                extra: None,
+            },
+        ]
+    );
+    let segment = Segment::with_endidness(&[3, 1, 1, 0xa, 0, 0xb], Endidness::Big);
+    assert_eq!(
+        Rows::parse(&segment, ())?.rows,
+        [Row {
+            id: 1,
+            flags: Some(0xa),
+            extra: Some(0xb),
+        }]
+    );
+    let segment = Segment::new(&[4]);
+    assert_eq!(Row::parse(&segment, (1, false))?.flags, None);
+
+    let segment = Segment::new(&[7, 7]);
+    assert_eq!(Scaled::parse(&segment, 3_u8)?.value, 21);
+    assert_eq!(Scaled::parse(&segment, 1000_u16)?.value, 7000);
+
+    let segment = Segment::with_endidness(&[0, 1, 0, 2], Endidness::Big);
+    assert_eq!(
+        Pointers::try_from(&segment)?,
+        Pointers {
+            first: Pointer { target: 0x101 },
+            second: Pointer { target: 0x202 },
+        }
+    );
+    segment.move_to(0)?;
+    assert_eq!(Pointer::try_from((0x10, &segment))?.target, 0x11);
+    assert_eq!(Pointer::parse(&segment, 0x20)?.target, 0x22);
+    Ok(())
+}